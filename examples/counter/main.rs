@@ -10,10 +10,10 @@ use log::LevelFilter;
 use rand::random;
 use simplelog::{Config, WriteLogger};
 
-use yui::{AfterFlow, ArcYard, Before, Cling, Confine, console, Create, Flow, Pack, Padding, Sendable, SenderLink, Spark, yard};
+use yui::{AfterFlow, ArcYard, Before, Cling, Command, Confine, console, Create, Flow, Pack, Padding, Sendable, SenderLink, Spark, yard};
 
 use yui::palette::{FillColor, FillGrade, StrokeColor};
-use yui::yard::{ButtonModel, ButtonAction, Priority, SubmitAffordance};
+use yui::yard::{ButtonModel, Priority, SubmitAffordance};
 
 fn main() -> Result<(), Box<dyn Error>> {
 	let log_file = File::create("counter.log")?;
@@ -24,7 +24,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 	pub struct MainState {
 		value: i32,
 		buttons: Vec<ButtonModel>,
-		index: HashMap<i32, usize>,
+		index: HashMap<i32, (usize, MainAction)>,
 	}
 	impl MainState {
 		pub fn increment(&self) -> Self {
@@ -36,16 +36,16 @@ fn main() -> Result<(), Box<dyn Error>> {
 		pub fn zero(&self) -> Self {
 			MainState { value: 0, ..self.clone() }.release()
 		}
-		pub fn press(&self, button_id: i32) -> Self {
-			let position = self.index.get(&button_id).cloned().expect("Button position");
+		pub fn press(&self, button_id: i32) -> (Self, Option<Command<MainAction>>) {
+			let (position, release) = self.index.get(&button_id).cloned().expect("Button position");
 			let mut buttons = self.buttons.clone();
-			let button = &buttons[position];
-			buttons[position] = button.update(ButtonAction::Press);
-			MainState { buttons, ..self.clone() }
+			let (button, release) = buttons[position].press(release);
+			buttons[position] = button;
+			(MainState { buttons, ..self.clone() }, release)
 		}
 		fn release(&self) -> Self {
 			let buttons = self.buttons.iter().map(|button| {
-				button.update(ButtonAction::Release)
+				button.release()
 			}).collect();
 			MainState { buttons, ..self.clone() }
 		}
@@ -75,34 +75,36 @@ fn main() -> Result<(), Box<dyn Error>> {
 					id: plus,
 					label: "+".into(),
 					affordance: SubmitAffordance::Enabled { priority: Priority::Default, press_link: MainAction::Press(plus).to_sync(ctx.link()) },
-					release_trigger: MainAction::Increment.into_trigger_link(ctx.link()),
 				},
 				ButtonModel {
 					id: minus,
 					label: "-".into(),
 					affordance: SubmitAffordance::Enabled { priority: Priority::None, press_link: MainAction::Press(minus).to_sync(ctx.link()) },
-					release_trigger: MainAction::Decrement.into_trigger_link(ctx.link()),
 				},
 				ButtonModel {
 					id: zero,
 					label: "0".into(),
 					affordance: SubmitAffordance::Enabled { priority: Priority::None, press_link: MainAction::Press(zero).to_sync(ctx.link()) },
-					release_trigger: MainAction::Zero.into_trigger_link(ctx.link()),
 				},
 				ButtonModel {
 					id: done,
 					label: "X".into(),
 					affordance: SubmitAffordance::Enabled { priority: Priority::None, press_link: MainAction::Press(done).to_sync(ctx.link()) },
-					release_trigger: MainAction::Done.into_trigger_link(ctx.link()),
 				},
 			];
-			let index = buttons.iter().enumerate().map(|(i, button)| (button.id, i)).collect::<HashMap<_, _>>();
+			let releases = [MainAction::Increment, MainAction::Decrement, MainAction::Zero, MainAction::Done];
+			let index = buttons.iter().zip(releases.iter()).enumerate()
+				.map(|(i, (button, release))| (button.id, (i, *release)))
+				.collect::<HashMap<_, _>>();
 			MainState { value: 0, buttons, index }
 		}
 
-		fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+		fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 			match action {
-				MainAction::Press(button_id) => AfterFlow::Revise(ctx.state().press(button_id)),
+				MainAction::Press(button_id) => {
+					let (state, release) = ctx.state().press(button_id);
+					AfterFlow::ReviseAnd(state, release.into_iter().collect())
+				}
 				MainAction::Increment => AfterFlow::Revise(ctx.state().increment()),
 				MainAction::Decrement => AfterFlow::Revise(ctx.state().decrement()),
 				MainAction::Zero => AfterFlow::Revise(ctx.state().zero()),
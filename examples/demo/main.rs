@@ -88,15 +88,15 @@ impl story::Spark for Main {
 		};
 		State {
 			main_tab: AppTab::from_index(0),
-			dialog_story: edge.sub_story(DialogDemo { dialog: self.dialog_id, next_dialog: self.dialog_id + 1 }, dialog_reports).story_id,
-			form_story: edge.sub_story(FormListDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
-			selector_story: edge.sub_story(SelectorListDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
-			text_story: edge.sub_story(TextDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
-			buttons_story: edge.sub_story(ButtonDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
+			dialog_story: edge.sub_story("dialog", DialogDemo { dialog: self.dialog_id, next_dialog: self.dialog_id + 1 }, dialog_reports).story_id,
+			form_story: edge.sub_story("form", FormListDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
+			selector_story: edge.sub_story("selector", SelectorListDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
+			text_story: edge.sub_story("text", TextDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
+			buttons_story: edge.sub_story("buttons", ButtonDemo {}, Some(SenderLink::wrap_sink(ctx.link().callback(select_tab)))).story_id,
 		}
 	}
 
-	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		info!("{:?}", action);
 		match action {
 			MainAction::SetTab(tab) => {
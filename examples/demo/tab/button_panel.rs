@@ -7,7 +7,7 @@ use yui::{Sendable, SenderLink};
 use yui::palette::FillGrade::Plain;
 use yui::prelude::*;
 use yui::sparks::selection_editor::SelectionEditorSpark;
-use yui::yard::{ButtonAction, ButtonModel, SubmitAffordance};
+use yui::yard::{ButtonModel, SubmitAffordance};
 
 use crate::AppTab;
 
@@ -33,17 +33,18 @@ pub struct State {
 }
 
 impl State {
-	pub fn press_button(&self, key: (usize, usize)) -> Self {
+	pub fn press_button(&self, key: (usize, usize)) -> (Self, Option<Command<Action>>) {
 		let mut buttons = self.buttons.clone();
 		let button = buttons.remove(&key).expect("proper button");
-		let pressed_button = button.update(ButtonAction::Press);
+		let release = if key == (1, 0) { Action::OfferChoice } else { Action::ReleaseIgnore };
+		let (pressed_button, release) = button.press(release);
 		buttons.insert(key, pressed_button);
-		State { choice: self.choice, buttons }
+		(State { choice: self.choice, buttons }, release)
 	}
 	pub fn release_buttons(&self) -> Self {
 		let buttons = self.buttons.iter()
 			.map(|(key, button)| {
-				let released_button = button.update(ButtonAction::Release);
+				let released_button = button.release();
 				(*key, released_button)
 			})
 			.collect();
@@ -86,36 +87,32 @@ impl Spark for ButtonDemo {
 		buttons.insert((0, 0), ButtonModel {
 			id: random(),
 			label: "Garfunkel".to_string(),
-			release_trigger: Action::ReleaseIgnore.to_send(&ctx.link()),
 			affordance: SubmitAffordance::enabled(Action::PressButton(0, 0).to_sync(ctx.link())),
 		});
 		buttons.insert((0, 1), ButtonModel {
 			id: random(),
 			label: "Simon".to_string(),
-			release_trigger: Action::ReleaseIgnore.to_send(&ctx.link()),
 			affordance: SubmitAffordance::enabled(Action::PressButton(0, 1).to_sync(ctx.link())),
 		});
 		buttons.insert((1, 0), ButtonModel {
 			id: random(),
 			label: button_names.0.to_string(),
-			release_trigger: Action::OfferChoice.to_send(&ctx.link()),
 			affordance: SubmitAffordance::enabled(Action::PressButton(1, 0).to_sync(ctx.link())),
 		});
 		buttons.insert((1, 1), ButtonModel {
 			id: random(),
 			label: button_names.1.to_string(),
-			release_trigger: Action::ReleaseIgnore.to_send(&ctx.link()),
 			affordance: SubmitAffordance::enabled(Action::PressButton(1, 1).to_sync(ctx.link())),
 		});
 		State { choice, buttons }
 	}
 
-	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let state = ctx.state();
 		match action {
 			Action::PressButton(left_right, top_bottom) => {
-				let state = state.press_button((left_right, top_bottom));
-				AfterFlow::Revise(state)
+				let (state, release) = state.press_button((left_right, top_bottom));
+				AfterFlow::ReviseAnd(state, release.into_iter().collect())
 			}
 			Action::ReleaseIgnore => {
 				let state = state.release_buttons();
@@ -133,7 +130,7 @@ impl Spark for ButtonDemo {
 						let choice = choice.map(|(index, _)| index);
 						Action::Choose(choice)
 					});
-				ctx.start_prequel(choose_spark, choose_link);
+				ctx.start_prequel("choose", choose_spark, choose_link);
 				let state = ctx.state().release_buttons();
 				AfterFlow::Revise(state)
 			}
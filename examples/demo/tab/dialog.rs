@@ -1,10 +1,10 @@
 use rand::random;
 
-use yui::{AfterFlow, ArcYard, Before, Cling, Confine, Create, Flow, Padding, Sendable, SenderLink, Spark, yard};
+use yui::{AfterFlow, ArcYard, Before, Cling, Command, Confine, Create, Flow, Padding, Sendable, SenderLink, Spark, yard};
 
 use yui::palette::{FillColor, StrokeColor};
 use yui::palette::FillGrade::Plain;
-use yui::yard::{ButtonAction, ButtonModel, SubmitAffordance};
+use yui::yard::{ButtonModel, SubmitAffordance};
 
 use crate::{AppTab, Main};
 
@@ -17,9 +17,15 @@ pub struct DialogButtons {
 }
 
 impl DialogButtons {
-	pub fn press_open(&self) -> Self { DialogButtons { open: self.open.update(ButtonAction::Press), close: self.close.clone() } }
-	pub fn release_open(&self) -> Self { DialogButtons { open: self.open.update(ButtonAction::Release), close: self.close.clone() } }
-	pub fn press_close(&self) -> Self { DialogButtons { open: self.open.clone(), close: self.close.update(ButtonAction::Press) } }
+	pub fn press_open(&self) -> (Self, Option<Command<Action>>) {
+		let (open, release) = self.open.press(Action::Open);
+		(DialogButtons { open, close: self.close.clone() }, release)
+	}
+	pub fn release_open(&self) -> Self { DialogButtons { open: self.open.release(), close: self.close.clone() } }
+	pub fn press_close(&self) -> (Self, Option<Command<Action>>) {
+		let (close, release) = self.close.press(Action::Close);
+		(DialogButtons { open: self.open.clone(), close }, release)
+	}
 	pub fn next_dialog(&self, next_dialog: u32) -> Self {
 		DialogButtons { open: self.open.set_label(&next_label(next_dialog)), close: self.close.clone() }
 	}
@@ -44,13 +50,11 @@ impl Spark for DialogDemo {
 			open: ButtonModel {
 				id: random(),
 				label: next_label(self.next_dialog),
-				release_trigger: Action::Open.to_send(create.link()),
 				affordance: SubmitAffordance::enabled(Action::PressOpen.to_sync(create.link())),
 			},
 			close: ButtonModel {
 				id: random(),
 				label: "Close".to_string(),
-				release_trigger: Action::Close.to_send(create.link()),
 				affordance: SubmitAffordance::enabled(Action::PressClose.to_sync(create.link())),
 			},
 		};
@@ -58,26 +62,26 @@ impl Spark for DialogDemo {
 	}
 
 
-	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let (dialog, next_dialog, buttons) = flow.state();
 		match action {
 			Action::PressClose => {
-				let buttons1 = buttons.press_close();
-				AfterFlow::Revise((dialog.clone(), next_dialog.clone(), buttons1))
+				let (buttons1, release) = buttons.press_close();
+				AfterFlow::ReviseAnd((dialog.clone(), next_dialog.clone(), buttons1), release.into_iter().collect())
 			}
 			Action::Close => {
 				let report = Report::ShouldCloseDialog(*next_dialog);
 				AfterFlow::Report(report)
 			}
 			Action::PressOpen => {
-				let buttons = buttons.press_open();
-				AfterFlow::Revise((dialog.clone(), next_dialog.clone(), buttons))
+				let (buttons, release) = buttons.press_open();
+				AfterFlow::ReviseAnd((dialog.clone(), next_dialog.clone(), buttons), release.into_iter().collect())
 			}
 			Action::Open => {
 				{
 					let dialog_spark = Main { dialog_id: next_dialog.clone() };
 					let report_link = flow.link().clone().map(|next_dialog| Action::NextDialog(next_dialog));
-					flow.start_prequel(dialog_spark, report_link);
+					flow.start_prequel("dialog", dialog_spark, report_link);
 				}
 				let buttons = buttons.release_open();
 				AfterFlow::Revise((dialog.clone(), next_dialog.clone(), buttons))
@@ -5,7 +5,7 @@ use yui::{Link, SenderLink};
 use yui::palette::{FillColor, StrokeColor};
 use yui::palette::FillGrade::Plain;
 use yui::prelude::*;
-use yui::yard::{ButtonAction, ButtonModel};
+use yui::yard::{ButtonAction, ButtonModel, TextfieldModel};
 use yui::yard::model::{ScrollAction, ScrollModel};
 
 use crate::AppTab;
@@ -22,24 +22,24 @@ pub enum Action {
 const DISABLED_TEXT: &str = "Enter N";
 
 impl Spark for FormListDemo {
-	type State = (StringEdit, ScrollModel, ButtonModel);
+	type State = (TextfieldModel, ScrollModel, ButtonModel);
 	type Action = Action;
 	type Report = usize;
 
-	fn create(&self, create: &Create<Self::Action, Self::Report>) -> Self::State {
-		let edit = StringEdit::empty(ValidIf::UnsignedInt);
+	fn create(&self, _create: &Create<Self::Action, Self::Report>) -> Self::State {
+		let edit = TextfieldModel::new(StringEdit::empty(ValidIf::UnsignedInt));
 		let list = ScrollModel::new(1930, vec![5, 5], 0);
-		let button = ButtonModel::disabled(DISABLED_TEXT, create.link().to_trigger(Action::ShowTab(0)));
+		let button = ButtonModel::disabled(DISABLED_TEXT);
 		(edit, list, button)
 	}
 
 
-	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let (edit, list, button) = flow.state();
 		match action {
 			Action::StringEdit(edit_action) => {
 				let edit = edit.edit(edit_action);
-				let button = if edit.is_valid() {
+				let button = if edit.edit.is_valid() {
 					button.enable("Submit", flow.link().to_sync().map(|_| UpdateButton(ButtonAction::Press)))
 				} else {
 					button.disable(DISABLED_TEXT)
@@ -58,8 +58,11 @@ impl Spark for FormListDemo {
 				}
 			}
 			Action::UpdateButton(action) => {
-				let button = button.update(action);
-				AfterFlow::Revise((edit.clone(), list.clone(), button))
+				let (button, release) = match action {
+					ButtonAction::Press => button.press(Action::ShowTab(0)),
+					ButtonAction::Release => (button.release(), None),
+				};
+				AfterFlow::ReviseAnd((edit.clone(), list.clone(), button), release.into_iter().collect())
 			}
 		}
 	}
@@ -67,7 +70,7 @@ impl Spark for FormListDemo {
 	fn render(state: &Self::State, link: &SenderLink<Self::Action>) -> Option<ArcYard> {
 		let (edit, list, button) = state;
 		let mirror = yard::label(
-			&String::from_iter(edit.chars.to_vec()),
+			&String::from_iter(edit.edit.chars.to_vec()),
 			StrokeColor::BodyOnBackground,
 			Cling::Left,
 		);
@@ -25,20 +25,17 @@ impl Spark for SelectorListDemo {
 		let count = 10;
 		let scroll = ScrollModel::new_count_height(LIST_ID, count, 4, 0);
 		let button = {
-			let release_trigger = create.link().to_trigger(Action::SetValue(1));
 			let press_link = create.link().to_sync().map(|_| Action::UpdateButton(ButtonAction::Press));
-			ButtonModel::enabled("Add", release_trigger, press_link, Priority::None)
+			ButtonModel::enabled("Add", press_link, Priority::None)
 		};
 		let presses
 			= (0..count).into_iter()
-			.map(|index| {
-				PressModel::new(random(), create.link().to_trigger(Action::SetValue(index as i32 + 1)))
-			})
+			.map(|_| PressModel::new(random()))
 			.collect::<Vec<_>>();
 		(scroll, button, presses)
 	}
 
-	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, flow: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let (scroll, button, presses) = flow.state();
 		match action {
 			Action::SetValue(value) => {
@@ -56,14 +53,20 @@ impl Spark for SelectorListDemo {
 				}
 			}
 			Action::UpdateButton(action) => {
-				let button = button.update(action);
-				AfterFlow::Revise((scroll.clone(), button, presses.clone()))
+				let (button, release) = match action {
+					ButtonAction::Press => button.press(Action::SetValue(1)),
+					ButtonAction::Release => (button.release(), None),
+				};
+				AfterFlow::ReviseAnd((scroll.clone(), button, presses.clone()), release.into_iter().collect())
 			}
 			Action::UpdatePress(index, action) => {
 				let mut presses = presses.clone();
-				let press = presses.remove(index).update(action);
-				presses.insert(index, press);
-				AfterFlow::Revise((scroll.clone(), button.clone(), presses))
+				let (press, release) = match action {
+					PressAction::Press => presses[index].press(Action::SetValue(index as i32 + 1)),
+					PressAction::Release => (presses[index].release(), None),
+				};
+				presses[index] = press;
+				AfterFlow::ReviseAnd((scroll.clone(), button.clone(), presses), release.into_iter().collect())
 			}
 		}
 	}
@@ -18,7 +18,7 @@ impl Spark for TextDemo {
 
 	fn create(&self, _ctx: &Create<Self::Action, Self::Report>) -> Self::State { () }
 
-	fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		AfterFlow::Ignore
 	}
 
@@ -26,7 +26,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 			()
 		}
 
-		fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+		fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 			AfterFlow::Close(None)
 		}
 		
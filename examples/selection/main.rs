@@ -47,25 +47,26 @@ impl Spark for Main {
 		let value = 0usize;
 		let open = ButtonModel::enabled(
 			"Open".into(),
-			ctx.link().to_trigger(MainAction::OpenSelector),
 			ctx.link().to_sync().map(|_| MainAction::UpdateOpen(ButtonAction::Press)),
 			Priority::Default,
 		);
 		let close = ButtonModel::enabled(
 			"Close".into(),
-			ctx.link().to_trigger(MainAction::Close),
 			ctx.link().to_sync().map(|_| MainAction::UpdateClose(ButtonAction::Press)),
 			Priority::None,
 		);
 		(value, open, close)
 	}
 
-	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let (value, open, close) = ctx.state();
 		match action {
 			MainAction::UpdateOpen(action) => {
-				let open = open.update(action);
-				AfterFlow::Revise((value.clone(), open, close.clone()))
+				let (open, release) = match action {
+					ButtonAction::Press => open.press(MainAction::OpenSelector),
+					ButtonAction::Release => (open.release(), None),
+				};
+				AfterFlow::ReviseAnd((value.clone(), open, close.clone()), release.into_iter().collect())
 			}
 			MainAction::OpenSelector => {
 				let spark = SelectionEditorSpark {
@@ -73,8 +74,8 @@ impl Spark for Main {
 					choices: (0..10usize).into_iter().collect::<Vec<_>>(),
 				};
 				let report_link = ctx.link().map(|it| MainAction::Select(it));
-				ctx.start_prequel(spark, report_link);
-				let open = open.update(ButtonAction::Release);
+				ctx.start_prequel("selection", spark, report_link);
+				let open = open.release();
 				AfterFlow::Revise((value.clone(), open, close.clone()))
 			}
 			MainAction::Select(choice) => {
@@ -85,8 +86,11 @@ impl Spark for Main {
 				}
 			}
 			MainAction::UpdateClose(action) => {
-				let close = close.update(action);
-				AfterFlow::Revise((value.clone(), open.clone(), close))
+				let (close, release) = match action {
+					ButtonAction::Press => close.press(MainAction::Close),
+					ButtonAction::Release => (close.release(), None),
+				};
+				AfterFlow::ReviseAnd((value.clone(), open.clone(), close), release.into_iter().collect())
 			}
 			MainAction::Close => {
 				AfterFlow::Close(None)
@@ -13,7 +13,7 @@ use yui::{AfterFlow, ArcYard, Before, console, Create, Flow, Link, Pack, Padding
 
 use yui::palette::FillColor::Background;
 use yui::palette::FillGrade::Plain;
-use yui::yard::{ButtonAction, ButtonModel, PressAction, PressModel, SubmitAffordance};
+use yui::yard::{ButtonModel, PressModel, SubmitAffordance};
 use yui::yard::model::{ScrollAction, ScrollModel};
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -37,7 +37,7 @@ pub enum MainAction {
 	PressButton,
 	SubmitRow(usize),
 	UpdateScroll(ScrollAction),
-	UpdatePress(usize, PressAction),
+	ReleasePress(usize),
 }
 
 impl Sendable for MainAction {}
@@ -58,28 +58,24 @@ impl Spark for Main {
 		let button = ButtonModel {
 			id: random(),
 			label: "Close".into(),
-			release_trigger: MainAction::Close.to_send(ctx.link()),
 			affordance: SubmitAffordance::enabled(MainAction::PressButton.to_sync(ctx.link())),
 		};
-		let presses = rows.iter().enumerate()
-			.map(|(index, _)| {
-				let trigger = ctx.link().to_trigger(MainAction::SubmitRow(index));
-				PressModel::new(random(), trigger)
-			})
+		let presses = rows.iter()
+			.map(|_| PressModel::new(random()))
 			.collect::<Vec<_>>();
 		(rows, list, button, presses)
 	}
 
-	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let (rows, scroll, button, presses) = ctx.state();
 		match action {
 			MainAction::Close => AfterFlow::Close(None),
 			MainAction::PressButton => {
-				let next_button = button.update(ButtonAction::Press);
-				AfterFlow::Revise((rows.clone(), scroll.clone(), next_button, presses.clone()))
+				let (next_button, release) = button.press(MainAction::Close);
+				AfterFlow::ReviseAnd((rows.clone(), scroll.clone(), next_button, presses.clone()), release.into_iter().collect())
 			}
 			MainAction::SubmitRow(index) => {
-				ctx.link().send(MainAction::UpdatePress(index, PressAction::Release));
+				ctx.link().send(MainAction::ReleasePress(index));
 				AfterFlow::Revise((rows.clone(), scroll.clone(), button.clone(), presses.clone()))
 			}
 			MainAction::UpdateScroll(action) => {
@@ -89,9 +85,9 @@ impl Spark for Main {
 					AfterFlow::Ignore
 				}
 			}
-			MainAction::UpdatePress(index, action) => {
+			MainAction::ReleasePress(index) => {
 				let mut presses = presses.clone();
-				let press = presses.remove(index).update(action);
+				let press = presses.remove(index).release();
 				presses.insert(index, press);
 				AfterFlow::Revise((rows.clone(), scroll.clone(), button.clone(), presses))
 			}
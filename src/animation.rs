@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+
+use crate::palette::FillGrade;
+
+/// Shapes how a transition's progress moves from start to finish.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Easing {
+	Linear,
+	EaseIn,
+	EaseOut,
+	EaseInOut,
+}
+
+impl Easing {
+	/// Maps linear progress in 0..=1 to eased progress in 0..=1.
+	pub fn apply(&self, t: f32) -> f32 {
+		let t = t.clamp(0.0, 1.0);
+		match self {
+			Easing::Linear => t,
+			Easing::EaseIn => t * t * t,
+			Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+			Easing::EaseInOut => if t < 0.5 {
+				4.0 * t * t * t
+			} else {
+				1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+			},
+		}
+	}
+}
+
+/// A span of time over which a yard moves from one value to another.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Transition {
+	pub start: Instant,
+	pub duration: Duration,
+	pub easing: Easing,
+}
+
+impl Transition {
+	pub fn new(start: Instant, duration: Duration, easing: Easing) -> Self {
+		Transition { start, duration, easing }
+	}
+	pub fn starting_now(duration: Duration, easing: Easing) -> Self {
+		Self::new(Instant::now(), duration, easing)
+	}
+	pub fn is_done(&self, now: Instant) -> bool {
+		now.saturating_duration_since(self.start) >= self.duration
+	}
+	/// Eased progress at `now`, from 0 at the start to 1 once done.
+	pub fn progress(&self, now: Instant) -> f32 {
+		if self.is_done(now) {
+			1.0
+		} else {
+			let elapsed = now.saturating_duration_since(self.start);
+			self.easing.apply(elapsed.as_secs_f32() / self.duration.as_secs_f32())
+		}
+	}
+}
+
+/// Values a transition can interpolate.
+pub trait Tween {
+	fn tween(&self, to: &Self, progress: f32) -> Self;
+}
+
+impl Tween for f32 {
+	fn tween(&self, to: &Self, progress: f32) -> Self { self + (to - self) * progress }
+}
+
+impl Tween for i32 {
+	fn tween(&self, to: &Self, progress: f32) -> Self {
+		(*self as f32).tween(&(*to as f32), progress).round() as i32
+	}
+}
+
+impl Tween for u8 {
+	fn tween(&self, to: &Self, progress: f32) -> Self {
+		(*self as i32).tween(&(*to as i32), progress) as u8
+	}
+}
+
+impl Tween for (i32, i32) {
+	fn tween(&self, to: &Self, progress: f32) -> Self {
+		(self.0.tween(&to.0, progress), self.1.tween(&to.1, progress))
+	}
+}
+
+/// Steps through the grades that lie between the two.
+impl Tween for FillGrade {
+	fn tween(&self, to: &Self, progress: f32) -> Self {
+		GRADES[(grade_index(*self) as i32).tween(&(grade_index(*to) as i32), progress) as usize]
+	}
+}
+
+const GRADES: [FillGrade; 4] = [FillGrade::Plain, FillGrade::Select, FillGrade::Focus, FillGrade::Press];
+
+fn grade_index(grade: FillGrade) -> usize {
+	GRADES.iter().position(|it| *it == grade).expect("grade")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use crate::animation::{Easing, Transition, Tween};
+	use crate::palette::FillGrade;
+
+	#[test]
+	fn transition_progress() {
+		let start = Instant::now();
+		let transition = Transition::new(start, Duration::from_millis(100), Easing::Linear);
+		assert_eq!(transition.progress(start), 0.0);
+		assert_eq!(transition.progress(start + Duration::from_millis(25)), 0.25);
+		assert_eq!(transition.progress(start + Duration::from_millis(500)), 1.0);
+		assert!(!transition.is_done(start + Duration::from_millis(99)));
+
+		let eased = Transition { easing: Easing::EaseOut, ..transition };
+		assert!(eased.progress(start + Duration::from_millis(25)) > 0.25);
+		assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+
+		assert_eq!(10.tween(&-10, 0.25), 5);
+		assert_eq!(FillGrade::Plain.tween(&FillGrade::Press, 0.5), FillGrade::Focus);
+	}
+}
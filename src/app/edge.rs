@@ -1,7 +1,8 @@
 use std::sync::mpsc::Sender;
 
-use crate::{Link, SenderLink, Spark, story_box, StoryVerseAction, Trigger};
+use crate::{KeyEvent, Link, Persistence, Recorder, SenderLink, Spark, story_box, StoryFailure, StoryVerseAction, Timers, Trigger};
 use crate::dialog_story::DialogStory;
+use crate::keymap::{Keymap, KeyScope};
 use crate::story_id::StoryId;
 use crate::sub_story::SubStory;
 use crate::super_story::SuperStory;
@@ -9,32 +10,52 @@ use crate::super_story::SuperStory;
 #[derive(Debug, Clone)]
 pub struct Edge {
 	story_id: StoryId,
+	dialog_id: StoryId,
 	end_dialog_trigger: Trigger,
 	story_verse_link: Sender<StoryVerseAction>,
+	keymap: Keymap,
+	persistence: Option<Persistence>,
+	recorder: Option<Recorder>,
+	timers: Timers,
 }
 
 impl Edge {
-	pub fn new(story_id: StoryId, end_dialog_trigger: Trigger, story_verse_link: Sender<StoryVerseAction>) -> Self {
-		Edge { story_id, end_dialog_trigger, story_verse_link }
+	pub fn new(story_id: StoryId, dialog_id: StoryId, end_dialog_trigger: Trigger, story_verse_link: Sender<StoryVerseAction>, keymap: Keymap, persistence: Option<Persistence>, recorder: Option<Recorder>, timers: Timers) -> Self {
+		Edge { story_id, dialog_id, end_dialog_trigger, story_verse_link, keymap, persistence, recorder, timers }
 	}
 }
 
 impl SuperStory for Edge {
-	fn sub_story<S: Spark + Send + 'static>(&self, spark: S, reports_link: Option<SenderLink<S::Report>>) -> SubStory {
+	fn sub_story<S: Spark + Send + 'static>(&self, key: &str, spark: S, reports_link: Option<SenderLink<S::Report>>) -> SubStory {
 		let story_id = self.story_id.sub_id();
-		let (story_box_link, _sub_story_link) = story_box::connect(spark, reports_link, story_id, self.story_verse_link.clone());
-		self.story_verse_link.send(StoryVerseAction::AddStoryBox(story_box_link, story_id)).expect("Add sub-story box");
+		let persistence = self.persistence.as_ref().map(|it| it.child(key));
+		let recorder = self.recorder.as_ref().map(|it| it.child(key));
+		let (story_box_link, _sub_story_link) = story_box::connect(spark, reports_link, story_id, self.dialog_id, self.story_verse_link.clone(), self.keymap.clone(), persistence, recorder, self.timers.clone());
+		self.story_verse_link.send(StoryVerseAction::AddStoryBox(story_box_link, story_id)).ok();
 		SubStory { story_id }
 	}
 }
 
 impl Edge {
 	pub fn story_id(&self) -> &StoryId { &self.story_id }
+	pub fn keymap(&self) -> &Keymap { &self.keymap }
+	pub fn persistence(&self) -> Option<&Persistence> { self.persistence.as_ref() }
+	pub fn recorder(&self) -> Option<&Recorder> { self.recorder.as_ref() }
+	/// The timers this story's `after` and `every` run on.
+	pub fn timers(&self) -> &Timers { &self.timers }
 
-	pub fn start_dialog<S: Spark + Send + 'static>(&self, spark: S, report_link: SenderLink<S::Report>) -> DialogStory {
+	/// Sends `action` to `link` when `key` reaches the keymap within `scope`.
+	pub fn bind_key<A: Clone + Send + Sync + 'static>(&self, key: KeyEvent, scope: KeyScope, description: &str, link: &SenderLink<A>, action: A) {
+		self.keymap.bind(self.story_id, self.dialog_id, key, scope, description, link, action);
+	}
+
+	/// Starts a dialog whose state persists, and whose actions record, under `key` within this story's key.
+	pub fn start_dialog<S: Spark + Send + 'static>(&self, key: &str, spark: S, report_link: SenderLink<S::Report>) -> DialogStory {
 		let story_id = self.story_id.dialog_id();
-		let (story_box_link, _dialog_story_link) = story_box::connect(spark, Some(report_link), story_id, self.story_verse_link.clone());
-		self.story_verse_link.send(StoryVerseAction::AddStackStoryBox(story_box_link, story_id)).expect("Send AddStackStoryBox");
+		let persistence = self.persistence.as_ref().map(|it| it.child(key));
+		let recorder = self.recorder.as_ref().map(|it| it.child(key));
+		let (story_box_link, _dialog_story_link) = story_box::connect(spark, Some(report_link), story_id, story_id, self.story_verse_link.clone(), self.keymap.clone(), persistence, recorder, self.timers.clone());
+		self.story_verse_link.send(StoryVerseAction::AddStackStoryBox(story_box_link, story_id)).ok();
 		DialogStory { story_id }
 	}
 
@@ -44,4 +65,10 @@ impl Edge {
 	}
 
 	pub fn redraw(&self) {}
+
+	/// Tells the story verse that this story's thread has died.
+	pub fn report_failure(&self, message: &str) {
+		let failure = StoryFailure { story_id: self.story_id, message: message.to_string() };
+		self.story_verse_link.send(StoryVerseAction::StoryFailed(failure)).ok();
+	}
 }
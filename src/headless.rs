@@ -0,0 +1,209 @@
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::{link, ScreenAction, SenderLink, Spark, StoryVerse, Timers};
+use crate::pod::Pod;
+use crate::pod_verse::PodVerse;
+use crate::spot::spot_table::SpotTable;
+use crate::story_id::StoryId;
+
+/// Drives a pod against a virtual screen instead of a terminal.
+///
+/// Each action is applied to the pod and the resulting spot table is read once
+/// the pod has acknowledged the action and the updates it set off have landed.
+pub struct Headless {
+	pod: Box<dyn Pod>,
+	width_height: (i32, i32),
+	refresh_source: Receiver<()>,
+	done_source: Option<Receiver<()>>,
+	quiet: Duration,
+	timeout: Duration,
+	_verses: Option<(StoryVerse, PodVerse)>,
+}
+
+impl Headless {
+	pub fn new(pod: impl Pod + 'static, width_height: (i32, i32)) -> Self {
+		let mut pod = Box::new(pod);
+		let (refresh_link, refresh_source) = channel();
+		pod.set_refresh_trigger(SenderLink::new(refresh_link));
+		Self::init(pod, width_height, refresh_source, None, None)
+	}
+
+	pub fn from_pod_verse(pod_verse: &PodVerse, width_height: (i32, i32)) -> Self {
+		let (refresh_link, refresh_source) = channel();
+		let pod = Box::new(pod_verse.to_main_pod(SenderLink::new(refresh_link)));
+		let (done_link, done_source) = channel();
+		pod_verse.set_done_trigger(done_link);
+		Self::init(pod, width_height, refresh_source, Some(done_source), None)
+	}
+
+	pub fn from_spark<S: Spark>(spark: S, width_height: (i32, i32)) -> Self where S: Send + 'static {
+		Self::from_spark_with_timers(spark, width_height, Timers::shared())
+	}
+
+	/// Runs the spark's `after` and `every` on the given timers, such as ones on a `ManualClock`.
+	pub fn from_spark_with_timers<S: Spark>(spark: S, width_height: (i32, i32), timers: Timers) -> Self where S: Send + 'static {
+		let (story_verse, _) = StoryVerse::build_with_timers(spark, StoryId::new(0), timers);
+		let pod_verse = PodVerse::build(&story_verse);
+		let mut headless = Self::from_pod_verse(&pod_verse, width_height);
+		headless._verses = Some((story_verse, pod_verse));
+		headless
+	}
+
+	fn init(pod: Box<dyn Pod>, width_height: (i32, i32), refresh_source: Receiver<()>, done_source: Option<Receiver<()>>, verses: Option<(StoryVerse, PodVerse)>) -> Self {
+		let mut headless = Headless {
+			pod,
+			width_height,
+			refresh_source,
+			done_source,
+			quiet: Duration::from_millis(50),
+			timeout: Duration::from_secs(2),
+			_verses: verses,
+		};
+		headless.pod.set_width_height(width_height);
+		headless.settle();
+		headless
+	}
+
+	/// Sets how long to wait for a story to answer a refresh the pod asked for.
+	pub fn with_quiet(mut self, quiet: Duration) -> Self {
+		self.quiet = quiet;
+		self
+	}
+
+	/// Sets the longest time to keep following refreshes before reading the pod anyway.
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	pub fn width_height(&self) -> (i32, i32) { self.width_height }
+
+	pub fn resize(&mut self, width_height: (i32, i32)) -> SpotTable {
+		self.width_height = width_height;
+		self.pod.set_width_height(width_height);
+		self.settle();
+		self.spot_table()
+	}
+
+	/// Applies an action, waits for the pod to settle, and returns its spot table.
+	pub fn send(&mut self, action: ScreenAction) -> SpotTable {
+		match action {
+			ScreenAction::Close => {}
+			ScreenAction::ResizeRefresh => {}
+			ScreenAction::SetTheme(_) => {}
+			ScreenAction::SetYard(yard) => self.pod.set_yard(yard),
+			ScreenAction::Key(key) => self.pod.insert_key(key),
+			ScreenAction::Paste(text) => self.pod.paste(text),
+			ScreenAction::FocusUp => self.pod.focus_up(),
+			ScreenAction::FocusDown => self.pod.focus_down(),
+			ScreenAction::FocusLeft => self.pod.focus_left(),
+			ScreenAction::FocusRight => self.pod.focus_right(),
+			ScreenAction::FocusNext => self.pod.focus_next(),
+			ScreenAction::FocusPrevious => self.pod.focus_previous(),
+			ScreenAction::Click { x, y } => self.pod.click(x, y),
+			ScreenAction::Hover { x, y } => self.pod.hover(x, y),
+			ScreenAction::Wheel { x, y, action } => self.pod.wheel(x, y, action),
+		}
+		self.settle();
+		self.spot_table()
+	}
+
+	pub fn send_all(&mut self, actions: impl IntoIterator<Item=ScreenAction>) -> SpotTable {
+		let mut spot_table = self.spot_table();
+		for action in actions {
+			spot_table = self.send(action);
+		}
+		spot_table
+	}
+
+	pub fn spot_table(&self) -> SpotTable {
+		self.pod.spot_table().unwrap_or_else(|| SpotTable::new(self.width_height.1, self.width_height.0))
+	}
+
+	/// True once the root story has ended.
+	pub fn is_done(&self) -> bool {
+		match &self.done_source {
+			None => false,
+			Some(done_source) => done_source.try_recv().is_ok(),
+		}
+	}
+
+	fn settle(&self) {
+		let deadline = Instant::now() + self.timeout;
+		loop {
+			// Reading the spot table round-trips through the pod, so every action sent
+			// before it has been handled once the read returns.
+			self.pod.spot_table();
+			link::flush();
+			let mut refreshed = false;
+			while self.refresh_source.try_recv().is_ok() {
+				refreshed = true;
+			}
+			let now = Instant::now();
+			if now >= deadline {
+				break;
+			}
+			// A refresh after the acknowledgement means stories are still answering.
+			if !refreshed && self.refresh_source.recv_timeout(self.quiet.min(deadline - now)).is_err() {
+				break;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use stringedit::{StringEdit, Validity};
+
+	use crate::{AfterFlow, ArcYard, Create, Flow, KeyEvent, ScreenAction, SenderLink, Spark, yard};
+	use crate::headless::Headless;
+	use crate::spot::spot_table::SpotTable;
+
+	struct Field;
+
+	impl Spark for Field {
+		type State = StringEdit;
+		type Action = stringedit::Action;
+		type Report = ();
+		fn create(&self, _ctx: &Create<Self::Action, Self::Report>) -> Self::State { StringEdit::empty(Validity::NotEmpty) }
+		fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
+			AfterFlow::Revise(ctx.state().edit(action))
+		}
+		fn render(state: &Self::State, link: &SenderLink<Self::Action>) -> Option<ArcYard> {
+			Some(yard::textfield(1, "Name", state.clone(), link.clone()))
+		}
+	}
+
+	fn row_text(spot_table: &SpotTable, row: usize) -> String {
+		spot_table.to_fronts()[row].iter()
+			.map(|front| front.stroke.as_ref().map(|(glyph, _)| glyph.to_string()).unwrap_or_else(|| " ".to_string()))
+			.collect::<String>()
+	}
+
+	#[test]
+	fn scripted_chars_reach_textfield() {
+		let mut headless = Headless::from_spark(Field, (6, 3));
+		assert_eq!(row_text(&headless.spot_table(), 0), " Name ");
+		let spot_table = headless.send_all(vec![ScreenAction::Key(KeyEvent::char('h')), ScreenAction::Key(KeyEvent::char('i'))]);
+		assert_eq!(row_text(&spot_table, 1), " hi   ");
+	}
+
+	#[test]
+	fn quiet_actions_do_not_wait_for_timeout() {
+		let mut headless = Headless::from_spark(Field, (6, 3)).with_timeout(Duration::from_secs(5));
+		let start = Instant::now();
+		headless.send(ScreenAction::FocusNext);
+		assert!(start.elapsed() < Duration::from_secs(1));
+	}
+
+	#[test]
+	fn resize_changes_table_size() {
+		let mut headless = Headless::from_spark(Field, (6, 3));
+		let spot_table = headless.resize((8, 4));
+		assert_eq!(spot_table.to_fronts().len(), 4);
+		assert_eq!(spot_table.to_fronts()[0].len(), 8);
+	}
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{KeyEvent, Link, SenderLink};
+use crate::story_id::StoryId;
+
+/// Where a key binding is active.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum KeyScope {
+	/// Active while the focused widget belongs to the story.
+	Story,
+	/// Active while the story's dialog is on top of the story stack.
+	Dialog,
+	/// Always active.
+	Global,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct KeyBinding {
+	pub key: KeyEvent,
+	pub scope: KeyScope,
+	pub description: String,
+	/// The story that registered the binding, or `None` for a built-in default.
+	pub story_id: Option<StoryId>,
+}
+
+struct KeyEntry {
+	binding: KeyBinding,
+	dialog_id: Option<StoryId>,
+	trigger: Arc<dyn Fn() + Send + Sync>,
+}
+
+#[derive(Default)]
+struct KeymapState {
+	entries: Vec<KeyEntry>,
+	defaults: Vec<KeyEntry>,
+	dialogs: Vec<StoryId>,
+	focus_stories: Vec<StoryId>,
+}
+
+/// Key bindings registered by stories, shared between the story verse and the pods.
+///
+/// A key reaches the keymap only when the focused widget does not take it.
+/// Story bindings beat dialog bindings which beat global bindings, and later
+/// registrations beat earlier ones within a scope.
+#[derive(Clone, Default)]
+pub struct Keymap {
+	state: Arc<Mutex<KeymapState>>,
+}
+
+impl fmt::Debug for Keymap {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Keymap")
+			.field("bindings", &self.active_bindings())
+			.finish()
+	}
+}
+
+impl Keymap {
+	pub fn new() -> Self { Keymap::default() }
+
+	pub fn bind<A: Clone + Send + Sync + 'static>(&self, story_id: StoryId, dialog_id: StoryId, key: KeyEvent, scope: KeyScope, description: &str, link: &SenderLink<A>, action: A) {
+		let link = link.clone();
+		let entry = KeyEntry {
+			binding: KeyBinding { key, scope, description: description.to_string(), story_id: Some(story_id) },
+			dialog_id: Some(dialog_id),
+			trigger: Arc::new(move || link.send(action.clone())),
+		};
+		self.state.lock().unwrap().entries.push(entry);
+	}
+
+	/// Sets a global fallback used when no story binds the key.
+	pub fn bind_default(&self, key: KeyEvent, description: &str, trigger: impl Fn() + Send + Sync + 'static) {
+		let entry = KeyEntry {
+			binding: KeyBinding { key, scope: KeyScope::Global, description: description.to_string(), story_id: None },
+			dialog_id: None,
+			trigger: Arc::new(trigger),
+		};
+		let mut state = self.state.lock().unwrap();
+		state.defaults.retain(|it| it.binding.key != key);
+		state.defaults.push(entry);
+	}
+
+	/// Drops the bindings of a story that has stopped.
+	pub fn unbind_story(&self, story_id: StoryId) {
+		let mut state = self.state.lock().unwrap();
+		state.entries.retain(|it| it.binding.story_id != Some(story_id));
+		state.dialogs.retain(|it| *it != story_id);
+	}
+
+	pub fn push_dialog(&self, dialog_id: StoryId) {
+		self.state.lock().unwrap().dialogs.push(dialog_id);
+	}
+
+	/// Records the stories, outermost first, that contain the focused widget.
+	pub fn set_focus_stories(&self, story_ids: Vec<StoryId>) {
+		self.state.lock().unwrap().focus_stories = story_ids;
+	}
+
+	/// Lists the bindings a key press would reach, in precedence order.
+	pub fn active_bindings(&self) -> Vec<KeyBinding> {
+		let state = self.state.lock().unwrap();
+		state.active_entries().into_iter().map(|it| it.binding.clone()).collect()
+	}
+
+	/// Fires the binding for the key, returning false if nothing is bound to it.
+	/// The trigger runs after the keymap is unlocked so it may bind keys or block.
+	pub fn dispatch(&self, key: &KeyEvent) -> bool {
+		let trigger = {
+			let state = self.state.lock().unwrap();
+			state.active_entries().into_iter().find(|it| &it.binding.key == key).map(|entry| entry.trigger.clone())
+		};
+		match trigger {
+			None => false,
+			Some(trigger) => {
+				trigger();
+				true
+			}
+		}
+	}
+}
+
+impl KeymapState {
+	fn active_entries(&self) -> Vec<&KeyEntry> {
+		let top_dialog = self.dialogs.last().cloned();
+		let in_top_dialog = |entry: &KeyEntry| entry.dialog_id.is_some() && entry.dialog_id == top_dialog;
+		let mut active = Vec::new();
+		if self.focus_stories.is_empty() {
+			active.extend(self.entries.iter().rev().filter(|it| it.binding.scope == KeyScope::Story && in_top_dialog(it)));
+		} else {
+			for story_id in self.focus_stories.iter().rev() {
+				active.extend(self.entries.iter().rev().filter(|it| it.binding.scope == KeyScope::Story && it.binding.story_id == Some(*story_id)));
+			}
+		}
+		active.extend(self.entries.iter().rev().filter(|it| it.binding.scope == KeyScope::Dialog && in_top_dialog(it)));
+		active.extend(self.entries.iter().rev().filter(|it| it.binding.scope == KeyScope::Global));
+		active.extend(self.defaults.iter());
+		let mut seen = Vec::new();
+		active.into_iter().filter(|it| {
+			if seen.contains(&it.binding.key) {
+				false
+			} else {
+				seen.push(it.binding.key);
+				true
+			}
+		}).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::mpsc::channel;
+
+	use crate::{KeyEvent, SenderLink};
+	use crate::keymap::{Keymap, KeyScope};
+	use crate::story_id::StoryId;
+
+	#[test]
+	fn scopes_and_precedence() {
+		let (tx, rx) = channel();
+		let link = SenderLink::new(tx);
+		let (main, inner, dialog) = (StoryId::new(1), StoryId::new(2), StoryId::new(3));
+		let keymap = Keymap::new();
+		keymap.push_dialog(main);
+		keymap.bind_default(KeyEvent::ctrl('d'), "Quit", || {});
+		keymap.bind(main, main, KeyEvent::ctrl('s'), KeyScope::Global, "Save", &link, "save");
+		keymap.bind(main, main, KeyEvent::char('q'), KeyScope::Dialog, "Quit", &link, "quit");
+		keymap.bind(inner, main, KeyEvent::char('q'), KeyScope::Story, "Close", &link, "close");
+		keymap.set_focus_stories(vec![main, inner]);
+		assert!(keymap.dispatch(&KeyEvent::char('q')));
+		assert_eq!(rx.recv().unwrap(), "close");
+
+		keymap.set_focus_stories(vec![main]);
+		assert!(keymap.dispatch(&KeyEvent::char('q')));
+		assert_eq!(rx.recv().unwrap(), "quit");
+
+		keymap.bind(dialog, dialog, KeyEvent::char('y'), KeyScope::Dialog, "Yes", &link, "yes");
+		keymap.push_dialog(dialog);
+		keymap.set_focus_stories(vec![dialog]);
+		assert!(!keymap.dispatch(&KeyEvent::char('q')));
+		let keys = keymap.active_bindings().into_iter().map(|it| it.description).collect::<Vec<_>>();
+		assert_eq!(keys, vec!["Yes", "Save", "Quit"]);
+
+		keymap.unbind_story(dialog);
+		keymap.set_focus_stories(vec![main]);
+		assert!(keymap.dispatch(&KeyEvent::char('q')));
+		assert_eq!(rx.recv().unwrap(), "quit");
+	}
+
+	#[test]
+	fn triggers_run_unlocked() {
+		let keymap = Keymap::new();
+		let inner = keymap.clone();
+		keymap.bind_default(KeyEvent::ctrl('r'), "Rebind", move || {
+			inner.bind_default(KeyEvent::ctrl('x'), "Cut", || {});
+		});
+		assert!(keymap.dispatch(&KeyEvent::ctrl('r')));
+		assert!(keymap.active_bindings().iter().any(|it| it.key == KeyEvent::ctrl('x')));
+	}
+}
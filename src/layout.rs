@@ -1,8 +1,10 @@
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::time::Instant;
 
 use crate::{ArcYard, Bounds, Focus};
+use crate::animation::Transition;
 use crate::core::bounds::BoundsHold;
 use crate::pod_verse::tree::PodBranch;
 use crate::story_id::StoryId;
@@ -16,6 +18,9 @@ pub struct LayoutState {
 	pub bounds_hold: Rc<RefCell<BoundsHold>>,
 	pub active_focus: ActiveFocus,
 	pub dependencies: HashSet<(i32, StoryId)>,
+	pub story_orders: HashMap<StoryId, usize>,
+	/// True when some yard is mid-transition and needs another layout to move on.
+	pub animating: bool,
 }
 
 impl LayoutState {
@@ -34,12 +39,20 @@ impl LayoutState {
 
 
 pub fn run(height: i32, width: i32, yard: &ArcYard, prev_focus: &ActiveFocus) -> LayoutState {
+	run_at(height, width, yard, prev_focus, Instant::now())
+}
+
+/// Lays out the yard with transitions read at `now`.
+pub fn run_at(height: i32, width: i32, yard: &ArcYard, prev_focus: &ActiveFocus, now: Instant) -> LayoutState {
 	let (start_index, bounds) = BoundsHold::init(width, height);
 	let mut layout_ctx = LayoutContext::new(start_index, bounds.clone());
+	layout_ctx.now = now;
 	yard.layout(&mut layout_ctx);
+	let animating = layout_ctx.animating.get();
 	let active_focus = layout_ctx.pop_active_focus(prev_focus);
 	let dependencies = layout_ctx.dependencies.borrow();
-	LayoutState { max_x: width, max_y: height, start_index, bounds_hold: bounds, active_focus, dependencies: dependencies.clone() }
+	let story_orders = layout_ctx.story_orders.borrow();
+	LayoutState { max_x: width, max_y: height, start_index, bounds_hold: bounds, active_focus, dependencies: dependencies.clone(), story_orders: story_orders.clone(), animating }
 }
 
 #[derive(Clone)]
@@ -49,11 +62,34 @@ pub struct LayoutContext {
 	focus_vec: Rc<RefCell<Vec<Rc<Focus>>>>,
 	focus_max: i32,
 	dependencies: Rc<RefCell<HashSet<(i32, StoryId)>>>,
+	story_orders: Rc<RefCell<HashMap<StoryId, usize>>>,
+	next_order: Rc<Cell<usize>>,
+	tab_index: Option<i32>,
+	now: Instant,
+	animating: Rc<Cell<bool>>,
 }
 
 impl LayoutContext {
 	pub fn add_dependency(&mut self, yard_id: i32, story_id: StoryId) {
 		(*self.dependencies).borrow_mut().insert((yard_id, story_id));
+		let order = self.take_order();
+		(*self.story_orders).borrow_mut().insert(story_id, order);
+	}
+
+	fn take_order(&self) -> usize {
+		let order = self.next_order.get();
+		self.next_order.set(order + 1);
+		order
+	}
+
+	pub fn now(&self) -> Instant { self.now }
+
+	/// Eased progress of the transition. Keeps the layout animating until it is done.
+	pub fn progress(&self, transition: &Transition) -> f32 {
+		if !transition.is_done(self.now) {
+			self.animating.set(true);
+		}
+		transition.progress(self.now)
 	}
 
 	pub fn trapped_focus(&self) -> Option<Rc<Focus>> {
@@ -93,7 +129,11 @@ impl LayoutContext {
 		bounds_index
 	}
 
-	pub fn add_focus(&mut self, focus: Focus) {
+	pub fn add_focus(&mut self, mut focus: Focus) {
+		focus.order = vec![self.take_order()];
+		if focus.tab_index.is_none() {
+			focus.tab_index = self.tab_index;
+		}
 		(*self.focus_vec).borrow_mut().push(Rc::new(focus));
 	}
 
@@ -111,6 +151,10 @@ impl LayoutContext {
 		LayoutContext { current_index: index, ..self.to_owned() }
 	}
 
+	pub fn with_tab_index(&self, tab_index: i32) -> Self {
+		LayoutContext { tab_index: Some(tab_index), ..self.to_owned() }
+	}
+
 	pub fn new(current_index: usize, bounds_hold: Rc<RefCell<BoundsHold>>) -> Self {
 		LayoutContext {
 			current_index,
@@ -118,6 +162,11 @@ impl LayoutContext {
 			focus_vec: Rc::new(RefCell::new(Vec::new())),
 			focus_max: i32::MAX,
 			dependencies: Rc::new(RefCell::new(HashSet::new())),
+			story_orders: Rc::new(RefCell::new(HashMap::new())),
+			next_order: Rc::new(Cell::new(0)),
+			tab_index: None,
+			now: Instant::now(),
+			animating: Rc::new(Cell::new(false)),
 		}
 	}
 }
@@ -139,7 +188,8 @@ pub fn to_active_focus(past_active: &ActiveFocus, available_foci: Vec<Rc<Focus>>
 			info!("PastActiveFocus is empty, pick priority focus");
 			pick_priority_focus(available_foci)
 		};
-	ActiveFocus { focus, peers, rear_z }
+	let hover_id = if peers.iter().chain(focus.iter()).any(|it| it.yard_id == past_active.hover_id) { past_active.hover_id } else { 0 };
+	ActiveFocus { focus, peers, rear_z, hover_id }
 }
 
 fn pick_priority_focus(mut candidates: Vec<Rc<Focus>>) -> (Option<Rc<Focus>>, Vec<Rc<Focus>>) {
@@ -3,6 +3,7 @@ extern crate log;
 extern crate ncurses;
 extern crate simplelog;
 extern crate stringedit;
+extern crate unicode_segmentation;
 extern crate unicode_width;
 
 pub use link::*;
@@ -13,7 +14,7 @@ pub use yard::ArcYard;
 pub use yui_curses::*;
 
 pub use crate::core::bounds::*;
-use crate::palette::{FillColor, FillGrade, StrokeColor};
+use crate::palette::{DARK_LEVELS, FillColor, FillGrade, StrokeColor, StrokeStyle};
 use crate::story_id::StoryId;
 
 pub use self::yui::*;
@@ -23,6 +24,7 @@ mod link;
 mod yui;
 mod yui_curses;
 
+pub mod animation;
 pub mod app;
 pub mod palette;
 pub mod sparks;
@@ -33,6 +35,9 @@ pub mod layout;
 pub(crate) mod render;
 pub mod pod;
 pub mod pod_verse;
+pub mod headless;
+pub mod keymap;
+pub mod theme;
 
 #[cfg(test)]
 mod tests;
@@ -40,8 +45,20 @@ mod tests;
 pub trait DrawPad {
 	fn fill(&mut self, bounds: &Bounds, color: FillColor);
 	fn grade(&mut self, bounds: &Bounds, grade: FillGrade);
-	fn glyph(&mut self, bounds: &Bounds, glyph: &str, color: StrokeColor);
-	fn dark(&mut self, bounds: &Bounds, exclude: &Bounds);
+	fn glyph(&mut self, bounds: &Bounds, glyph: &str, color: StrokeColor) {
+		self.styled_glyph(bounds, glyph, color, StrokeStyle::PLAIN)
+	}
+	fn styled_glyph(&mut self, bounds: &Bounds, glyph: &str, color: StrokeColor, style: StrokeStyle);
+	fn dark(&mut self, bounds: &Bounds, exclude: &Bounds) {
+		self.shade(bounds, exclude, DARK_LEVELS)
+	}
+	/// Darkens `bounds` outside of `exclude` to a level from 0 to `DARK_LEVELS`.
+	fn shade(&mut self, bounds: &Bounds, exclude: &Bounds, level: u8);
+	/// Restricts drawing to `bounds` within the current clip until the matching `pop_clip`.
+	fn push_clip(&mut self, bounds: &Bounds);
+	fn pop_clip(&mut self);
 	fn story(&mut self, bounds: &Bounds, story_id: StoryId);
+	/// The id of the yard under the pointer, or 0 when none is.
+	fn hover_id(&self) -> i32 { 0 }
 }
 
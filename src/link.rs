@@ -1,10 +1,32 @@
-use std::sync::mpsc::{channel, Sender, sync_channel, SyncSender};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::cell::Cell;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
 use std::thread;
 
 pub trait Link<A: Send> {
-	fn send(&self, action: A);
+	/// Sends the action, failing if the receiving end has gone away.
+	fn try_send(&self, action: A) -> Result<(), LinkClosed>;
+	/// Sends the action, logging instead of failing when the receiving end has gone away.
+	fn send(&self, action: A) {
+		if self.try_send(action).is_err() {
+			warn!("Dropped action for closed link");
+		}
+	}
+}
+
+/// The receiver behind a link has stopped.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct LinkClosed;
+
+impl fmt::Display for LinkClosed {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "link closed") }
 }
 
+impl std::error::Error for LinkClosed {}
+
 pub trait Sendable: Clone + Send + 'static {
 	fn send(self, sender_link: &SenderLink<Self>) { sender_link.send(self); }
 	fn send2(self, sender: &Sender<Self>, msg: &str) { sender.send(self).expect(msg); }
@@ -22,58 +44,142 @@ pub trait Sendable: Clone + Send + 'static {
 	}
 }
 
+type Job = Box<dyn FnOnce() + Send>;
+
+static JOBS_QUEUED: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+	static ON_DISPATCHER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs link sinks in order on one shared thread so that making a link never
+/// spawns a thread of its own.
+fn dispatch(job: Job) -> Result<(), LinkClosed> {
+	static DISPATCHER: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+	let dispatcher = DISPATCHER.get_or_init(|| {
+		let (tx, rx) = channel::<Job>();
+		thread::Builder::new().name("LinkDispatcher".to_string()).spawn(move || {
+			ON_DISPATCHER.with(|it| it.set(true));
+			for job in rx {
+				// A panicking sink must not take every other link down with the dispatcher.
+				if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+					error!("Link sink panicked");
+				}
+				JOBS_QUEUED.fetch_sub(1, Ordering::SeqCst);
+			}
+		}).expect("spawn");
+		Mutex::new(tx)
+	});
+	JOBS_QUEUED.fetch_add(1, Ordering::SeqCst);
+	dispatcher.lock().unwrap_or_else(|e| e.into_inner()).send(job).map_err(|_| {
+		JOBS_QUEUED.fetch_sub(1, Ordering::SeqCst);
+		LinkClosed
+	})
+}
+
+/// Waits until every sink queued so far, and every sink those queue in turn, has run.
+pub(crate) fn flush() {
+	loop {
+		let (idle_tx, idle_rx) = channel();
+		// The marker runs on the dispatcher, so no other sink is running; the queue is
+		// empty once the marker is the only job counted.
+		let marker = Box::new(move || { idle_tx.send(JOBS_QUEUED.load(Ordering::SeqCst) == 1).ok(); });
+		if dispatch(marker).is_err() {
+			return;
+		}
+		match idle_rx.recv() {
+			Ok(false) => continue,
+			_ => return,
+		}
+	}
+}
+
+type Sink<A> = Arc<Mutex<Box<dyn Fn(A) + Send>>>;
+
+enum Target<A> {
+	Channel(Sender<A>),
+	Sink(Sink<A>),
+}
+
+impl<A> Clone for Target<A> {
+	fn clone(&self) -> Self {
+		match self {
+			Target::Channel(tx) => Target::Channel(tx.clone()),
+			Target::Sink(sink) => Target::Sink(sink.clone()),
+		}
+	}
+}
+
+impl<A: Send + 'static> Target<A> {
+	fn sink(f: impl Fn(A) + Send + 'static) -> Self {
+		Target::Sink(Arc::new(Mutex::new(Box::new(f))))
+	}
+	fn send(&self, action: A) -> Result<(), LinkClosed> {
+		match self {
+			Target::Channel(tx) => tx.send(action).map_err(|_| LinkClosed),
+			Target::Sink(sink) => {
+				let sink = sink.clone();
+				dispatch(Box::new(move || (sink.lock().unwrap_or_else(|e| e.into_inner()))(action)))
+			}
+		}
+	}
+}
+
+impl<A> fmt::Debug for Target<A> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Target::Channel(_) => write!(f, "Channel"),
+			Target::Sink(_) => write!(f, "Sink"),
+		}
+	}
+}
+
+/// Sends actions to a channel or to a sink run on the shared dispatcher.
+///
+/// The channel used to be the public `tx` field. Make a channel link with
+/// `SenderLink::new(tx)` or `SenderLink::from(tx)` instead of `SenderLink { tx }`.
 #[derive(Debug)]
 pub struct SenderLink<A: Send> {
-	pub tx: Sender<A>,
+	target: Target<A>,
 }
 
 impl<A: Send> Clone for SenderLink<A> {
 	fn clone(&self) -> Self {
-		SenderLink { tx: self.tx.clone() }
+		SenderLink { target: self.target.clone() }
 	}
 }
 
-impl<A: Send> Link<A> for SenderLink<A> {
-	fn send(&self, action: A) {
-		self.tx.send(action).expect("Send Action")
+impl<A: Send + 'static> Link<A> for SenderLink<A> {
+	fn try_send(&self, action: A) -> Result<(), LinkClosed> {
+		self.target.send(action)
+	}
+}
+
+impl<A: Send> From<Sender<A>> for SenderLink<A> {
+	fn from(tx: Sender<A>) -> Self {
+		SenderLink { target: Target::Channel(tx) }
 	}
 }
 
 impl<A: Send + 'static> SenderLink<A> {
+	pub fn new(tx: Sender<A>) -> Self { SenderLink::from(tx) }
 	pub fn to_sync(&self) -> SyncLink<A> {
 		SyncLink::from(self.clone())
 	}
 	pub fn wrap_sender<B: Send + 'static>(sender: Sender<B>, f: impl Fn(A) -> B + Send + 'static) -> Self {
-		let (tx, rx) = channel();
-		thread::Builder::new().name("SenderLink::new".to_string()).spawn(move || {
-			for a in rx {
-				let b = f(a);
-				sender.send(b).expect("send B");
+		Self::wrap_sink(move |a| {
+			if sender.send(f(a)).is_err() {
+				warn!("Dropped action for closed sender");
 			}
-		}).expect("spawn");
-		SenderLink { tx }
+		})
 	}
 	pub fn wrap_sink(sink: impl Fn(A) + Send + 'static) -> Self {
-		let sink_a = Box::new(sink);
-		let (tx, rx) = channel();
-		thread::Builder::new().name("SenderLink::new_f".to_string()).spawn(move || {
-			for a in rx {
-				sink_a(a)
-			}
-		}).expect("spawn");
-		SenderLink { tx }
+		SenderLink { target: Target::sink(sink) }
 	}
 	pub fn ignore() -> Self { Self::wrap_sink(|_| {}) }
 	pub fn map<B: Send + 'static>(&self, f: impl Fn(B) -> A + Send + 'static) -> SenderLink<B> {
-		let f = Box::new(f);
 		let link = self.clone();
-		let (tx, rx) = channel();
-		thread::Builder::new().name("SenderLink::map".to_string()).spawn(move || {
-			for b in rx {
-				link.send(f(b))
-			}
-		}).expect("spawn");
-		SenderLink { tx }
+		SenderLink::wrap_sink(move |b| link.send(f(b)))
 	}
 	pub fn callback<B>(&self, f: impl Fn(B) -> A + Send) -> impl Fn(B) {
 		let link = self.clone();
@@ -90,62 +196,142 @@ pub fn trigger<F: Clone + Send + 'static>(value: F, sender: &Sender<F>) -> Trigg
 	SenderLink::wrap_sender(sender.clone(), move |_| value.clone())
 }
 
+/// Actions a sync link's sink may have waiting before its senders block.
+const SYNC_BOUND: usize = 100;
+
+/// Counts the actions a sync link has queued but its sink has not yet run.
+#[derive(Debug, Default)]
+struct Pending {
+	count: Mutex<usize>,
+	drained: Condvar,
+}
+
+impl Pending {
+	/// Waits for room in the queue. Sinks running on the dispatcher never wait
+	/// because they are the ones draining it.
+	fn acquire(&self) {
+		let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+		if !ON_DISPATCHER.with(|it| it.get()) {
+			while *count >= SYNC_BOUND {
+				count = self.drained.wait(count).unwrap_or_else(|e| e.into_inner());
+			}
+		}
+		*count += 1;
+	}
+	fn release(&self) {
+		let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+		*count -= 1;
+		self.drained.notify_all();
+	}
+}
+
+/// Releases a queued action once its sink has run, even if the sink panicked.
+struct Release(Arc<Pending>);
+
+impl Drop for Release {
+	fn drop(&mut self) { self.0.release() }
+}
+
+/// Like `SenderLink`, but senders block while the sink has `SYNC_BOUND` actions waiting.
 #[derive(Debug)]
-pub struct SyncLink<A> {
-	tx: SyncSender<A>,
+pub struct SyncLink<A: Send> {
+	target: Target<A>,
+	pending: Arc<Pending>,
 }
 
-impl<A> Clone for SyncLink<A> {
+impl<A: Send> Clone for SyncLink<A> {
 	fn clone(&self) -> Self {
-		SyncLink { tx: self.tx.clone() }
+		SyncLink { target: self.target.clone(), pending: self.pending.clone() }
 	}
 }
 
-impl<A: Send> Link<A> for SyncLink<A> {
-	fn send(&self, action: A) {
-		self.tx.send(action).expect("send action")
+impl<A: Send + 'static> Link<A> for SyncLink<A> {
+	fn try_send(&self, action: A) -> Result<(), LinkClosed> {
+		match &self.target {
+			Target::Channel(tx) => tx.send(action).map_err(|_| LinkClosed),
+			Target::Sink(sink) => {
+				self.pending.acquire();
+				let release = Release(self.pending.clone());
+				let sink = sink.clone();
+				dispatch(Box::new(move || {
+					let _release = release;
+					(sink.lock().unwrap_or_else(|e| e.into_inner()))(action)
+				}))
+			}
+		}
 	}
 }
 
 impl<A: Send + 'static> From<SenderLink<A>> for SyncLink<A> {
 	fn from(sender_link: SenderLink<A>) -> Self {
-		let (tx, rx) = sync_channel(100);
-		thread::Builder::new().name("SenderLink from SenderLink".to_string()).spawn(move || {
-			for a in rx {
-				sender_link.send(a)
-			}
-		}).expect("spawn");
-		SyncLink { tx }
+		SyncLink { target: sender_link.target, pending: Arc::new(Pending::default()) }
 	}
 }
 
 impl<A: Send + 'static> SyncLink<A> {
 	pub fn wrap_sink(f: impl Fn(A) + Send + 'static) -> Self {
-		let sink_a = Box::new(f);
-		let (tx, rx) = sync_channel(100);
-		thread::Builder::new().name("SyncLink new".to_string()).spawn(move || {
-			for a in rx {
-				sink_a(a)
-			}
-		}).expect("spawn");
-		SyncLink { tx }
+		SyncLink { target: Target::sink(f), pending: Arc::new(Pending::default()) }
 	}
 	pub fn ignore() -> Self {
 		Self::wrap_sink(|_| {})
 	}
 	pub fn map<B: Send + 'static>(self, f: impl Fn(B) -> A + Send + 'static) -> SyncLink<B> {
-		let f = Box::new(f);
 		let link = self;
-		let (tx, rx) = sync_channel(100);
-		thread::Builder::new().name("SyncLink map".to_string()).spawn(move || {
-			for b in rx {
-				link.send(f(b))
-			}
-		}).expect("spawn");
-		SyncLink { tx }
+		SyncLink::wrap_sink(move |b| link.send(f(b)))
 	}
 	pub fn callback<B>(&self, f: impl Fn(B) -> A + Send) -> impl Fn(B) {
 		let link = self.clone();
 		move |b: B| link.send(f(b))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Mutex;
+	use std::sync::mpsc::channel;
+	use std::thread;
+	use std::time::Duration;
+
+	use crate::{Link, LinkClosed, SenderLink, SyncLink};
+	use crate::link::SYNC_BOUND;
+
+	#[test]
+	fn mapped_links_keep_order() {
+		let (tx, rx) = channel();
+		let link = SenderLink::new(tx);
+		let doubled = link.map(|n: i32| n * 2).to_sync().map(|n: i32| n + 1);
+		for n in 0..100 {
+			doubled.send(n);
+		}
+		assert_eq!(rx.iter().take(100).collect::<Vec<_>>(), (0..100).map(|n| (n + 1) * 2).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn closed_links_fail_without_panic() {
+		let (tx, rx) = channel::<i32>();
+		let link = SenderLink::new(tx);
+		drop(rx);
+		assert_eq!(link.try_send(1), Err(LinkClosed));
+		link.send(2);
+		link.map(|n: i32| n).send(3);
+	}
+
+	#[test]
+	fn full_sync_links_block_senders() {
+		let (gate_tx, gate_rx) = channel::<()>();
+		let gate_rx = Mutex::new(gate_rx);
+		let link = SyncLink::wrap_sink(move |_: usize| { gate_rx.lock().unwrap().recv().ok(); });
+		let (done_tx, done_rx) = channel();
+		thread::spawn(move || {
+			for n in 0..=SYNC_BOUND {
+				link.send(n);
+			}
+			done_tx.send(()).unwrap();
+		});
+		assert!(done_rx.recv_timeout(Duration::from_millis(100)).is_err());
+		for _ in 0..=SYNC_BOUND {
+			gate_tx.send(()).unwrap();
+		}
+		assert!(done_rx.recv_timeout(Duration::from_secs(5)).is_ok());
+	}
+}
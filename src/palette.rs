@@ -1,15 +1,57 @@
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fmt;
 
-use ncurses::{A_DIM, attr_t, COLOR_PAIR, init_color, init_pair, start_color, use_default_colors};
+use ncurses::{A_BOLD, A_DIM, A_ITALIC, A_NORMAL, A_REVERSE, A_UNDERLINE, attr_t, can_change_color, COLOR_PAIR, COLOR_PAIRS, COLORS, has_colors, init_color, init_pair, OK, start_color, use_default_colors};
 
 use crate::spot::SpotFront;
+use crate::theme::{Rgb, Theme};
 
 pub fn body_and_comment_for_fill(color: FillColor) -> (StrokeColor, StrokeColor) {
 	match color {
 		FillColor::Primary => (StrokeColor::BodyOnPrimary, StrokeColor::CommentOnPrimary),
 		FillColor::Side => (StrokeColor::BodyOnSide, StrokeColor::CommentOnSide),
 		FillColor::Background => (StrokeColor::BodyOnBackground, StrokeColor::CommentOnBackground),
+		FillColor::Error | FillColor::Warning | FillColor::Success | FillColor::Info => (StrokeColor::BodyOnStatus, StrokeColor::CommentOnStatus),
+	}
+}
+
+/// The kind of message a status color reports.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Status {
+	Error,
+	Warning,
+	Success,
+	Info,
+}
+
+impl Status {
+	pub fn fill(&self) -> FillColor {
+		match self {
+			Status::Error => FillColor::Error,
+			Status::Warning => FillColor::Warning,
+			Status::Success => FillColor::Success,
+			Status::Info => FillColor::Info,
+		}
+	}
+
+	/// The status color for text drawn on a fill.
+	pub fn stroke_on(&self, fill: FillColor) -> StrokeColor {
+		match (self, fill) {
+			(_, FillColor::Error) | (_, FillColor::Warning) | (_, FillColor::Success) | (_, FillColor::Info) => StrokeColor::BodyOnStatus,
+			(Status::Error, FillColor::Background) => StrokeColor::ErrorOnBackground,
+			(Status::Error, FillColor::Side) => StrokeColor::ErrorOnSide,
+			(Status::Error, FillColor::Primary) => StrokeColor::ErrorOnPrimary,
+			(Status::Warning, FillColor::Background) => StrokeColor::WarningOnBackground,
+			(Status::Warning, FillColor::Side) => StrokeColor::WarningOnSide,
+			(Status::Warning, FillColor::Primary) => StrokeColor::WarningOnPrimary,
+			(Status::Success, FillColor::Background) => StrokeColor::SuccessOnBackground,
+			(Status::Success, FillColor::Side) => StrokeColor::SuccessOnSide,
+			(Status::Success, FillColor::Primary) => StrokeColor::SuccessOnPrimary,
+			(Status::Info, FillColor::Background) => StrokeColor::InfoOnBackground,
+			(Status::Info, FillColor::Side) => StrokeColor::InfoOnSide,
+			(Status::Info, FillColor::Primary) => StrokeColor::InfoOnPrimary,
+		}
 	}
 }
 
@@ -18,8 +60,19 @@ pub enum FillColor {
 	Background,
 	Primary,
 	Side,
+	Error,
+	Warning,
+	Success,
+	Info,
 }
 
+/// The first color redefined in `ColorMode::Rgb`. The 16 ANSI colors below it
+/// keep the terminal's own values.
+const FIRST_RGB_COLOR: i16 = 16;
+
+/// Dark level of a fully faded cell. Partly faded cells use the levels below it.
+pub const DARK_LEVELS: u8 = 4;
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum FillGrade {
 	Plain,
@@ -28,29 +81,6 @@ pub enum FillGrade {
 	Press,
 }
 
-fn fill_i16(color: FillColor, grade: FillGrade, dimmed: bool) -> i16 {
-	let flat = match color {
-		FillColor::Primary => COLOR_BASE02,
-		FillColor::Side => COLOR_BASE2,
-		FillColor::Background => COLOR_BASE3,
-	};
-	let graded = match color {
-		FillColor::Background | FillColor::Primary => match grade {
-			FillGrade::Plain => flat,
-			FillGrade::Select => center(1, flat),
-			FillGrade::Focus => center(2, flat),
-			FillGrade::Press => center(4, flat),
-		}
-		FillColor::Side => match grade {
-			FillGrade::Select => center(-1, flat),
-			FillGrade::Plain => flat,
-			FillGrade::Focus => center(1, flat),
-			FillGrade::Press => center(2, flat),
-		}
-	};
-	if dimmed { darken(graded) } else { graded }
-}
-
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum StrokeColor {
 	CommentOnBackground,
@@ -61,76 +91,202 @@ pub enum StrokeColor {
 	EnabledOnPrimary,
 	BodyOnPrimary,
 	CommentOnPrimary,
+	ErrorOnBackground,
+	ErrorOnSide,
+	ErrorOnPrimary,
+	WarningOnBackground,
+	WarningOnSide,
+	WarningOnPrimary,
+	SuccessOnBackground,
+	SuccessOnSide,
+	SuccessOnPrimary,
+	InfoOnBackground,
+	InfoOnSide,
+	InfoOnPrimary,
+	BodyOnStatus,
+	CommentOnStatus,
+}
+
+/// Text attributes drawn along with a stroke color.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct StrokeStyle(u8);
+
+impl StrokeStyle {
+	pub const PLAIN: StrokeStyle = StrokeStyle(0);
+	pub const BOLD: StrokeStyle = StrokeStyle(1);
+	pub const ITALIC: StrokeStyle = StrokeStyle(2);
+	pub const UNDERLINE: StrokeStyle = StrokeStyle(4);
+	pub const REVERSE: StrokeStyle = StrokeStyle(8);
+	pub const STRIKETHROUGH: StrokeStyle = StrokeStyle(16);
+
+	const NAMES: [(&'static str, StrokeStyle); 5] = [
+		("BOLD", StrokeStyle::BOLD),
+		("ITALIC", StrokeStyle::ITALIC),
+		("UNDERLINE", StrokeStyle::UNDERLINE),
+		("REVERSE", StrokeStyle::REVERSE),
+		("STRIKETHROUGH", StrokeStyle::STRIKETHROUGH),
+	];
+
+	pub fn bits(&self) -> u8 { self.0 }
+	pub fn from_bits(bits: u8) -> Self { StrokeStyle(bits & 0b11111) }
+	pub fn contains(&self, other: StrokeStyle) -> bool { self.0 & other.0 == other.0 }
+	pub fn is_plain(&self) -> bool { self.0 == 0 }
+
+	/// The curses attributes for the style. Curses has no strikethrough, so the screen
+	/// draws it with a combining overlay instead.
+	pub fn to_attr(&self) -> attr_t {
+		let mut attr = A_NORMAL();
+		if self.contains(StrokeStyle::BOLD) { attr |= A_BOLD(); }
+		if self.contains(StrokeStyle::ITALIC) { attr |= A_ITALIC(); }
+		if self.contains(StrokeStyle::UNDERLINE) { attr |= A_UNDERLINE(); }
+		if self.contains(StrokeStyle::REVERSE) { attr |= A_REVERSE(); }
+		attr
+	}
+}
+
+impl std::ops::BitOr for StrokeStyle {
+	type Output = StrokeStyle;
+	fn bitor(self, rhs: Self) -> Self::Output { StrokeStyle(self.0 | rhs.0) }
 }
 
-fn stroke_i16((color, dimmed): (StrokeColor, bool)) -> i16 {
-	let color = match color {
-		StrokeColor::CommentOnBackground => COLOR_BASE1,
-		StrokeColor::CommentOnSide => COLOR_BASE00,
-		StrokeColor::BodyOnBackground => COLOR_BASE00,
-		StrokeColor::BodyOnSide => COLOR_BASE02,
-		StrokeColor::EnabledOnBackground => COLOR_MAGENTA,
-		StrokeColor::EnabledOnPrimary => COLOR_MAGENTA,
-		StrokeColor::BodyOnPrimary => COLOR_BASE1,
-		StrokeColor::CommentOnPrimary => COLOR_BASE00,
-	};
-	if dimmed { darken(color) } else { color }
+impl fmt::Debug for StrokeStyle {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.is_plain() {
+			return write!(f, "PLAIN");
+		}
+		let names = StrokeStyle::NAMES.iter().filter(|(_, style)| self.contains(*style)).map(|(name, _)| *name).collect::<Vec<_>>();
+		write!(f, "{}", names.join("|"))
+	}
 }
 
+/// How the palette turns theme colors into terminal colors.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ColorMode {
+	/// Redefines terminal colors to the exact theme RGB values.
+	Rgb,
+	/// Uses the nearest color of the xterm 256-color cube and gray ramp.
+	Xterm256,
+	/// Uses the nearest of the first 16 or 8 ANSI colors.
+	Ansi(i16),
+	/// No color. Grades, primary fills and dark cells become text attributes.
+	Mono,
+}
+
+impl ColorMode {
+	pub fn detect(has_colors: bool, can_change_color: bool, colors: i32) -> Self {
+		if !has_colors || colors < 8 {
+			ColorMode::Mono
+		} else if can_change_color && colors > FIRST_RGB_COLOR as i32 {
+			ColorMode::Rgb
+		} else if colors >= 256 {
+			ColorMode::Xterm256
+		} else if colors >= 16 {
+			ColorMode::Ansi(16)
+		} else {
+			ColorMode::Ansi(8)
+		}
+	}
+}
 
 #[derive(Debug)]
 pub struct Palette {
-	indices: RefCell<HashMap<(StrokeColor, FillColor, FillGrade, bool), i16>>,
+	mode: Cell<ColorMode>,
+	theme: Theme,
+	colors: RefCell<HashMap<Rgb, i16>>,
+	indices: RefCell<HashMap<(StrokeColor, FillColor, FillGrade, u8), i16>>,
 	next_index: Cell<i16>,
 }
 
 impl Palette {
-	pub fn new() -> Self {
-		start_color();
-		use_default_colors();
-		init_color_by_parts(COLOR_BASE3, BASE3);
-		init_color_by_parts(COLOR_BASE2, BASE2);
-		init_color_by_parts(COLOR_BASE1, BASE1);
-		init_color_by_parts(COLOR_BASE0, BASE0);
-		init_color_by_parts(COLOR_BASE00, BASE00);
-		init_color_by_parts(COLOR_BASE01, BASE01);
-		init_color_by_parts(COLOR_BASE02, BASE02);
-		init_color_by_parts(COLOR_BASE03, BASE03);
-		init_color_by_parts(COLOR_YELLOW, YELLOW);
-		init_color_by_parts(COLOR_MAGENTA, MAGENTA);
-		init_color_by_parts(COLOR_VIOLET, VIOLET);
-		init_color_by_parts(COLOR_GREEN, GREEN);
+	pub fn new(theme: Theme) -> Self {
+		if has_colors() {
+			start_color();
+			use_default_colors();
+		}
+		let mode = ColorMode::detect(has_colors(), can_change_color(), COLORS());
+		info!("Color mode: {:?}", mode);
 		Palette {
+			mode: Cell::new(mode),
+			theme,
+			colors: RefCell::new(HashMap::new()),
 			indices: RefCell::new(HashMap::new()),
 			next_index: Cell::new(1),
 		}
 	}
 
-	pub fn color_pair_index(&self, key: (StrokeColor, FillColor, FillGrade, bool)) -> i16 {
-		match self.existing_index(&key) {
-			Some(index) => index,
-			None => {
-				let (stroke, fill, fill_grade, dimmed) = key;
-				let index = self.advance_index();
-				init_pair(
-					index,
-					stroke_i16((stroke, dimmed)),
-					fill_i16(fill, fill_grade, dimmed),
-				);
-				self.indices.borrow_mut().insert(key, index);
-				index
-			}
+	/// Switches to a new theme. Cells drawn with the old theme keep its colors until redrawn.
+	pub fn set_theme(&mut self, theme: Theme) {
+		self.theme = theme;
+		self.colors.borrow_mut().clear();
+		self.indices.borrow_mut().clear();
+		self.next_index.set(1);
+	}
+
+	/// The pair for the key, or None once the terminal has no pairs left.
+	pub fn color_pair_index(&self, key: (StrokeColor, FillColor, FillGrade, u8)) -> Option<i16> {
+		if let Some(index) = self.existing_index(&key) {
+			return Some(index);
+		}
+		let (stroke, fill) = self.pair_colors(key);
+		let index = self.advance_index()?;
+		init_pair(index, stroke, fill);
+		self.indices.borrow_mut().insert(key, index);
+		Some(index)
+	}
+
+	/// Settles the color mode before a pair gets allocated so both halves of the
+	/// pair come from the same mode.
+	fn pair_colors(&self, (stroke, fill, fill_grade, dimmed): (StrokeColor, FillColor, FillGrade, u8)) -> (i16, i16) {
+		let mode = self.mode.get();
+		let stroke_index = self.color_index(self.theme.stroke_rgb(stroke, dimmed));
+		let fill_index = self.color_index(self.theme.fill_rgb(fill, fill_grade, dimmed));
+		if self.mode.get() == mode {
+			(stroke_index, fill_index)
+		} else {
+			self.pair_colors((stroke, fill, fill_grade, dimmed))
 		}
 	}
 
-	fn existing_index(&self, index_key: &(StrokeColor, FillColor, FillGrade, bool)) -> Option<i16> {
+	fn color_index(&self, rgb: Rgb) -> i16 {
+		if let Some(index) = self.colors.borrow().get(&rgb) {
+			return *index;
+		}
+		let index = match self.mode.get() {
+			ColorMode::Rgb => {
+				let next = FIRST_RGB_COLOR + self.colors.borrow().len() as i16;
+				let defined = (next as i32) < COLORS()
+					&& init_color(next, to_component(rgb[0]), to_component(rgb[1]), to_component(rgb[2])) == OK;
+				if defined {
+					next
+				} else {
+					// The terminal refused the redefinition, so stop trying and approximate
+					// instead. Pairs made so far get remade with approximated colors.
+					self.mode.set(ColorMode::detect(true, false, COLORS()));
+					self.colors.borrow_mut().clear();
+					self.indices.borrow_mut().clear();
+					self.next_index.set(1);
+					return self.color_index(rgb);
+				}
+			}
+			ColorMode::Xterm256 => nearest_xterm256(rgb),
+			ColorMode::Ansi(count) => nearest_ansi(rgb, count),
+			ColorMode::Mono => 0,
+		};
+		self.colors.borrow_mut().insert(rgb, index);
+		index
+	}
+
+	fn existing_index(&self, index_key: &(StrokeColor, FillColor, FillGrade, u8)) -> Option<i16> {
 		self.indices.borrow().get(&index_key).map(|it| it.to_owned())
 	}
 
-	fn advance_index(&self) -> i16 {
+	fn advance_index(&self) -> Option<i16> {
 		let index = self.next_index.get();
+		if index as i32 >= COLOR_PAIRS() {
+			return None;
+		}
 		self.next_index.set(index + 1);
-		index
+		Some(index)
 	}
 
 	fn to_cpi_glyph_dim<'a>(&self, front: &'a SpotFront) -> (i16, &'a str, bool) {
@@ -138,80 +294,115 @@ impl Palette {
 			None => (" ", StrokeColor::BodyOnBackground),
 			Some((ref glyph, color)) => (glyph.as_str(), color),
 		};
-		let color_pair = self.color_pair_index((stroke_color, front.fill_color, front.fill_grade, front.dark));
-		(color_pair, glyph, front.dark)
+		match self.color_pair_index((stroke_color, front.fill_color, front.fill_grade, front.dark)) {
+			Some(color_pair) => (color_pair, glyph, front.dark >= DARK_LEVELS),
+			// Out of pairs, so partly dark cells share the plain pair and get dimmed instead.
+			None => {
+				let color_pair = self.color_pair_index((stroke_color, front.fill_color, front.fill_grade, 0)).unwrap_or(0);
+				(color_pair, glyph, front.dark > 0)
+			}
+		}
 	}
 
 	pub fn to_glyph_attr<'a>(&self, front: &'a SpotFront) -> Option<(&'a str, attr_t)> {
+		if self.mode.get() == ColorMode::Mono {
+			return match &front.stroke {
+				Some((glyph, _)) if glyph.is_empty() => None,
+				Some((glyph, _)) => Some((glyph.as_str(), mono_attr(front) | front.stroke_style.to_attr())),
+				None => Some((" ", mono_attr(front))),
+			};
+		}
 		let (color_pair_index, glyph, darken) = self.to_cpi_glyph_dim(front);
 		if !glyph.is_empty() {
-			let color_attr = COLOR_PAIR(color_pair_index);
+			let color_attr = COLOR_PAIR(color_pair_index) | self.lost_grade_attr(front) | front.stroke_style.to_attr();
 			let attr = if darken { color_attr | A_DIM() } else { color_attr };
 			Some((glyph, attr))
 		} else {
 			None
 		}
 	}
+
+	/// Attributes that stand in for a fill grade the approximated colors cannot tell apart from plain.
+	fn lost_grade_attr(&self, front: &SpotFront) -> attr_t {
+		if self.mode.get() == ColorMode::Rgb || front.fill_grade == FillGrade::Plain {
+			return A_NORMAL();
+		}
+		let graded = self.color_index(self.theme.fill_rgb(front.fill_color, front.fill_grade, front.dark));
+		let plain = self.color_index(self.theme.fill_rgb(front.fill_color, FillGrade::Plain, front.dark));
+		if graded == plain { grade_attr(front.fill_grade) } else { A_NORMAL() }
+	}
+}
+
+fn grade_attr(grade: FillGrade) -> attr_t {
+	match grade {
+		FillGrade::Plain => A_NORMAL(),
+		FillGrade::Select => A_UNDERLINE(),
+		FillGrade::Focus => A_REVERSE(),
+		FillGrade::Press => A_REVERSE() | A_BOLD(),
+	}
+}
+
+fn mono_attr(front: &SpotFront) -> attr_t {
+	let mut attr = grade_attr(front.fill_grade);
+	if front.fill_color != FillColor::Background && front.fill_color != FillColor::Side {
+		// Primary and status fills are drawn reversed, so a reversed grade flips back and gets bold instead.
+		attr = (attr ^ A_REVERSE()) | if attr & A_REVERSE() != 0 { A_BOLD() } else { A_NORMAL() };
+	}
+	if front.dark > 0 { attr | A_DIM() } else { attr }
+}
+
+const ANSI: [Rgb; 16] = [
+	[0, 0, 0], [205, 0, 0], [0, 205, 0], [205, 205, 0],
+	[0, 0, 238], [205, 0, 205], [0, 205, 205], [229, 229, 229],
+	[127, 127, 127], [255, 0, 0], [0, 255, 0], [255, 255, 0],
+	[92, 92, 255], [255, 0, 255], [0, 255, 255], [255, 255, 255],
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: Rgb, b: Rgb) -> i32 {
+	(0..3).map(|i| {
+		let delta = a[i] as i32 - b[i] as i32;
+		delta * delta
+	}).sum()
 }
 
-const COLOR_BASE03: i16 = 0;
-const COLOR_BASE02: i16 = 1;
-const COLOR_BASE01: i16 = 2;
-const COLOR_BASE00: i16 = 3;
-const COLOR_BASE0: i16 = 4;
-const COLOR_BASE1: i16 = 5;
-const COLOR_BASE2: i16 = 6;
-const COLOR_BASE3: i16 = 7;
-const COLOR_YELLOW: i16 = 8;
-const COLOR_MAGENTA: i16 = 9;
-const COLOR_VIOLET: i16 = 10;
-const COLOR_GREEN: i16 = 11;
-
-fn darken(color_i16: i16) -> i16 {
-	let delta = 3;
-	if color_i16 < delta {
-		0
-	} else if color_i16 <= COLOR_BASE3 {
-		color_i16 - delta
-	} else {
-		color_i16
-	}
-}
-
-fn center(magnitude: i16, color_i16: i16) -> i16 {
-	if color_i16 <= COLOR_BASE00 {
-		(color_i16 + magnitude).min(COLOR_BASE00)
-	} else if color_i16 <= COLOR_BASE3 {
-		(color_i16 - magnitude).max(COLOR_BASE0)
-	} else {
-		color_i16
-	}
-}
-
-const BASE03: [i32; 3] = [0, 43, 54];
-const BASE02: [i32; 3] = [7, 54, 66];
-const BASE01: [i32; 3] = [88, 110, 117];
-const BASE00: [i32; 3] = [101, 123, 131];
-const BASE0: [i32; 3] = [131, 148, 150];
-const BASE1: [i32; 3] = [147, 161, 161];
-const BASE2: [i32; 3] = [238, 232, 213];
-const BASE3: [i32; 3] = [253, 246, 227];
-const YELLOW: [i32; 3] = [181, 137, 0];
-const MAGENTA: [i32; 3] = [211, 54, 130];
-const VIOLET: [i32; 3] = [108, 113, 196];
-const GREEN: [i32; 3] = [133, 153, 0];
-
-
-fn init_color_by_parts(color: i16, parts: [i32; 3]) {
-	init_color(
-		color,
-		to_component(parts[0]),
-		to_component(parts[1]),
-		to_component(parts[2]),
-	);
-}
-
-fn to_component(color8: i32) -> i16 {
+fn nearest_ansi(rgb: Rgb, count: i16) -> i16 {
+	(0..count).min_by_key(|index| distance(rgb, ANSI[*index as usize])).unwrap_or(0)
+}
+
+fn nearest_xterm256(rgb: Rgb) -> i16 {
+	let level = |channel: u8| (0..6).min_by_key(|i| (CUBE_LEVELS[*i] as i32 - channel as i32).abs()).unwrap();
+	let (r, g, b) = (level(rgb[0]), level(rgb[1]), level(rgb[2]));
+	let cube = (16 + 36 * r + 6 * g + b) as i16;
+	let cube_rgb = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+	let average = (rgb.iter().map(|it| *it as i32).sum::<i32>() / 3).max(8).min(238);
+	let gray_step = (average - 8 + 5) / 10;
+	let gray = (232 + gray_step) as i16;
+	let gray_value = (8 + 10 * gray_step) as u8;
+	if distance(rgb, [gray_value; 3]) < distance(rgb, cube_rgb) { gray } else { cube }
+}
+
+fn to_component(color8: u8) -> i16 {
 	(color8 as f32 / 256.0 * 1000.0) as i16
 }
 
+#[cfg(test)]
+mod tests {
+	use crate::palette::{ColorMode, nearest_ansi, nearest_xterm256};
+
+	#[test]
+	fn degrade_colors() {
+		assert_eq!(ColorMode::detect(false, false, 0), ColorMode::Mono);
+		assert_eq!(ColorMode::detect(true, true, 256), ColorMode::Rgb);
+		assert_eq!(ColorMode::detect(true, false, 256), ColorMode::Xterm256);
+		assert_eq!(ColorMode::detect(true, true, 8), ColorMode::Ansi(8));
+		assert_eq!(ColorMode::detect(true, true, 16), ColorMode::Ansi(16));
+		assert_eq!(nearest_xterm256([255, 0, 0]), 196);
+		assert_eq!(nearest_xterm256([253, 246, 227]), 230);
+		assert_eq!(nearest_xterm256([128, 128, 128]), 244);
+		assert_eq!(nearest_ansi([220, 50, 47], 16), 1);
+		assert_eq!(nearest_ansi([250, 250, 250], 16), 15);
+		assert_eq!(nearest_ansi([253, 246, 227], 8), 7);
+	}
+}
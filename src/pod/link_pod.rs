@@ -1,9 +1,10 @@
 use std::sync::mpsc::{channel, Sender};
 
-use crate::{ArcYard, Trigger};
+use crate::{ArcYard, KeyEvent, Trigger};
 use crate::pod::Pod;
 use crate::pod_verse::{EditAction, MoveDirection, PodVerseAction};
 use crate::spot::spot_table::SpotTable;
+use crate::yard::model::ScrollAction;
 
 pub struct MainPod {
 	pod_verse_link: Sender<PodVerseAction>,
@@ -11,11 +12,13 @@ pub struct MainPod {
 
 impl MainPod {
 	pub fn new(pod_verse_link: Sender<PodVerseAction>, screen_refresh_trigger: Trigger) -> Self {
-		pod_verse_link.send(PodVerseAction::SetScreenRefreshTrigger(screen_refresh_trigger)).expect("set screen-refresh-trigger");
+		pod_verse_link.send(PodVerseAction::SetScreenRefreshTrigger(screen_refresh_trigger)).ok();
 		MainPod { pod_verse_link: pod_verse_link.clone() }
 	}
 	fn send_edit(&self, edit_action: EditAction, msg: &str) {
-		self.pod_verse_link.send(PodVerseAction::Edit(edit_action)).expect(msg);
+		if self.pod_verse_link.send(PodVerseAction::Edit(edit_action)).is_err() {
+			warn!("Dropped {} for stopped pod verse", msg);
+		}
 	}
 }
 
@@ -25,7 +28,7 @@ impl Pod for MainPod {
 	}
 
 	fn set_width_height(&mut self, width_height: (i32, i32)) {
-		self.pod_verse_link.send(PodVerseAction::SetWidthHeight { width: width_height.0, height: width_height.1 }).expect("set-width-height");
+		self.pod_verse_link.send(PodVerseAction::SetWidthHeight { width: width_height.0, height: width_height.1 }).ok();
 	}
 
 	fn focus_up(&mut self) {
@@ -48,23 +51,48 @@ impl Pod for MainPod {
 		self.send_edit(action, "focus-right");
 	}
 
-	fn insert_char(&self, char: char) {
-		let action = EditAction::InsertChar(char);
-		self.send_edit(action, "insert-char");
+	fn focus_next(&mut self) {
+		let action = EditAction::MoveFocus(MoveDirection::Next);
+		self.send_edit(action, "focus-next");
 	}
 
-	fn insert_space(&self) {
-		let action = EditAction::InsertSpace;
-		self.send_edit(action, "insert-space");
+	fn focus_previous(&mut self) {
+		let action = EditAction::MoveFocus(MoveDirection::Previous);
+		self.send_edit(action, "focus-previous");
+	}
+
+	fn insert_key(&self, key: KeyEvent) {
+		let action = EditAction::InsertKey(key);
+		self.send_edit(action, "insert-key");
+	}
+
+	fn paste(&self, text: String) {
+		let action = EditAction::Paste(text);
+		self.send_edit(action, "paste");
+	}
+
+	fn click(&mut self, x: i32, y: i32) {
+		let action = EditAction::Click { x, y };
+		self.send_edit(action, "click");
+	}
+
+	fn hover(&mut self, x: i32, y: i32) {
+		let action = EditAction::Hover { x, y };
+		self.send_edit(action, "hover");
+	}
+
+	fn wheel(&self, x: i32, y: i32, action: ScrollAction) {
+		let action = EditAction::Wheel { x, y, action };
+		self.send_edit(action, "wheel");
 	}
 
 	fn set_refresh_trigger(&mut self, trigger: Trigger) {
-		self.pod_verse_link.send(PodVerseAction::SetScreenRefreshTrigger(trigger)).expect("send set-screen-refresh-trigger");
+		self.pod_verse_link.send(PodVerseAction::SetScreenRefreshTrigger(trigger)).ok();
 	}
 
 	fn spot_table(&self) -> Option<SpotTable> {
 		let (sender, receiver) = channel();
-		self.pod_verse_link.send(PodVerseAction::ReadSpotTable(sender)).expect("read spot-table");
-		receiver.recv().expect("receive spot-table")
+		self.pod_verse_link.send(PodVerseAction::ReadSpotTable(sender)).ok()?;
+		receiver.recv().ok().flatten()
 	}
 }
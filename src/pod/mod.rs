@@ -1,5 +1,6 @@
-use crate::{ArcYard, Trigger};
+use crate::{ArcYard, KeyEvent, Trigger};
 use crate::spot::spot_table::SpotTable;
+use crate::yard::model::ScrollAction;
 
 pub mod yard;
 pub mod link_pod;
@@ -11,8 +12,14 @@ pub trait Pod {
 	fn focus_down(&mut self);
 	fn focus_left(&mut self);
 	fn focus_right(&mut self);
-	fn insert_char(&self, char: char);
-	fn insert_space(&self);
+	fn focus_next(&mut self);
+	fn focus_previous(&mut self);
+	fn insert_key(&self, key: KeyEvent);
+	fn paste(&self, text: String);
+	fn click(&mut self, x: i32, y: i32);
+	/// Marks the focus under the pointer as hovered without moving focus.
+	fn hover(&mut self, x: i32, y: i32);
+	fn wheel(&self, x: i32, y: i32, action: ScrollAction);
 	fn set_refresh_trigger(&mut self, trigger: Trigger);
 	fn spot_table(&self) -> Option<SpotTable>;
 }
@@ -1,35 +1,42 @@
-use std::sync::mpsc::{channel, Sender};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
+use std::time::Duration;
 
-use crate::{ArcYard, Bounds, Link, pod_verse, Sendable, StoryVerse, Trigger};
+use crate::{ArcYard, Bounds, KeyEvent, Link, LinkClosed, pod_verse, Sendable, SenderLink, StoryFailure, StoryVerse, Timers, Trigger};
+use crate::keymap::Keymap;
 use crate::pod::link_pod::MainPod;
 use crate::pod::Pod;
 use crate::pod_verse::tree::PodTree;
 use crate::spot::spot_table::SpotTable;
 use crate::story_id::StoryId;
+use crate::yard::model::ScrollAction;
 
 pub mod tree;
 
 #[derive(Clone)]
 pub struct PodVerse {
 	pod_verse_link: Sender<PodVerseAction>,
+	story_verse: StoryVerse,
 }
 
 impl PodVerse {
 	pub fn build(story_verse: &StoryVerse) -> Self {
 		let link = pod_verse::connect(story_verse);
-		PodVerse { pod_verse_link: link }
+		PodVerse { pod_verse_link: link, story_verse: story_verse.clone() }
 	}
 	pub fn to_main_pod(&self, screen_refresh_trigger: Trigger) -> MainPod {
 		MainPod::new(self.pod_verse_link.clone(), screen_refresh_trigger)
 	}
+	pub fn keymap(&self) -> &Keymap { self.story_verse.keymap() }
+	/// Streams the failures of stories that panic from now on.
+	pub fn start_failures(&self) -> Receiver<StoryFailure> { self.story_verse.start_failures() }
 	pub fn set_done_trigger(&self, trigger: Sender<()>) {
-		self.pod_verse_link.send(PodVerseAction::SetDoneTrigger(trigger)).expect("set-done-trigger");
+		self.pod_verse_link.send(PodVerseAction::SetDoneTrigger(trigger)).ok();
 	}
-	pub fn read_pod_count(&self) -> usize {
+	pub fn read_pod_count(&self) -> Result<usize, LinkClosed> {
 		let (response_link, response_source) = channel();
-		self.pod_verse_link.send(PodVerseAction::GetPodCount(response_link)).expect("send pod-count request");
-		response_source.recv().expect("receive pod-count response")
+		self.pod_verse_link.send(PodVerseAction::GetPodCount(response_link)).map_err(|_| LinkClosed)?;
+		response_source.recv().map_err(|_| LinkClosed)
 	}
 }
 
@@ -37,6 +44,7 @@ impl PodVerse {
 pub enum PodVerseAction {
 	SetScreenRefreshTrigger(Trigger),
 	Refresh,
+	AnimationFrame,
 	FullRefresh,
 	YardUpdate { story_id: StoryId, story_yard: Option<ArcYard> },
 	SetWidthHeight { width: i32, height: i32 },
@@ -48,9 +56,12 @@ pub enum PodVerseAction {
 
 #[derive(Debug, Clone)]
 pub enum EditAction {
-	InsertSpace,
-	InsertChar(char),
+	InsertKey(KeyEvent),
+	Paste(String),
 	MoveFocus(MoveDirection),
+	Click { x: i32, y: i32 },
+	Hover { x: i32, y: i32 },
+	Wheel { x: i32, y: i32, action: ScrollAction },
 }
 
 #[derive(Debug, Clone)]
@@ -59,19 +70,27 @@ pub enum MoveDirection {
 	Down,
 	Left,
 	Right,
+	Next,
+	Previous,
 }
 
 impl Sendable for PodVerseAction {}
 
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
 fn connect(story_verse: &StoryVerse) -> Sender<PodVerseAction> {
 	let (pod_verse_link, action_source) = channel::<PodVerseAction>();
 	let own_actions = pod_verse_link.clone();
 	let root_story_id = story_verse.root_story_id();
+	let keymap = story_verse.keymap().clone();
 	thread::spawn(move || {
-		let refresh_trigger = PodVerseAction::FullRefresh.into_trigger(&own_actions);
+		let refresh_trigger = PodVerseAction::Refresh.into_trigger(&own_actions);
 		let mut pod_tree = PodTree::new(root_story_id, refresh_trigger.clone());
+		pod_tree.set_keymap(keymap.clone());
 		let mut screen_refresh_trigger: Option<Trigger> = None;
 		let mut done_trigger: Option<Sender<()>> = None;
+		let frame_link = SenderLink::new(own_actions.clone());
+		let mut frame_pending = false;
 		for action in action_source {
 			match action {
 				PodVerseAction::SetScreenRefreshTrigger(trigger) => {
@@ -81,48 +100,65 @@ fn connect(story_verse: &StoryVerse) -> Sender<PodVerseAction> {
 					info!("PodVerseRefresh");
 					if let Some(trigger) = &screen_refresh_trigger { trigger.send(()); }
 				}
+				PodVerseAction::AnimationFrame => {
+					frame_pending = false;
+					pod_tree.animate();
+					own_actions.send(PodVerseAction::Refresh).ok();
+				}
 				PodVerseAction::FullRefresh => {
 					info!("FullRefresh");
 					pod_tree.redraw();
-					own_actions.send(PodVerseAction::Refresh).expect("send refresh");
+					own_actions.send(PodVerseAction::Refresh).ok();
 				}
 				PodVerseAction::GetPodCount(response_link) => {
 					let count = pod_tree.layout_count();
-					response_link.send(count).expect("Send pod count");
+					response_link.send(count).ok();
 				}
 				PodVerseAction::SetDoneTrigger(trigger) => {
+					let quit_trigger = trigger.clone();
+					keymap.bind_default(KeyEvent::ctrl('d'), "Quit", move || { quit_trigger.send(()).ok(); });
 					done_trigger = Some(trigger);
 				}
 				PodVerseAction::SetWidthHeight { width, height } => {
 					pod_tree.set_bounds(Bounds::new(width, height));
-					own_actions.send(PodVerseAction::Refresh).expect("send refresh");
+					own_actions.send(PodVerseAction::Refresh).ok();
 				}
 				PodVerseAction::YardUpdate { story_id, story_yard: yard } => {
 					if yard.is_none() && story_id == root_story_id && done_trigger.is_some() {
-						done_trigger.clone().unwrap().send(()).expect("send done");
+						done_trigger.clone().unwrap().send(()).ok();
 					} else {
 						pod_tree.set_story_yard(story_id, yard);
-						own_actions.send(PodVerseAction::Refresh).expect("send refresh");
+						own_actions.send(PodVerseAction::Refresh).ok();
 					}
 				}
 				PodVerseAction::Edit(edit) => {
 					match edit {
-						EditAction::InsertSpace => pod_tree.insert_space(),
-						EditAction::InsertChar(c) => pod_tree.insert_char(c),
+						EditAction::InsertKey(key) => pod_tree.insert_key(key),
+						EditAction::Paste(text) => pod_tree.paste(text),
 						EditAction::MoveFocus(direction) => match direction {
 							MoveDirection::Up => pod_tree.focus_up(),
 							MoveDirection::Down => pod_tree.focus_down(),
 							MoveDirection::Left => pod_tree.focus_left(),
 							MoveDirection::Right => pod_tree.focus_right(),
+							MoveDirection::Next => pod_tree.focus_next(),
+							MoveDirection::Previous => pod_tree.focus_previous(),
 						}
+						EditAction::Click { x, y } => pod_tree.click(x, y),
+						EditAction::Hover { x, y } => pod_tree.hover(x, y),
+						EditAction::Wheel { x, y, action } => pod_tree.wheel(x, y, action),
 					}
-					own_actions.send(PodVerseAction::FullRefresh).expect("send refresh");
+					own_actions.send(PodVerseAction::Refresh).ok();
 				}
 				PodVerseAction::ReadSpotTable(result) => {
 					let spot_table = Some(pod_tree.to_spot_table());
-					result.send(spot_table).expect("send spot-table");
+					result.send(spot_table).ok();
 				}
 			}
+			// Frames only tick while a transition is running.
+			if pod_tree.is_animating() && !frame_pending {
+				Timers::shared().after(FRAME_INTERVAL, &frame_link, PodVerseAction::AnimationFrame);
+				frame_pending = true;
+			}
 		}
 	});
 	connect_story_verse(story_verse, pod_verse_link.clone());
@@ -130,11 +166,16 @@ fn connect(story_verse: &StoryVerse) -> Sender<PodVerseAction> {
 }
 
 fn connect_story_verse(story_verse: &StoryVerse, pod_verse_link: Sender<PodVerseAction>) {
-	let yards_source = story_verse.start_yards();
+	let yards_source = match story_verse.start_yards() {
+		Ok(yards_source) => yards_source,
+		Err(_) => return,
+	};
 	thread::spawn(move || {
 		for (story_id, story_yard) in yards_source {
 			let action = PodVerseAction::YardUpdate { story_id, story_yard };
-			pod_verse_link.send(action).expect("send update-pod to pod-verse");
+			if pod_verse_link.send(action).is_err() {
+				break;
+			}
 		}
 	});
 }
@@ -5,17 +5,31 @@ use crate::pod_verse::tree::PodPath;
 use crate::spot::spot_table::SpotTable;
 use crate::yui::layout::ActiveFocus;
 
-pub fn link_focus_regions(layout_map: &HashMap<PodPath, LayoutState>, children: &HashMap<PodPath, HashSet<PodPath>>) -> HashMap<PodPath, ActiveFocus> {
-	let all_paths_longest_first = {
-		let mut paths = layout_map.keys().cloned().collect::<Vec<_>>();
-		paths.sort_by_key(|it| it.len());
-		paths.reverse();
-		paths
-	};
-	let mut expanded_focus_map: HashMap<PodPath, ActiveFocus> = HashMap::new();
-	for parent_path in all_paths_longest_first {
-		let path = &parent_path;
-		let unlinked_focus = active_focus_at_path(&path, &expanded_focus_map, layout_map).cloned().unwrap_or_else(|| ActiveFocus::default());
+/// Collects the dirty paths and their ancestors, longest first, so that every
+/// child is relinked before its parent.
+fn relink_order<T>(dirty: &HashSet<PodPath>, present: &HashMap<PodPath, T>) -> Vec<PodPath> {
+	let mut paths = HashSet::new();
+	for path in dirty {
+		let mut next = Some(path.clone());
+		while let Some(path) = next {
+			next = path.parent();
+			if present.contains_key(&path) {
+				paths.insert(path);
+			}
+		}
+	}
+	let mut paths = paths.into_iter().collect::<Vec<_>>();
+	paths.sort_by_key(|it| it.len());
+	paths.reverse();
+	paths
+}
+
+/// Updates `expanded_focus_map` for the dirty paths and their ancestors, reusing
+/// the cached results of every other child.
+pub fn link_focus_regions(layout_map: &HashMap<PodPath, LayoutState>, children: &HashMap<PodPath, HashSet<PodPath>>, expanded_focus_map: &mut HashMap<PodPath, ActiveFocus>, dirty: &HashSet<PodPath>) {
+	expanded_focus_map.retain(|path, _| layout_map.contains_key(path));
+	for parent_path in relink_order(dirty, layout_map) {
+		let unlinked_focus = layout_map.get(&parent_path).map(|it| it.active_focus.clone()).unwrap_or_else(|| ActiveFocus::default());
 		let linked_focus = if let Some(direct_children) = children.get(&parent_path) {
 			let children_nearest_first = {
 				let mut vec = direct_children.iter().collect::<Vec<_>>();
@@ -29,6 +43,10 @@ pub fn link_focus_regions(layout_map: &HashMap<PodPath, LayoutState>, children:
 					warn!("Missing expanded focus at path {:?}", &child_path);
 					ActiveFocus::default()
 				});
+				let story_order = layout_map.get(&parent_path)
+					.and_then(|layout| layout.story_orders.get(child_path.last_story_id()).cloned())
+					.unwrap_or(0);
+				let child_focus = child_focus.with_order_prefix(story_order);
 				let child_depth = child_focus.nearest_z().abs() + 1;
 				sum.expand_seam(insertion_point, child_depth);
 				sum.insert_seam(&child_focus, insertion_point, insertion_bounds.left, insertion_bounds.top);
@@ -39,25 +57,13 @@ pub fn link_focus_regions(layout_map: &HashMap<PodPath, LayoutState>, children:
 		};
 		expanded_focus_map.insert(parent_path, linked_focus);
 	};
-	expanded_focus_map
 }
 
-fn active_focus_at_path<'a>(path: &PodPath, expanded_focus_map: &'a HashMap<PodPath, ActiveFocus>, layout_map: &'a HashMap<PodPath, LayoutState>) -> Option<&'a ActiveFocus> {
-	let expanded = expanded_focus_map.get(path);
-	if expanded.is_some() {
-		expanded
-	} else {
-		layout_map.get(&path).map(|it| &it.active_focus)
-	}
-}
 
-
-pub fn link_spot_tables(spot_tables: &HashMap<PodPath, SpotTable>, children: &HashMap<PodPath, HashSet<PodPath>>, root_path: &PodPath) -> SpotTable {
-	let mut paths = spot_tables.keys().cloned().collect::<Vec<_>>();
-	paths.sort_by_key(PodPath::len);
-	paths.reverse();
-	let mut expanded_tables = HashMap::<PodPath, SpotTable>::new();
-	for path in &paths {
+/// Updates `expanded_tables` for the dirty paths and their ancestors and returns the root table.
+pub fn link_spot_tables(spot_tables: &HashMap<PodPath, SpotTable>, children: &HashMap<PodPath, HashSet<PodPath>>, expanded_tables: &mut HashMap<PodPath, SpotTable>, dirty: &HashSet<PodPath>, root_path: &PodPath) -> SpotTable {
+	expanded_tables.retain(|path, _| spot_tables.contains_key(path));
+	for path in &relink_order(dirty, spot_tables) {
 		let unlinked_table = spot_tables.get(path).expect("spot table").clone();
 		let linked_table = if let Some(children) = children.get(path) {
 			let children_nearest_first = {
@@ -83,6 +89,5 @@ pub fn link_spot_tables(spot_tables: &HashMap<PodPath, SpotTable>, children: &Ha
 		} else { unlinked_table };
 		expanded_tables.insert(path.clone(), linked_table);
 	}
-	let linked = expanded_tables.get(&root_path).cloned().expect("root table");
-	linked
+	expanded_tables.get(&root_path).cloned().unwrap_or_else(|| SpotTable::new(0, 0))
 }
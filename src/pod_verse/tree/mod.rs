@@ -3,11 +3,13 @@ use std::collections::{HashMap, HashSet};
 pub use branch::*;
 pub use path::*;
 
-use crate::{ArcYard, Bounds, layout, Link, render, StoryId, Trigger, yard};
+use crate::{ArcYard, Bounds, KeyEvent, layout, Link, render, StoryId, Trigger, yard};
+use crate::keymap::Keymap;
 use crate::layout::{LayoutState, to_active_focus};
 use crate::pod::Pod;
 use crate::pod_verse::tree::linker::{link_focus_regions, link_spot_tables};
 use crate::spot::spot_table::SpotTable;
+use crate::yard::model::ScrollAction;
 use crate::yui::layout::ActiveFocus;
 
 mod branch;
@@ -22,8 +24,12 @@ pub struct PodTree {
 	focus_map: HashMap<PodPath, ActiveFocus>,
 	children: HashMap<PodPath, HashSet<PodPath>>,
 	spots_map: HashMap<PodPath, SpotTable>,
+	table_map: HashMap<PodPath, SpotTable>,
+	dirty_paths: HashSet<PodPath>,
+	animating_paths: HashSet<PodPath>,
 	active_focus: ActiveFocus,
 	linked_table: SpotTable,
+	keymap: Keymap,
 }
 
 impl Pod for PodTree {
@@ -36,29 +42,59 @@ impl Pod for PodTree {
 	}
 
 	fn focus_up(&mut self) {
-		self.active_focus = self.active_focus.move_up();
+		self.move_focus(self.active_focus.move_up());
 	}
 
 	fn focus_down(&mut self) {
-		self.active_focus = self.active_focus.move_down();
+		self.move_focus(self.active_focus.move_down());
 	}
 
 	fn focus_left(&mut self) {
-		self.active_focus = self.active_focus.move_left();
+		self.move_focus(self.active_focus.move_left());
 	}
 
 	fn focus_right(&mut self) {
-		self.active_focus = self.active_focus.move_right();
+		self.move_focus(self.active_focus.move_right());
 	}
 
-	fn insert_char(&self, char: char) {
+	fn focus_next(&mut self) {
+		self.move_focus(self.active_focus.move_next());
+	}
+
+	fn focus_previous(&mut self) {
+		self.move_focus(self.active_focus.move_previous());
+	}
+
+	fn insert_key(&self, key: KeyEvent) {
+		if self.active_focus.accepts_key(&key) || !self.keymap.dispatch(&key) {
+			let refresh_trigger = self.refresh_trigger.clone();
+			self.active_focus.insert_key(key, move || { refresh_trigger.send(()); });
+		}
+	}
+
+	fn paste(&self, text: String) {
 		let refresh_trigger = self.refresh_trigger.clone();
-		self.active_focus.insert_char(char, move || { refresh_trigger.send(()); });
+		self.active_focus.paste(text, move || { refresh_trigger.send(()); });
 	}
 
-	fn insert_space(&self) {
+	fn click(&mut self, x: i32, y: i32) {
 		let refresh_trigger = self.refresh_trigger.clone();
-		self.active_focus.insert_space(move || { refresh_trigger.send(()); });
+		self.move_focus(self.active_focus.click(y, x, move || { refresh_trigger.send(()); }));
+	}
+
+	fn hover(&mut self, x: i32, y: i32) {
+		let old_hover_id = self.active_focus.hover_id;
+		self.active_focus = self.active_focus.hover(y, x);
+		let new_hover_id = self.active_focus.hover_id;
+		if new_hover_id != old_hover_id {
+			let mut paths = self.focus_paths(old_hover_id);
+			paths.extend(self.focus_paths(new_hover_id));
+			self.render_paths(paths, HashSet::new());
+		}
+	}
+
+	fn wheel(&self, x: i32, y: i32, action: ScrollAction) {
+		self.active_focus.wheel(y, x, action);
 	}
 
 	fn set_refresh_trigger(&mut self, trigger: Trigger) {
@@ -80,8 +116,12 @@ impl PodTree {
 			focus_map: HashMap::new(),
 			children: HashMap::new(),
 			spots_map: HashMap::new(),
+			table_map: HashMap::new(),
+			dirty_paths: HashSet::new(),
+			animating_paths: HashSet::new(),
 			active_focus: ActiveFocus::default(),
 			linked_table: SpotTable::new(0, 0),
+			keymap: Keymap::new(),
 		};
 		tree.layout_paths(vec![tree.root_path.clone()]);
 		tree
@@ -89,6 +129,11 @@ impl PodTree {
 
 	pub fn to_spot_table(&self) -> SpotTable { self.linked_table.clone() }
 
+	pub fn set_keymap(&mut self, keymap: Keymap) {
+		self.keymap = keymap;
+		self.sync_keymap_focus();
+	}
+
 	pub fn root_path(&self) -> &PodPath { &self.root_path }
 
 	pub fn layout_count(&self) -> usize { self.layout_map.len() }
@@ -110,18 +155,74 @@ impl PodTree {
 		} else {
 			self.yard_map.remove(&story_id);
 			self.drop_paths(story_paths);
+			self.relink(HashSet::new());
 		}
 	}
 
+	/// True while some pod's layout holds a running transition.
+	pub fn is_animating(&self) -> bool { !self.animating_paths.is_empty() }
+
+	/// Lays out the pods with running transitions again so they draw their next frame.
+	pub fn animate(&mut self) {
+		let paths = self.animating_paths.iter().cloned().collect();
+		self.layout_paths(paths);
+	}
+
 	pub fn redraw(&mut self) {
 		self.drop_paths(vec![self.root_path.clone()]);
 		self.layout_paths(vec![self.root_path.clone()]);
 	}
 
+	fn set_active_focus(&mut self, active_focus: ActiveFocus) {
+		self.active_focus = active_focus;
+		self.sync_keymap_focus();
+	}
+
+	/// Moves focus without re-layout, re-rendering only the pods that gain or lose it.
+	fn move_focus(&mut self, active_focus: ActiveFocus) {
+		let old_focus_id = self.active_focus.focus_id();
+		self.set_active_focus(active_focus);
+		let new_focus_id = self.active_focus.focus_id();
+		if new_focus_id != old_focus_id {
+			let mut paths = self.focus_paths(old_focus_id);
+			paths.extend(self.focus_paths(new_focus_id));
+			self.render_paths(paths, HashSet::new());
+		}
+	}
+
+	/// Paths whose own layout holds the focus. Searches layouts instead of the focus map
+	/// because a linked focus is present at every ancestor.
+	fn focus_paths(&self, focus_id: i32) -> HashSet<PodPath> {
+		self.layout_map.iter()
+			.filter(|&(_path, layout)| layout.active_focus.contains_focus_id(focus_id))
+			.map(|(path, _layout)| path.clone())
+			.collect()
+	}
+
+	fn sync_keymap_focus(&self) {
+		let focus_paths = self.focus_paths(self.active_focus.focus_id());
+		let focus_path = focus_paths.iter().max_by_key(|path| path.len());
+		let story_ids = focus_path.map(|path| path.story_ids()).unwrap_or_else(Vec::new);
+		self.keymap.set_focus_stories(story_ids);
+	}
+
 	fn drop_paths(&mut self, mut paths: Vec<PodPath>) {
 		while let Some(path) = paths.pop() {
 			self.layout_map.remove(&path);
 			self.spots_map.remove(&path);
+			self.dirty_paths.remove(&path);
+			self.animating_paths.remove(&path);
+			if let Some(parent) = path.parent() {
+				if let Some(siblings) = self.children.get_mut(&parent) {
+					siblings.remove(&path);
+					if siblings.is_empty() {
+						self.children.remove(&parent);
+					}
+				}
+				if self.layout_map.contains_key(&parent) {
+					self.dirty_paths.insert(parent);
+				}
+			}
 			if let Some(children) = self.children.remove(&path) {
 				paths.extend(children)
 			}
@@ -129,7 +230,7 @@ impl PodTree {
 	}
 
 	fn layout_paths(&mut self, mut paths: Vec<PodPath>) {
-		let mut altered = Vec::new();
+		let mut altered = HashSet::new();
 		//Layout the supplied paths and their descendents.
 		while let Some(path) = paths.pop() {
 			let tail_branch = path.last_branch();
@@ -137,6 +238,11 @@ impl PodTree {
 			let layout_state = layout::run(tail_branch.bounds.height(), tail_branch.bounds.width(), &yard, &self.active_focus);
 			let cur_children = layout_state.to_branches().into_iter().map(|child_branch| path.append_branch(child_branch)).collect::<HashSet<_>>();
 			let old_children = self.children.remove(&path).unwrap_or_else(|| HashSet::new());
+			if layout_state.animating {
+				self.animating_paths.insert(path.clone());
+			} else {
+				self.animating_paths.remove(&path);
+			}
 			self.layout_map.insert(path.clone(), layout_state);
 			let dropped_children = old_children.difference(&cur_children).cloned().collect::<Vec<_>>();
 			let added_children = cur_children.difference(&old_children).cloned().collect::<Vec<_>>();
@@ -145,40 +251,97 @@ impl PodTree {
 			}
 			self.drop_paths(dropped_children);
 			paths.extend(added_children);
-			altered.push(path.clone());
+			altered.insert(path.clone());
 		}
-		// Next recompute the focus.
-		self.focus_map = link_focus_regions(&self.layout_map, &self.children);
+		self.relink(altered);
+	}
+
+	/// Relinks focus for the dirty paths, then renders `altered` along with the pods
+	/// that gain or lose focus and relinks their spot tables.
+	fn relink(&mut self, altered: HashSet<PodPath>) {
+		let mut dirty = std::mem::take(&mut self.dirty_paths);
+		dirty.extend(altered.iter().cloned());
+		link_focus_regions(&self.layout_map, &self.children, &mut self.focus_map, &dirty);
+		let old_focus_id = self.active_focus.focus_id();
 		let linked_focus = self.focus_map.get(&self.root_path).cloned().unwrap_or_else(|| ActiveFocus::default());
-		self.active_focus = to_active_focus(&self.active_focus, linked_focus.to_foci(), linked_focus.rear_z);
-		{
-			// Make sure to re-render the pod that contains the active focus
-			let new_focus_id = self.active_focus.focus_id();
-			let mut set = altered.into_iter().collect::<HashSet<_>>();
-			// Search layouts instead of focus_map because any focus can be present in multiple items in the focus map.
-			if let Some((path, _layout)) = self.layout_map.iter().find(|&(_path, layout)| layout.active_focus.contains_focus_id(new_focus_id)) {
-				set.insert(path.clone());
-			} else {
-				warn!("No pod contains new focus id {}!", new_focus_id)
-			}
-			altered = set.into_iter().collect::<Vec<_>>();
+		self.set_active_focus(to_active_focus(&self.active_focus, linked_focus.to_foci(), linked_focus.rear_z));
+		let new_focus_id = self.active_focus.focus_id();
+		let mut render = altered;
+		let new_focus_paths = self.focus_paths(new_focus_id);
+		if new_focus_paths.is_empty() {
+			warn!("No pod contains new focus id {}!", new_focus_id)
+		}
+		render.extend(new_focus_paths);
+		if old_focus_id != new_focus_id {
+			render.extend(self.focus_paths(old_focus_id));
 		}
-		// Render altered pods.
-		while let Some(path) = altered.pop() {
+		self.render_paths(render, dirty);
+	}
+
+	fn render_paths(&mut self, paths: HashSet<PodPath>, mut dirty: HashSet<PodPath>) {
+		for path in paths {
 			let yard = self.yard_map.get(path.last_story_id()).cloned().unwrap_or_else(yard::empty);
-			let bounds_hold = self.layout_map.get(&path).expect("layout state").bounds_hold.clone();
-			let spot_table = render::run(&yard, path.last_bounds().width(), path.last_bounds().height(), bounds_hold, self.active_focus.focus_id());
-			self.spots_map.insert(path, spot_table);
+			let bounds_hold = match self.layout_map.get(&path) {
+				Some(layout) => layout.bounds_hold.clone(),
+				None => continue,
+			};
+			let spot_table = render::run_with_hover(&yard, path.last_bounds().width(), path.last_bounds().height(), bounds_hold, self.active_focus.focus_id(), self.active_focus.hover_id);
+			self.spots_map.insert(path.clone(), spot_table);
+			dirty.insert(path);
 		}
-		self.linked_table = link_spot_tables(&self.spots_map, &self.children, &&self.root_path);
+		self.linked_table = link_spot_tables(&self.spots_map, &self.children, &mut self.table_map, &dirty, &self.root_path);
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::{Bounds, SenderLink, StoryId};
+	use std::time::{Duration, Instant};
+
+	use crate::{ArcYard, Bounds, Cling, Pack, SenderLink, StoryId, StrokeColor, yard};
+	use crate::animation::{Easing, Transition};
 	use crate::pod_verse::tree::{PodPath, PodTree};
 
+	fn build(yards: &[(StoryId, ArcYard)]) -> PodTree {
+		let mut tree = PodTree::new(StoryId::new(0), SenderLink::ignore());
+		tree.set_bounds(Bounds::new(6, 1));
+		for (story_id, yard) in yards {
+			tree.set_story_yard(*story_id, Some(yard.clone()));
+		}
+		tree
+	}
+
+	#[test]
+	fn story_change_relinks_incrementally() {
+		let (root, left, right) = (StoryId::new(0), StoryId::new(1), StoryId::new(2));
+		let label = |text: &str| yard::label(text, StrokeColor::BodyOnBackground, Cling::Left);
+		let root_yard = yard::story(2, right).pack_left(3, yard::story(1, left));
+		let mut tree = build(&[(root, root_yard.clone()), (left, label("ab")), (right, label("cd"))]);
+		tree.set_story_yard(right, Some(label("xyz")));
+		let fresh = build(&[(root, root_yard.clone()), (left, label("ab")), (right, label("xyz"))]);
+		let glyphs = tree.to_spot_table().to_fronts()[0].iter().map(|front| front.stroke.as_ref().map(|(glyph, _)| glyph.clone()).unwrap_or_default()).collect::<String>();
+		assert_eq!(glyphs, "abxyz");
+		assert_eq!(tree.to_spot_table().to_fronts(), fresh.to_spot_table().to_fronts());
+
+		tree.set_story_yard(left, None);
+		let fresh = build(&[(root, root_yard), (right, label("xyz"))]);
+		assert_eq!(tree.to_spot_table().to_fronts(), fresh.to_spot_table().to_fronts());
+	}
+
+	#[test]
+	fn animates_only_while_a_transition_runs() {
+		let (root, child) = (StoryId::new(0), StoryId::new(1));
+		let label = yard::label("ab", StrokeColor::BodyOnBackground, Cling::Left);
+		let running = Transition::starting_now(Duration::from_secs(3600), Easing::Linear);
+		let mut tree = build(&[(root, yard::story(1, child)), (child, yard::slide(label.clone(), (0, 1), running))]);
+		assert!(tree.is_animating());
+		tree.animate();
+		assert!(tree.is_animating());
+
+		let finished = Transition::new(Instant::now() - Duration::from_secs(2), Duration::from_secs(1), Easing::Linear);
+		tree.set_story_yard(child, Some(yard::slide(label, (0, 1), finished)));
+		assert!(!tree.is_animating());
+	}
+
 	#[test]
 	fn pod_tree() {
 		let story_id = StoryId::new(0);
@@ -18,6 +18,14 @@ impl PodPath {
 		PodPath(vec)
 	}
 	pub fn len(&self) -> usize { self.0.len() }
+	pub fn parent(&self) -> Option<Self> {
+		if self.0.len() > 1 {
+			Some(PodPath(self.0[..self.0.len() - 1].to_vec()))
+		} else {
+			None
+		}
+	}
+	pub fn story_ids(&self) -> Vec<StoryId> { self.0.iter().map(|branch| branch.story_id).collect() }
 	pub fn last_story_id(&self) -> &StoryId { &self.last_branch().story_id }
 	pub fn last_bounds(&self) -> &Bounds { &self.last_branch().bounds }
 }
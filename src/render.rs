@@ -1,22 +1,33 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{ArcYard, Bounds};
+use crate::{ArcYard, Bounds, DrawPad};
 use crate::core::bounds::BoundsHold;
 use crate::spot::spot_table::SpotTable;
 
 pub fn run(yard: &ArcYard, max_x: i32, max_y: i32, bounds_hold: Rc<RefCell<BoundsHold>>, focus_id: i32) -> SpotTable {
+	run_with_hover(yard, max_x, max_y, bounds_hold, focus_id, 0)
+}
+
+/// Renders with the yard under the pointer, or 0 for none, available through `DrawPad::hover_id`.
+pub fn run_with_hover(yard: &ArcYard, max_x: i32, max_y: i32, bounds_hold: Rc<RefCell<BoundsHold>>, focus_id: i32, hover_id: i32) -> SpotTable {
 	let bounds_hold = bounds_hold.borrow();
-	let mut draw_pad = SpotTable::new(max_y, max_x);
-	let mut tasks: Vec<(ArcYard, i32)> = vec![(yard.clone(), focus_id)];
+	let mut draw_pad = SpotTable::new(max_y, max_x).with_hover_id(hover_id);
+	let mut tasks: Vec<(ArcYard, i32, Option<Bounds>)> = vec![(yard.clone(), focus_id, None)];
 	loop {
 		let empty_bounds = Bounds::new(0, 0);
-		if let Some((yard, focus_id)) = tasks.pop() {
-			let bounds = bounds_hold.yard_bounds(yard.id()).unwrap_or(&empty_bounds);
+		if let Some((yard, focus_id, parent_clip)) = tasks.pop() {
+			let yard_bounds = bounds_hold.yard_bounds(yard.id());
+			let bounds = yard_bounds.unwrap_or(&empty_bounds);
+			// Yards without bounds of their own draw within their parent's clip.
+			let clips = parent_clip.iter().chain(yard_bounds.into_iter()).collect::<Vec<_>>();
+			clips.iter().for_each(|clip| draw_pad.push_clip(clip));
 			let more = yard.render(bounds, focus_id, &mut draw_pad);
+			clips.iter().for_each(|_| draw_pad.pop_clip());
+			let clip = yard_bounds.or(parent_clip.as_ref()).cloned();
 			if let Some(more) = more {
 				more.iter().for_each(|(yard, alt)| {
-					tasks.insert(0, (yard.clone(), alt.unwrap_or(focus_id)))
+					tasks.insert(0, (yard.clone(), alt.unwrap_or(focus_id), clip.clone()))
 				})
 			}
 		} else {
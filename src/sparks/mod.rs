@@ -1 +1,2 @@
-pub mod selection_editor;
\ No newline at end of file
+pub mod selection_editor;
+pub mod time_travel;
@@ -23,7 +23,7 @@ pub enum SelectionAction {
 	UpdateScroll(ScrollAction),
 }
 
-impl<T: Clone + Send + fmt::Display> Spark for SelectionEditorSpark<T> {
+impl<T: Clone + Send + fmt::Display + 'static> Spark for SelectionEditorSpark<T> {
 	type State = (Vec<T>, Vec<PressModel>, ScrollModel, ButtonModel);
 	type Action = SelectionAction;
 	type Report = Option<(usize, T)>;
@@ -31,21 +31,17 @@ impl<T: Clone + Send + fmt::Display> Spark for SelectionEditorSpark<T> {
 	fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State {
 		let choices = self.choices.clone();
 		let presses = choices.iter().enumerate()
-			.map(|(i, _)| {
-				let release_trigger = ctx.link().to_trigger(SelectionAction::SelectIndex(i));
-				PressModel::new(random(), release_trigger).with_priority(Priority::Default)
-			})
+			.map(|_| PressModel::new(random()).with_priority(Priority::Default))
 			.collect::<Vec<_>>();
 		let scroll = ScrollModel::new_count_height(random(), choices.len(), 3, self.selected);
 		let close_button = {
-			let release_trigger = ctx.link().to_trigger(SelectionAction::Close);
 			let press_link = ctx.link().to_sync().map(|_| SelectionAction::UpdateButton(ButtonAction::Press));
-			ButtonModel::enabled("Close", release_trigger, press_link, Priority::None)
+			ButtonModel::enabled("Close", press_link, Priority::None)
 		};
 		(choices, presses, scroll, close_button)
 	}
 
-	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		let (choices, presses, scroll, button) = ctx.state();
 		match action {
 			SelectionAction::Close => {
@@ -59,13 +55,19 @@ impl<T: Clone + Send + fmt::Display> Spark for SelectionEditorSpark<T> {
 			}
 			SelectionAction::UpdatePress(index, action) => {
 				let mut presses = presses.clone();
-				let press = presses.remove(index).update(action);
-				presses.insert(index, press);
-				AfterFlow::Revise((choices.clone(), presses, scroll.clone(), button.clone()))
+				let (press, release) = match action {
+					PressAction::Press => presses[index].press(SelectionAction::SelectIndex(index)),
+					PressAction::Release => (presses[index].release(), None),
+				};
+				presses[index] = press;
+				AfterFlow::ReviseAnd((choices.clone(), presses, scroll.clone(), button.clone()), release.into_iter().collect())
 			}
 			SelectionAction::UpdateButton(action) => {
-				let button = button.update(action);
-				AfterFlow::Revise((choices.clone(), presses.clone(), scroll.clone(), button))
+				let (button, release) = match action {
+					ButtonAction::Press => button.press(SelectionAction::Close),
+					ButtonAction::Release => (button.release(), None),
+				};
+				AfterFlow::ReviseAnd((choices.clone(), presses.clone(), scroll.clone(), button), release.into_iter().collect())
 			}
 			SelectionAction::UpdateScroll(action) => {
 				if let Some(scroll) = scroll.update(action) {
@@ -113,4 +115,3 @@ impl<T: Clone + Send + fmt::Display> Spark for SelectionEditorSpark<T> {
 		Some(yard)
 	}
 }
-
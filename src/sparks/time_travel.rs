@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use crate::{ActionLog, ActionLogError, AfterFlow, ArcYard, Create, Flow, KeyCode, KeyEvent, Pack, Persist, Replay, SenderLink, Spark, StoryKey};
+use crate::keymap::KeyScope;
+use crate::palette::Status;
+use crate::yui::prelude::yard;
+
+/// Shows a story as it stood after each action of a recorded log. Left and right
+/// step through the actions, home and end jump to either end.
+pub struct TimeTravelSpark<S: Spark> {
+	states: Arc<Vec<S::State>>,
+	log: Arc<ActionLog>,
+}
+
+impl<S: Spark> TimeTravelSpark<S> where S::Action: Persist + 'static, S::Report: 'static {
+	/// Replays the records of the story at `key` into the spark up front.
+	pub fn new(spark: S, key: &StoryKey, log: &ActionLog) -> Result<Self, ActionLogError> {
+		let states = Replay::new(spark, key, log)?.into_states();
+		Ok(TimeTravelSpark { states: Arc::new(states), log: Arc::new(log.story(key)) })
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TimeTravelAction {
+	Back,
+	Forward,
+	First,
+	Last,
+}
+
+pub struct TimeTravelState<T> {
+	pub states: Arc<Vec<T>>,
+	pub log: Arc<ActionLog>,
+	pub position: usize,
+}
+
+impl<T> Clone for TimeTravelState<T> {
+	fn clone(&self) -> Self {
+		TimeTravelState { states: self.states.clone(), log: self.log.clone(), position: self.position }
+	}
+}
+
+impl<S: Spark> Spark for TimeTravelSpark<S> where S::State: Sync, S::Action: 'static {
+	type State = TimeTravelState<S::State>;
+	type Action = TimeTravelAction;
+	type Report = ();
+
+	fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State {
+		ctx.bind_key(KeyEvent::plain(KeyCode::Left), KeyScope::Story, "Step back", TimeTravelAction::Back);
+		ctx.bind_key(KeyEvent::plain(KeyCode::Right), KeyScope::Story, "Step forward", TimeTravelAction::Forward);
+		ctx.bind_key(KeyEvent::plain(KeyCode::Home), KeyScope::Story, "First state", TimeTravelAction::First);
+		ctx.bind_key(KeyEvent::plain(KeyCode::End), KeyScope::Story, "Last state", TimeTravelAction::Last);
+		TimeTravelState { states: self.states.clone(), log: self.log.clone(), position: 0 }
+	}
+
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
+		let state = ctx.state();
+		let last = state.states.len() - 1;
+		let position = match action {
+			TimeTravelAction::Back => state.position.saturating_sub(1),
+			TimeTravelAction::Forward => (state.position + 1).min(last),
+			TimeTravelAction::First => 0,
+			TimeTravelAction::Last => last,
+		};
+		if position == state.position {
+			AfterFlow::Ignore
+		} else {
+			AfterFlow::Revise(TimeTravelState { position, ..state.clone() })
+		}
+	}
+
+	fn render(state: &Self::State, _link: &SenderLink<Self::Action>) -> Option<ArcYard> {
+		let message = match state.position.checked_sub(1).and_then(|i| state.log.records.get(i)) {
+			None => format!("0/{}: created", state.log.len()),
+			Some(record) => format!("{}/{} +{}ms {:?}: {}", state.position, state.log.len(), record.at.as_millis(), record.kind, record.action),
+		};
+		let status = yard::status_line(Status::Info, &message);
+		let story = S::render(&state.states[state.position], &SenderLink::ignore()).unwrap_or_else(yard::empty);
+		Some(story.pack_bottom(1, status))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use crate::{ActionLog, Link, ManualClock, StoryKey, StoryTimers, Timers};
+	use crate::story::spark_with;
+	use crate::tests::Tally;
+
+	use super::{TimeTravelAction, TimeTravelSpark};
+
+	#[test]
+	fn steps_through_recorded_states() {
+		let log = "0 Revise main 2\n5 Revise main/other 7\n10 Revise main 3\n".parse::<ActionLog>().unwrap();
+		let spark = TimeTravelSpark::new(Tally, &StoryKey::new("main"), &log).unwrap();
+		let timers = StoryTimers::new(Timers::new(Arc::new(ManualClock::new())));
+		let story = spark_with(spark, None, None, timers, None, None);
+		let visions = story.visions(1).unwrap();
+		let link = story.link();
+		for action in [TimeTravelAction::Back, TimeTravelAction::Forward, TimeTravelAction::Last, TimeTravelAction::Forward, TimeTravelAction::Back, TimeTravelAction::First] {
+			link.send(action);
+		}
+		let seen = visions.iter().take(5)
+			.map(|it| (it.position, it.states[it.position], it.log.len()))
+			.collect::<Vec<_>>();
+		assert_eq!(seen, vec![(0, 0, 2), (1, 2, 2), (2, 5, 2), (1, 2, 2), (0, 0, 2)]);
+	}
+}
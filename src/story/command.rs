@@ -0,0 +1,134 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Link, SenderLink, StoryTimers};
+
+/// Side effects a flow asks the story runtime to carry out. Commands finish by
+/// sending actions back to the story.
+pub enum Command<A> {
+	/// Runs the closure on a shared worker thread and sends the action it produces.
+	Perform(Box<dyn FnOnce() -> A + Send>),
+	/// Sends the action once the duration passes on the story's timers, unless the story stops first.
+	Delay(Duration, A),
+	/// Runs each command.
+	Batch(Vec<Command<A>>),
+}
+
+impl<A: Send + 'static> Command<A> {
+	/// Runs the task off the story thread and maps its result to an action.
+	pub fn perform<T>(task: impl FnOnce() -> T + Send + 'static, to_action: impl FnOnce(T) -> A + Send + 'static) -> Self {
+		Command::Perform(Box::new(move || to_action(task())))
+	}
+	pub fn delay(duration: Duration, action: A) -> Self { Command::Delay(duration, action) }
+	pub fn batch(commands: impl IntoIterator<Item=Command<A>>) -> Self { Command::Batch(commands.into_iter().collect()) }
+
+	/// Carries out the command, sending its actions to the link.
+	pub fn run(self, link: &SenderLink<A>, timers: &StoryTimers) {
+		match self {
+			Command::Perform(task) => {
+				let link = link.clone();
+				perform(Box::new(move || link.send(task())));
+			}
+			Command::Delay(duration, action) => {
+				timers.after(duration, link, action);
+			}
+			Command::Batch(commands) => for command in commands {
+				command.run(link, timers)
+			},
+		}
+	}
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+const PERFORM_WORKERS: usize = 4;
+
+static PERFORMER: OnceLock<Option<Sender<Job>>> = OnceLock::new();
+
+/// Queues the job for the shared workers, or runs it here if none could start.
+fn perform(job: Job) {
+	let performer = PERFORMER.get_or_init(start_performer);
+	let job = match performer {
+		Some(performer) => match performer.send(job) {
+			Ok(()) => return,
+			Err(e) => e.0,
+		},
+		None => job,
+	};
+	warn!("No command workers, performing on the story thread");
+	job();
+}
+
+fn start_performer() -> Option<Sender<Job>> {
+	let (tx, rx) = channel::<Job>();
+	let rx = Arc::new(Mutex::new(rx));
+	let started = (0..PERFORM_WORKERS).filter(|_| {
+		let rx = rx.clone();
+		let spawned = thread::Builder::new().name("command".to_string()).spawn(move || loop {
+			let job = rx.lock().unwrap_or_else(|e| e.into_inner()).recv();
+			match job {
+				Ok(job) => if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+					error!("Command task panicked");
+				},
+				Err(_) => break,
+			}
+		});
+		if let Err(e) = &spawned {
+			error!("Failed to start command worker: {}", e);
+		}
+		spawned.is_ok()
+	}).count();
+	if started > 0 { Some(tx) } else { None }
+}
+
+impl<A: fmt::Debug> fmt::Debug for Command<A> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Command::Perform(_) => write!(f, "Perform"),
+			Command::Delay(duration, action) => f.debug_tuple("Delay").field(duration).field(action).finish(),
+			Command::Batch(commands) => f.debug_tuple("Batch").field(commands).finish(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::mpsc::channel;
+	use std::time::Duration;
+
+	use crate::{Command, ManualClock, SenderLink, StoryTimers, Timers};
+
+	#[test]
+	fn batch_sends_each_result() {
+		let (tx, rx) = channel();
+		let clock = Arc::new(ManualClock::new());
+		let timers = Timers::new(clock.clone());
+		let command = Command::batch(vec![
+			Command::delay(Duration::from_millis(50), 1),
+			Command::perform(|| "22", |s: &str| s.parse::<i32>().unwrap()),
+		]);
+		command.run(&SenderLink::new(tx), &StoryTimers::new(timers.clone()));
+		assert_eq!(rx.recv().ok(), Some(22));
+		clock.advance(Duration::from_millis(50));
+		timers.fire_due();
+		assert_eq!(rx.recv().ok(), Some(1));
+	}
+
+	#[test]
+	fn delays_stop_with_the_story() {
+		let (tx, rx) = channel();
+		let clock = Arc::new(ManualClock::new());
+		let timers = Timers::new(clock.clone());
+		let story_timers = StoryTimers::new(timers.clone());
+		Command::delay(Duration::from_millis(50), 1).run(&SenderLink::new(tx), &story_timers);
+		story_timers.cancel_all();
+		clock.advance(Duration::from_millis(50));
+		timers.fire_due();
+		assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+	}
+}
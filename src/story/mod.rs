@@ -2,14 +2,22 @@ use std::error::Error;
 use std::sync::mpsc::{channel, Receiver, Sender, sync_channel, SyncSender};
 use std::thread;
 
+pub use command::*;
+pub use persist::*;
+pub use record::*;
 pub use spark::*;
+pub use timer::*;
 
 use crate::{ArcYard, SenderLink};
 pub use crate::story_verse::*;
 use crate::yard::{YardControlMsg, YardPublisher};
 
+mod command;
+mod persist;
+mod record;
 mod scope;
 mod spark;
+mod timer;
 
 /// Stories are evolving elements in an interaction.  They
 /// maintain state, respond to actions, and emit reports.
@@ -34,7 +42,7 @@ impl Worker {
 
 	fn stop(&mut self) {
 		if let Some(ref stopper) = self.stopper {
-			stopper.send(()).expect("send () to stopper");
+			stopper.send(()).ok();
 		} else {}
 		self.stopper = None;
 	}
@@ -49,7 +57,16 @@ impl<Spk> Story<Spk> where Spk: Spark + 'static, Spk::Action: 'static
 {
 	pub fn link(&self) -> SenderLink<Spk::Action> {
 		let sender = self.tx.to_owned();
-		SenderLink::wrap_sink(move |action: Spk::Action| { sender.send(Msg::Update(action)).expect("send Msg::Update"); })
+		SenderLink::wrap_sink(move |action: Spk::Action| {
+			if sender.send(Msg::Update(action)).is_err() {
+				warn!("Dropped action for stopped story");
+			}
+		})
+	}
+
+	/// Saves any revision still waiting out its quiet period.
+	pub(crate) fn flush(&self) {
+		self.tx.send(Msg::Flush).ok();
 	}
 
 	pub fn visions(&self, id: i32) -> Result<Receiver<Spk::State>, Box<dyn Error>> {
@@ -73,18 +90,17 @@ impl<Spk> Story<Spk> where Spk: Spark + 'static, Spk::Action: 'static
 						YardControlMsg::On(sender) => {
 							worker.stop();
 							yards_out = Some(sender);
-							let yards = story.subscribe().expect("subscribe story");
+							let yards = match story.subscribe() {
+								Ok(yards) => yards,
+								Err(_) => break,
+							};
 							let edge_tx = edge_tx.clone();
 							let (end_tx, end_rx) = channel();
 							worker.start(end_tx);
 							thread::Builder::new().name("YardControlMsg::On".to_string()).spawn(move || {
-								let mut done = false;
-								while !done {
-									let yard = yards.recv().expect("receive yard");
-									if end_rx.try_recv().is_err() {
-										edge_tx.send(YardControlMsg::Forward(yard)).expect("send Forward to edge");
-									} else {
-										done = true
+								for yard in yards {
+									if end_rx.try_recv().is_ok() || edge_tx.send(YardControlMsg::Forward(yard)).is_err() {
+										break;
 									}
 								}
 							}).expect("spawn");
@@ -95,7 +111,9 @@ impl<Spk> Story<Spk> where Spk: Spark + 'static, Spk::Action: 'static
 						}
 						YardControlMsg::Forward(yard) => {
 							if let Some(ref forward) = yards_out {
-								forward.send(yard).expect("send yard to sync-sender");
+								if forward.send(yard).is_err() {
+									yards_out = None;
+								}
 							}
 						}
 					}
@@ -114,23 +132,11 @@ impl<Sprk> YardPublisher for Story<Sprk> where Sprk: Spark + 'static {
 		thread::Builder::new().name("YardPublisher subscribe".to_string()).spawn({
 			let link = self.link();
 			move || {
-				let mut done = false;
-				while !done {
-					let vision = {
-						let mut first = visions.recv().expect("receive vision");
-						loop {
-							let second = visions.try_recv();
-							if second.is_err() {
-								break;
-							} else {
-								first = second.expect("second");
-							}
-						}
-						first
-					};
+				while let Ok(first) = visions.recv() {
+					let vision = visions.try_iter().last().unwrap_or(first);
 					if let Some(yard) = Sprk::render(&vision, &link) {
-						if let Err(_) = tx_yard.send(yard) {
-							done = true;
+						if tx_yard.send(yard).is_err() {
+							break;
 						}
 					};
 				}
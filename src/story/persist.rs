@@ -0,0 +1,255 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{SenderLink, TimerId, Timers};
+
+/// State that can be written to storage and read back after a restart.
+pub trait Persist: Sized {
+	fn persist(&self) -> String;
+	fn restore(saved: &str) -> Option<Self>;
+}
+
+macro_rules! persist_from_str {
+	($($t:ty),*) => {$(
+		impl Persist for $t {
+			fn persist(&self) -> String { self.to_string() }
+			fn restore(saved: &str) -> Option<Self> { saved.parse().ok() }
+		}
+	)*}
+}
+
+persist_from_str!(String, bool, i32, i64, u32, u64, usize, f64);
+
+/// Names a story the same way on every run. Sub-stories and dialogs extend
+/// their parent's key.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct StoryKey(String);
+
+impl StoryKey {
+	pub fn new(key: &str) -> Self { StoryKey(key.to_string()) }
+	pub fn as_str(&self) -> &str { &self.0 }
+	pub fn child(&self, name: &str) -> Self { StoryKey(format!("{}/{}", self.0, name)) }
+}
+
+impl fmt::Display for StoryKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+}
+
+/// Where persisted state lives between runs.
+pub trait Storage: Send + Sync {
+	fn load(&self, key: &StoryKey) -> Option<String>;
+	fn save(&self, key: &StoryKey, saved: &str) -> io::Result<()>;
+}
+
+/// Keeps each story's state in its own file within a directory.
+#[derive(Clone, Debug)]
+pub struct DirStorage {
+	dir: PathBuf,
+}
+
+impl DirStorage {
+	pub fn new(dir: impl Into<PathBuf>) -> Self { DirStorage { dir: dir.into() } }
+
+	/// Percent-encodes the key's bytes into one file name. A leading `.` is encoded too,
+	/// so names never start with one and `.` and `..` stay inside the directory.
+	fn file_name(key: &StoryKey) -> String {
+		key.as_str().bytes().enumerate().map(|(i, b)| match b {
+			b'.' if i == 0 => "%2E".to_string(),
+			b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' | b'_' => (b as char).to_string(),
+			_ => format!("%{:02X}", b),
+		}).collect()
+	}
+	fn path(&self, key: &StoryKey) -> PathBuf { self.dir.join(Self::file_name(key)) }
+	/// Temp names start with `.`, which no key's file name does.
+	fn temp_path(&self, key: &StoryKey) -> PathBuf { self.dir.join(format!(".{}.tmp", Self::file_name(key))) }
+}
+
+impl Storage for DirStorage {
+	fn load(&self, key: &StoryKey) -> Option<String> {
+		fs::read_to_string(self.path(key)).ok()
+	}
+	fn save(&self, key: &StoryKey, saved: &str) -> io::Result<()> {
+		fs::create_dir_all(&self.dir)?;
+		// Write aside and rename so a crash mid-write leaves the previous state intact.
+		let temp = self.temp_path(key);
+		fs::write(&temp, saved)?;
+		fs::rename(temp, self.path(key))
+	}
+}
+
+/// Keeps state in memory. Useful in tests.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+	saved: Mutex<HashMap<StoryKey, String>>,
+}
+
+impl MemoryStorage {
+	pub fn new() -> Self { Self::default() }
+}
+
+impl Storage for MemoryStorage {
+	fn load(&self, key: &StoryKey) -> Option<String> {
+		self.saved.lock().unwrap_or_else(|e| e.into_inner()).get(key).cloned()
+	}
+	fn save(&self, key: &StoryKey, saved: &str) -> io::Result<()> {
+		self.saved.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone(), saved.to_string());
+		Ok(())
+	}
+}
+
+/// Storage and key for one story, handed down to its sub-stories and dialogs.
+#[derive(Clone)]
+pub struct Persistence {
+	storage: Arc<dyn Storage>,
+	key: StoryKey,
+	debounce: Duration,
+}
+
+impl Persistence {
+	/// Persists the main story under the key "main". Revisions are saved once
+	/// the state has been quiet for half a second.
+	pub fn new(storage: Arc<dyn Storage>) -> Self {
+		Persistence { storage, key: StoryKey::new("main"), debounce: Duration::from_millis(500) }
+	}
+	pub fn with_debounce(self, debounce: Duration) -> Self { Persistence { debounce, ..self } }
+	pub fn key(&self) -> &StoryKey { &self.key }
+	pub fn child(&self, name: &str) -> Self { Persistence { key: self.key.child(name), ..self.clone() } }
+	pub fn load<T: Persist>(&self) -> Option<T> {
+		self.storage.load(&self.key).and_then(|saved| T::restore(&saved))
+	}
+}
+
+impl fmt::Debug for Persistence {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Persistence").field("key", &self.key).field("debounce", &self.debounce).finish()
+	}
+}
+
+pub(crate) type Encode = Box<dyn Fn(&dyn Any) -> Option<String> + Send>;
+
+pub(crate) fn encoder<T: Persist + 'static>() -> Encode {
+	Box::new(|state: &dyn Any| state.downcast_ref::<T>().map(Persist::persist))
+}
+
+/// Saves a story's state some time after its latest revision.
+pub(crate) struct Snapshots {
+	persistence: Persistence,
+	encode: Encode,
+	timers: Timers,
+	pending: Option<TimerId>,
+}
+
+impl Snapshots {
+	pub fn new(persistence: Persistence, encode: Encode, timers: Timers) -> Self {
+		Snapshots { persistence, encode, timers, pending: None }
+	}
+	/// Restarts the quiet period. The action arrives when it ends.
+	pub fn revised<A: Send + 'static>(&mut self, link: &SenderLink<A>, save_action: A) {
+		if let Some(id) = self.pending.take() {
+			self.timers.cancel(id);
+		}
+		self.pending = Some(self.timers.after(self.persistence.debounce, link, save_action));
+	}
+	/// Saves now if a revision is waiting.
+	pub fn flush(&mut self, state: &dyn Any) {
+		if let Some(id) = self.pending.take() {
+			self.timers.cancel(id);
+			self.save(state);
+		}
+	}
+	pub fn save(&mut self, state: &dyn Any) {
+		self.pending = None;
+		match (self.encode)(state) {
+			Some(saved) => if let Err(e) = self.persistence.storage.save(&self.persistence.key, &saved) {
+				warn!("Failed to save story {}: {}", self.persistence.key, e);
+			},
+			None => warn!("Story {} restored a type other than its state", self.persistence.key),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::mpsc::channel;
+	use std::thread;
+	use std::time::Duration;
+
+	use crate::{DirStorage, link, Link, ManualClock, MemoryStorage, Persistence, Storage, StoryKey, StoryTimers, StoryVerseAction, Timers};
+	use crate::keymap::Keymap;
+	use crate::story::spark_with;
+	use crate::tests::Tally;
+	use crate::story_id::StoryId;
+	use crate::story_verse::story_box::{self, StoryBoxAction};
+
+	#[test]
+	fn saves_after_quiet_period_and_restores() {
+		let clock = Arc::new(ManualClock::new());
+		let timers = Timers::new(clock.clone());
+		let storage = Arc::new(MemoryStorage::new());
+		let persistence = Persistence::new(storage.clone()).with_debounce(Duration::from_secs(1));
+		let key = StoryKey::new("main");
+
+		let story = spark_with(Tally, None, None, StoryTimers::new(timers.clone()), Some(persistence.clone()), None);
+		let visions = story.visions(1).unwrap();
+		story.link().send(2);
+		story.link().send(3);
+		assert_eq!(visions.iter().find(|it| *it == 5), Some(5));
+		assert_eq!(storage.load(&key), None);
+
+		clock.advance(Duration::from_secs(1));
+		timers.fire_due();
+		let saved = (0..100).find_map(|_| storage.load(&key).or_else(|| {
+			thread::sleep(Duration::from_millis(10));
+			None
+		}));
+		assert_eq!(saved.as_deref(), Some("5"));
+
+		let restarted = spark_with(Tally, None, None, StoryTimers::new(timers), Some(persistence), None);
+		assert_eq!(restarted.visions(1).unwrap().recv().ok(), Some(5));
+	}
+
+	#[test]
+	fn stopping_saves_pending_revision() {
+		let timers = Timers::new(Arc::new(ManualClock::new()));
+		let storage = Arc::new(MemoryStorage::new());
+		let persistence = Persistence::new(storage.clone()).with_debounce(Duration::from_secs(3600));
+		let (verse_link, verse_actions) = channel();
+		let story_id = StoryId::new(0);
+		let (box_link, link) = story_box::connect(Tally, None, story_id, story_id, verse_link, Keymap::new(), Some(persistence), None, timers);
+		link.send(2);
+		link::flush();
+		box_link.send(StoryBoxAction::SetStopped).unwrap();
+
+		let stopped = verse_actions.iter().any(|it| matches!(it, StoryVerseAction::StoryBoxStopped(_)));
+		assert!(stopped);
+		let saved = (0..100).find_map(|_| storage.load(&StoryKey::new("main")).or_else(|| {
+			thread::sleep(Duration::from_millis(10));
+			None
+		}));
+		assert_eq!(saved.as_deref(), Some("2"));
+	}
+
+	#[test]
+	fn dir_storage_keeps_keys_apart() {
+		let dir = std::env::temp_dir().join(format!("yui-dir-storage-{}", std::process::id()));
+		let storage = DirStorage::new(&dir);
+		let keys = [".", "..", "a", "a.tmp", "main.a", "main.b", "main/x", "ü"];
+		for key in keys {
+			storage.save(&StoryKey::new(key), key).unwrap();
+		}
+		let loaded = keys.iter().map(|key| storage.load(&StoryKey::new(key))).collect::<Vec<_>>();
+		let entries = std::fs::read_dir(&dir).unwrap().count();
+		std::fs::remove_dir_all(&dir).ok();
+		assert_eq!(loaded, keys.iter().map(|key| Some(key.to_string())).collect::<Vec<_>>());
+		assert_eq!(entries, keys.len());
+		assert_eq!(DirStorage::file_name(&StoryKey::new("main.a")), "main.a");
+		assert_eq!(DirStorage::file_name(&StoryKey::new("..")), "%2E.");
+	}
+}
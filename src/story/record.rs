@@ -0,0 +1,324 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{AfterFlow, Create, Flow, ManualClock, Persist, SenderLink, Spark, StoryKey, StoryTimers, Timers};
+use crate::story::scope::StoryScope;
+
+/// The kind of `AfterFlow` a story's flow returned.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum FlowKind {
+	Ignore,
+	Report,
+	Close,
+	Revise,
+	ReviseQuietly,
+	ReviseAnd,
+	Run,
+}
+
+impl<State, Report, Action> AfterFlow<State, Report, Action> {
+	pub fn kind(&self) -> FlowKind {
+		match self {
+			AfterFlow::Ignore => FlowKind::Ignore,
+			AfterFlow::Report(_) => FlowKind::Report,
+			AfterFlow::Close(_) => FlowKind::Close,
+			AfterFlow::Revise(_) => FlowKind::Revise,
+			AfterFlow::ReviseQuietly(_) => FlowKind::ReviseQuietly,
+			AfterFlow::ReviseAnd(_, _) => FlowKind::ReviseAnd,
+			AfterFlow::Run(_) => FlowKind::Run,
+		}
+	}
+}
+
+impl FromStr for FlowKind {
+	type Err = ActionLogError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let kind = match s {
+			"Ignore" => FlowKind::Ignore,
+			"Report" => FlowKind::Report,
+			"Close" => FlowKind::Close,
+			"Revise" => FlowKind::Revise,
+			"ReviseQuietly" => FlowKind::ReviseQuietly,
+			"ReviseAnd" => FlowKind::ReviseAnd,
+			"Run" => FlowKind::Run,
+			_ => return Err(ActionLogError(format!("unknown flow kind {:?}", s))),
+		};
+		Ok(kind)
+	}
+}
+
+/// One action a story received, when it arrived and what the flow did with it.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ActionRecord {
+	/// Time since the recorder started.
+	pub at: Duration,
+	/// The story that received the action.
+	pub story: StoryKey,
+	/// The action as written by `Persist::persist`.
+	pub action: String,
+	pub kind: FlowKind,
+}
+
+/// Recorded actions in arrival order. Displays as one `millis kind story action`
+/// line per record and parses back from the same text.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ActionLog {
+	pub records: Vec<ActionRecord>,
+}
+
+impl ActionLog {
+	pub fn len(&self) -> usize { self.records.len() }
+	pub fn is_empty(&self) -> bool { self.records.is_empty() }
+	/// The records of one story.
+	pub fn story(&self, key: &StoryKey) -> ActionLog {
+		ActionLog { records: self.records.iter().filter(|record| &record.story == key).cloned().collect() }
+	}
+}
+
+impl fmt::Display for ActionLog {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for record in &self.records {
+			writeln!(f, "{} {:?} {} {}", record.at.as_millis(), record.kind, escape(record.story.as_str()).replace(' ', "\\s"), escape(&record.action))?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for ActionLog {
+	type Err = ActionLogError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let records = s.lines().filter(|line| !line.is_empty()).map(|line| {
+			let mut parts = line.splitn(4, ' ');
+			let at = parts.next().and_then(|it| it.parse::<u64>().ok())
+				.ok_or_else(|| ActionLogError(format!("bad time in {:?}", line)))?;
+			let kind = parts.next().unwrap_or("").parse::<FlowKind>()?;
+			let story = StoryKey::new(&unescape(parts.next().unwrap_or("")));
+			let action = unescape(parts.next().unwrap_or(""));
+			Ok(ActionRecord { at: Duration::from_millis(at), story, action, kind })
+		}).collect::<Result<Vec<_>, ActionLogError>>()?;
+		Ok(ActionLog { records })
+	}
+}
+
+fn escape(action: &str) -> String {
+	action.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(action: &str) -> String {
+	let mut out = String::with_capacity(action.len());
+	let mut chars = action.chars();
+	while let Some(c) = chars.next() {
+		if c == '\\' {
+			match chars.next() {
+				Some('n') => out.push('\n'),
+				Some('r') => out.push('\r'),
+				Some('s') => out.push(' '),
+				Some(other) => out.push(other),
+				None => out.push('\\'),
+			}
+		} else {
+			out.push(c);
+		}
+	}
+	out
+}
+
+/// An action log could not be read or replayed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ActionLogError(pub String);
+
+impl fmt::Display for ActionLogError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "action log: {}", self.0) }
+}
+
+impl std::error::Error for ActionLogError {}
+
+/// Collects the actions of every story that opts in with `Create::record`, handed
+/// down to sub-stories and dialogs under their keys.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+	start: Instant,
+	key: StoryKey,
+	records: Arc<Mutex<Vec<ActionRecord>>>,
+}
+
+impl Recorder {
+	/// Records the main story under the key "main".
+	pub fn new() -> Self { Recorder { start: Instant::now(), key: StoryKey::new("main"), records: Arc::new(Mutex::new(Vec::new())) } }
+	pub fn key(&self) -> &StoryKey { &self.key }
+	pub fn child(&self, name: &str) -> Self { Recorder { key: self.key.child(name), ..self.clone() } }
+	/// The actions recorded so far by this recorder and every recorder it shares records with.
+	pub fn log(&self) -> ActionLog {
+		ActionLog { records: self.records.lock().unwrap_or_else(|e| e.into_inner()).clone() }
+	}
+	fn push(&self, action: String, kind: FlowKind) {
+		let at = self.start.elapsed();
+		let story = self.key.clone();
+		self.records.lock().unwrap_or_else(|e| e.into_inner()).push(ActionRecord { at, story, action, kind });
+	}
+}
+
+impl Default for Recorder {
+	fn default() -> Self { Self::new() }
+}
+
+/// A recorder paired with the encoder for one story's actions.
+pub(crate) struct Recording<A> {
+	recorder: Recorder,
+	encode: fn(&A) -> String,
+}
+
+impl<A: Persist> Recording<A> {
+	pub fn new(recorder: &Recorder) -> Self { Recording { recorder: recorder.clone(), encode: A::persist } }
+}
+
+impl<A> Recording<A> {
+	pub fn encode(&self, action: &A) -> String { (self.encode)(action) }
+	pub fn push(&self, action: String, kind: FlowKind) { self.recorder.push(action, kind) }
+}
+
+/// Feeds one story's records from a log into a fresh story on the calling thread
+/// so its states can be stepped through. Commands, timers and reports are dropped.
+/// Stories that open dialogs or redraw during flow cannot be replayed.
+pub struct Replay<S: Spark> {
+	spark: S,
+	log: ActionLog,
+	actions: Vec<Option<S::Action>>,
+	states: Vec<S::State>,
+	scope: StoryScope<S::State, S::Action, S::Report>,
+	position: usize,
+}
+
+impl<S: Spark> Replay<S> where S::Action: Persist + 'static, S::Report: 'static {
+	/// Replays the records of the story at `key`, ignoring every other story's.
+	pub fn new(spark: S, key: &StoryKey, log: &ActionLog) -> Result<Self, ActionLogError> {
+		let log = log.story(key);
+		let actions = log.records.iter().enumerate().map(|(i, record)| {
+			S::Action::restore(&record.action)
+				.map(Some)
+				.ok_or_else(|| ActionLogError(format!("cannot restore action {} from {:?}", i, record.action)))
+		}).collect::<Result<Vec<_>, ActionLogError>>()?;
+		let timers = StoryTimers::new(Timers::new(Arc::new(ManualClock::new())));
+		let create = Create::detached(timers.clone());
+		let state = spark.create(&create);
+		let scope = StoryScope::new(state.clone(), SenderLink::ignore(), None, SenderLink::ignore(), timers);
+		Ok(Replay { spark, log, actions, states: vec![state], scope, position: 0 })
+	}
+
+	/// The number of recorded actions.
+	pub fn len(&self) -> usize { self.log.len() }
+	pub fn is_empty(&self) -> bool { self.log.is_empty() }
+	/// The number of actions applied to reach the current state.
+	pub fn position(&self) -> usize { self.position }
+	pub fn state(&self) -> &S::State { &self.states[self.position] }
+	pub fn record(&self, index: usize) -> Option<&ActionRecord> { self.log.records.get(index) }
+
+	/// Applies the next action. Returns false at the end of the log.
+	pub fn forward(&mut self) -> bool {
+		if self.position + 1 < self.states.len() {
+			self.position += 1;
+			return true;
+		}
+		let action = match self.actions.get_mut(self.position).and_then(Option::take) {
+			Some(action) => action,
+			None => return false,
+		};
+		let after = S::flow(&self.spark, action, &self.scope);
+		match after {
+			AfterFlow::Revise(next) | AfterFlow::ReviseQuietly(next) | AfterFlow::ReviseAnd(next, _) => {
+				self.scope.set_vision(next, false)
+			}
+			AfterFlow::Ignore | AfterFlow::Report(_) | AfterFlow::Close(_) | AfterFlow::Run(_) => {}
+		}
+		self.states.push(self.scope.state().clone());
+		self.position += 1;
+		true
+	}
+
+	/// Returns to the previous state. Returns false at the start of the log.
+	pub fn back(&mut self) -> bool {
+		if self.position == 0 {
+			return false;
+		}
+		self.position -= 1;
+		true
+	}
+
+	/// Moves to the state after `position` actions, or to the last state.
+	pub fn seek(&mut self, position: usize) {
+		if position < self.states.len() {
+			self.position = position;
+		} else {
+			while self.position < position && self.forward() {}
+		}
+	}
+
+	/// Applies every remaining action and returns the state after each one,
+	/// starting with the created state.
+	pub fn into_states(mut self) -> Vec<S::State> {
+		self.seek(self.len());
+		self.states
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use crate::{ActionLog, FlowKind, Link, ManualClock, Recorder, Replay, StoryKey, StoryTimers, Timers};
+	use crate::story::spark_with;
+	use crate::tests::Tally;
+
+	#[test]
+	fn log_survives_text() {
+		let recorder = Recorder::new();
+		recorder.push("2".to_string(), FlowKind::Revise);
+		recorder.child("a dialog").push("a b\\\nc".to_string(), FlowKind::Ignore);
+		let text = recorder.log().to_string();
+		assert_eq!(text.lines().count(), 2);
+		let parsed = text.parse::<ActionLog>().unwrap();
+		assert_eq!(parsed.to_string(), text);
+		assert_eq!(parsed.records[1].story, StoryKey::new("main/a dialog"));
+		assert_eq!(parsed.records[1].action, "a b\\\nc");
+		assert_eq!(parsed.records[1].kind, FlowKind::Ignore);
+	}
+
+	#[test]
+	fn story_records_updates_with_flow_kind() {
+		let recorder = Recorder::new();
+		let timers = StoryTimers::new(Timers::new(Arc::new(ManualClock::new())));
+		let story = spark_with(Tally, None, None, timers, None, Some(recorder.clone()));
+		let visions = story.visions(1).unwrap();
+		story.link().send(0);
+		story.link().send(2);
+		assert_eq!(visions.iter().find(|it| *it == 2), Some(2));
+		let records = recorder.log().records.into_iter()
+			.map(|record| (record.story, record.action, record.kind))
+			.collect::<Vec<_>>();
+		assert_eq!(records, vec![
+			(StoryKey::new("main"), "0".to_string(), FlowKind::Ignore),
+			(StoryKey::new("main"), "2".to_string(), FlowKind::Revise),
+		]);
+	}
+
+	#[test]
+	fn replay_steps_through_states() {
+		let log = "0 Revise main 2\n5 Revise main/left 7\n10 Ignore main 0\n25 Revise main 3\n".parse::<ActionLog>().unwrap();
+		let mut replay = Replay::new(Tally, &StoryKey::new("main"), &log).unwrap();
+		assert_eq!(replay.len(), 3);
+		assert_eq!(*replay.state(), 0);
+		assert!(replay.forward());
+		assert!(replay.forward());
+		assert_eq!((replay.position(), *replay.state()), (2, 2));
+		replay.seek(10);
+		assert_eq!((replay.position(), *replay.state()), (3, 5));
+		assert!(!replay.forward());
+		assert!(replay.back());
+		assert_eq!(*replay.state(), 2);
+		assert_eq!(replay.into_states(), vec![0, 2, 2, 5]);
+	}
+}
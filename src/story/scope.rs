@@ -1,7 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::Sender;
+use std::time::Duration;
 
-use crate::{Flow, Link, SenderLink, Spark};
+use crate::{Flow, Link, SenderLink, Spark, StoryTimers, TimerId};
 use crate::app::Edge;
 use crate::dialog_story::DialogStory;
 
@@ -11,6 +12,7 @@ pub(super) struct StoryScope<V, A: Send, R: Send, > {
 	link: SenderLink<A>,
 	edge: Option<Edge>,
 	on_report: SenderLink<R>,
+	timers: StoryTimers,
 }
 
 impl<V: Clone, A: Send, R: Send + 'static> StoryScope<V, A, R> {
@@ -38,8 +40,8 @@ impl<V: Clone, A: Send, R: Send + 'static> StoryScope<V, A, R> {
 		}
 	}
 
-	pub fn new(vision: V, link: SenderLink<A>, edge: Option<Edge>, on_report: SenderLink<R>) -> Self {
-		StoryScope { vision, watchers: HashMap::new(), link, on_report, edge }
+	pub fn new(vision: V, link: SenderLink<A>, edge: Option<Edge>, on_report: SenderLink<R>, timers: StoryTimers) -> Self {
+		StoryScope { vision, watchers: HashMap::new(), link, on_report, edge, timers }
 	}
 }
 
@@ -48,10 +50,10 @@ impl<S, A: Send, R: Send + 'static> Flow<S, A, R> for StoryScope<S, A, R> {
 
 	fn link(&self) -> &SenderLink<A> { &self.link }
 
-	fn start_prequel<T: Spark + Send + 'static>(&self, spark: T, on_report: SenderLink<T::Report>) -> DialogStory {
+	fn start_prequel<T: Spark + Send + 'static>(&self, key: &str, spark: T, on_report: SenderLink<T::Report>) -> DialogStory {
 		match &self.edge {
 			None => panic!("No edge in StoryScope"),
-			Some(ctx) => ctx.start_dialog::<T>(spark, on_report),
+			Some(ctx) => ctx.start_dialog::<T>(key, spark, on_report),
 		}
 	}
 
@@ -73,4 +75,14 @@ impl<S, A: Send, R: Send + 'static> Flow<S, A, R> for StoryScope<S, A, R> {
 	fn report(&self, report: R) {
 		self.on_report.send(report)
 	}
+
+	fn every(&self, period: Duration, action: A) -> TimerId where A: Clone + 'static {
+		self.timers.every(period, &self.link, action)
+	}
+
+	fn after(&self, delay: Duration, action: A) -> TimerId where A: 'static {
+		self.timers.after(delay, &self.link, action)
+	}
+
+	fn cancel_timer(&self, id: TimerId) { self.timers.cancel(id) }
 }
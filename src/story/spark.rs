@@ -1,53 +1,144 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::mpsc::{channel, Sender};
 use std::thread;
+use std::time::Duration;
 
-use crate::{ArcYard, SenderLink, Story};
+use crate::{ArcYard, Command, KeyEvent, Persist, Persistence, Recorder, SenderLink, Story, StoryTimers, TimerId, Timers};
 use crate::app::Edge;
 use crate::dialog_story::DialogStory;
+use crate::keymap::KeyScope;
+use crate::story::persist::{Encode, encoder, Snapshots};
+use crate::story::record::Recording;
 use crate::story::scope::StoryScope;
 
 pub fn spark<S: Spark>(spark: S, edge: Option<Edge>, report_link: Option<SenderLink<S::Report>>) -> Story<S>
 	where S: Sized + Send + 'static
+{
+	spark_with_timers(spark, edge, report_link, StoryTimers::new(Timers::shared()))
+}
+
+/// Starts a story whose `after` and `every` subscriptions run on the given timers.
+pub fn spark_with_timers<S: Spark>(spark: S, edge: Option<Edge>, report_link: Option<SenderLink<S::Report>>, timers: StoryTimers) -> Story<S>
+	where S: Sized + Send + 'static
+{
+	let persistence = edge.as_ref().and_then(|edge| edge.persistence().cloned());
+	let recorder = edge.as_ref().and_then(|edge| edge.recorder().cloned());
+	spark_with(spark, edge, report_link, timers, persistence, recorder)
+}
+
+pub(crate) fn spark_with<S: Spark>(spark: S, edge: Option<Edge>, report_link: Option<SenderLink<S::Report>>, timers: StoryTimers, persistence: Option<Persistence>, recorder: Option<Recorder>) -> Story<S>
+	where S: Sized + Send + 'static
 {
 	let (tx, rx) = channel::<Msg<S>>();
+	let save_link = SenderLink::new(tx.clone());
 	let story = Story { tx };
 	let action_link = story.link().clone();
 	thread::Builder::new().name("story".to_string()).spawn(move || {
-		let state = spark.create(&Create {
-			action_link: action_link.clone(),
-			edge: edge.clone(),
-			report_link: report_link.clone(),
-		});
-		let on_report = match &report_link {
-			None => SenderLink::ignore(),
-			Some(link) => link.clone(),
-		};
-		let mut ctx = StoryScope::new(state, action_link, edge, on_report);
-		for msg in rx {
-			match msg {
-				Msg::Subscribe(subscriber_id, watcher) => ctx.add_watcher(subscriber_id, watcher),
-				Msg::Update(action) => match S::flow(&spark, action, &ctx) {
-					AfterFlow::ReviseQuietly(next) => ctx.set_vision(next, false),
-					AfterFlow::Revise(next) => ctx.set_vision(next, true),
-					AfterFlow::Ignore => (),
-					AfterFlow::Report(report) => ctx.report(report),
-					AfterFlow::Close(report) => {
-						if let Some(report) = report { ctx.report(report) }
-						ctx.end_prequel();
+		let failure_edge = edge.clone();
+		let run = panic::catch_unwind(AssertUnwindSafe(|| {
+			let create = Create {
+				action_link: action_link.clone(),
+				edge: edge.clone(),
+				report_link: report_link.clone(),
+				timers: timers.clone(),
+				persistence: persistence.clone(),
+				recorder,
+				encode: RefCell::new(None),
+				recording: RefCell::new(None),
+			};
+			let state = spark.create(&create);
+			let recording = create.recording.into_inner();
+			let mut snapshots = match (persistence, create.encode.into_inner()) {
+				(Some(persistence), Some(encode)) => Some(Snapshots::new(persistence, encode, timers.timers().clone())),
+				_ => None,
+			};
+			let on_report = match &report_link {
+				None => SenderLink::ignore(),
+				Some(link) => link.clone(),
+			};
+			let mut ctx = StoryScope::new(state, action_link.clone(), edge, on_report, timers.clone());
+			for msg in rx.iter() {
+				match msg {
+					Msg::Subscribe(subscriber_id, watcher) => ctx.add_watcher(subscriber_id, watcher),
+					Msg::Save => if let Some(snapshots) = &mut snapshots { snapshots.save(ctx.state()) },
+					Msg::Flush => if let Some(snapshots) = &mut snapshots { snapshots.flush(ctx.state()) },
+					Msg::Update(action) => {
+						let recorded = recording.as_ref().map(|recording| recording.encode(&action));
+						let after = S::flow(&spark, action, &ctx);
+						if let (Some(recording), Some(recorded)) = (&recording, recorded) {
+							recording.push(recorded, after.kind());
+						}
+						let revised = match after {
+							AfterFlow::ReviseQuietly(next) => {
+								ctx.set_vision(next, false);
+								true
+							}
+							AfterFlow::Revise(next) => {
+								ctx.set_vision(next, true);
+								true
+							}
+							AfterFlow::ReviseAnd(next, commands) => {
+								ctx.set_vision(next, true);
+								Command::batch(commands).run(&action_link, &timers);
+								true
+							}
+							AfterFlow::Run(commands) => {
+								Command::batch(commands).run(&action_link, &timers);
+								false
+							}
+							AfterFlow::Ignore => false,
+							AfterFlow::Report(report) => {
+								ctx.report(report);
+								false
+							}
+							AfterFlow::Close(report) => {
+								if let Some(report) = report { ctx.report(report) }
+								timers.cancel_all();
+								if let Some(snapshots) = &mut snapshots { snapshots.flush(ctx.state()) }
+								ctx.end_prequel();
+								false
+							}
+						};
+						if let (true, Some(snapshots)) = (revised, &mut snapshots) {
+							snapshots.revised(&save_link, Msg::Save);
+						}
 					}
-				},
+				}
+			}
+			if let Some(snapshots) = &mut snapshots { snapshots.flush(ctx.state()) }
+		}));
+		timers.cancel_all();
+		// Report before the message channel drops so the failure arrives ahead of the story's stop.
+		if let Err(payload) = run {
+			let message = panic_message(payload.as_ref());
+			match &failure_edge {
+				Some(edge) => edge.report_failure(&message),
+				None => error!("Story failed: {}", message),
 			}
 		}
+		drop(rx);
 	}).expect("spawn");
 	story
 }
 
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+	if let Some(message) = payload.downcast_ref::<&str>() {
+		message.to_string()
+	} else if let Some(message) = payload.downcast_ref::<String>() {
+		message.clone()
+	} else {
+		"unknown panic".to_string()
+	}
+}
+
 pub trait Spark {
 	//! Sparks specify the state of a story, the actions that change it, and
 	//! the reports it emits.
 
 	/// Specifies state that evolves with the story.
-	type State: Send + Clone;
+	type State: Send + Clone + 'static;
 	/// Specifies actions that move the story forward.
 	type Action: Send;
 	/// Specifies reports the story emit.
@@ -57,7 +148,7 @@ pub trait Spark {
 	fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State;
 
 	/// Produce a new state after an action moves the story forward.
-	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report>;
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action>;
 
 	/// Produce a rendering for a state of the story.
 	fn render(state: &Self::State, _action_link: &SenderLink<Self::Action>) -> Option<ArcYard>;
@@ -68,34 +159,98 @@ pub struct Create<Action: Send, Report: Send> {
 	action_link: SenderLink<Action>,
 	report_link: Option<SenderLink<Report>>,
 	edge: Option<Edge>,
+	timers: StoryTimers,
+	persistence: Option<Persistence>,
+	recorder: Option<Recorder>,
+	encode: RefCell<Option<Encode>>,
+	recording: RefCell<Option<Recording<Action>>>,
 }
 
 impl<Action: Send, Report: Send> Create<Action, Report> {
 	pub fn link(&self) -> &SenderLink<Action> { &self.action_link }
 	pub fn report_link(&self) -> &Option<SenderLink<Report>> { &self.report_link }
 	pub fn edge(&self) -> &Option<Edge> { &self.edge }
+	/// Opts the story into persistence and reads the state saved by an earlier run.
+	/// Does nothing unless the story verse was built with persistence. `T` must be
+	/// the story's state type.
+	pub fn restore<T: Persist + 'static>(&self) -> Option<T> {
+		let persistence = self.persistence.as_ref()?;
+		*self.encode.borrow_mut() = Some(encoder::<T>());
+		persistence.load()
+	}
+	/// Logs every action this story receives, with the kind of flow it produced.
+	/// Does nothing unless the story verse was built with a recorder.
+	pub fn record(&self) where Action: Persist {
+		if let Some(recorder) = &self.recorder {
+			*self.recording.borrow_mut() = Some(Recording::new(recorder));
+		}
+	}
+	/// A create context with no edge, persistence or live links, for replaying a story.
+	pub(crate) fn detached(timers: StoryTimers) -> Self where Action: 'static {
+		Create {
+			action_link: SenderLink::ignore(),
+			report_link: None,
+			edge: None,
+			timers,
+			persistence: None,
+			recorder: None,
+			encode: RefCell::new(None),
+			recording: RefCell::new(None),
+		}
+	}
+}
+
+impl<Action: Clone + Send + 'static, Report: Send> Create<Action, Report> {
+	/// Binds a key to an action of this story. Widgets with focus see the key first.
+	pub fn bind_key(&self, key: KeyEvent, scope: KeyScope, description: &str, action: Action) where Action: Sync {
+		if let Some(edge) = &self.edge {
+			edge.bind_key(key, scope, description, &self.action_link, action);
+		}
+	}
+	/// Sends the action to this story each time the period passes, until the story stops.
+	pub fn every(&self, period: Duration, action: Action) -> TimerId {
+		self.timers.every(period, &self.action_link, action)
+	}
+	/// Sends the action to this story once after the delay, unless the story stops first.
+	pub fn after(&self, delay: Duration, action: Action) -> TimerId {
+		self.timers.after(delay, &self.action_link, action)
+	}
+	pub fn cancel_timer(&self, id: TimerId) { self.timers.cancel(id) }
 }
 
 pub trait Flow<State, Action: Send, Report: Send> {
 	//! TODO: Move start_prequel and end_prequel into edge component.
 	fn state(&self) -> &State;
 	fn link(&self) -> &SenderLink<Action>;
-	fn start_prequel<S: Spark + Send + 'static>(&self, spark: S, on_report: SenderLink<S::Report>) -> DialogStory;
+	/// Starts a dialog whose state persists, and whose actions record, under `key` within this story's key.
+	fn start_prequel<S: Spark + Send + 'static>(&self, key: &str, spark: S, on_report: SenderLink<S::Report>) -> DialogStory;
 	fn end_prequel(&self);
 	fn redraw(&self);
 	fn report(&self, report: Report);
+	/// Sends the action to this story each time the period passes, until the story stops.
+	fn every(&self, period: Duration, action: Action) -> TimerId where Action: Clone + 'static;
+	/// Sends the action to this story once after the delay, unless the story stops first.
+	fn after(&self, delay: Duration, action: Action) -> TimerId where Action: 'static;
+	fn cancel_timer(&self, id: TimerId);
 }
 
 
-pub enum AfterFlow<State, Report> {
+pub enum AfterFlow<State, Report, Action> {
 	Ignore,
 	Report(Report),
 	Close(Option<Report>),
 	Revise(State),
 	ReviseQuietly(State),
+	/// Revises the state, then runs the commands.
+	ReviseAnd(State, Vec<Command<Action>>),
+	/// Runs the commands and leaves the state alone.
+	Run(Vec<Command<Action>>),
 }
 
 pub(crate) enum Msg<S: Spark> {
 	Subscribe(i32, Sender<S::State>),
 	Update(S::Action),
+	Save,
+	/// Saves now any revision still waiting out its quiet period.
+	Flush,
 }
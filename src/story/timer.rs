@@ -0,0 +1,240 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Link, SenderLink};
+
+/// Tells timers what time it is.
+pub trait Clock: Send + Sync {
+	fn now(&self) -> Instant;
+}
+
+/// Reads the time from the operating system.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant { Instant::now() }
+}
+
+/// A clock that only moves when told to. Lets tests step timers deterministically.
+#[derive(Debug)]
+pub struct ManualClock {
+	now: Mutex<Instant>,
+}
+
+impl ManualClock {
+	pub fn new() -> Self { ManualClock { now: Mutex::new(Instant::now()) } }
+	pub fn advance(&self, duration: Duration) {
+		*self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+	}
+}
+
+impl Default for ManualClock {
+	fn default() -> Self { Self::new() }
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> Instant { *self.now.lock().unwrap_or_else(|e| e.into_inner()) }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TimerId(u64);
+
+struct Entry {
+	deadline: Instant,
+	period: Option<Duration>,
+	fire: Box<dyn FnMut() -> bool + Send>,
+}
+
+#[derive(Default)]
+struct Schedule {
+	next_id: u64,
+	entries: HashMap<u64, Entry>,
+}
+
+impl Schedule {
+	fn next_due(&self, now: Instant) -> Option<u64> {
+		self.entries.iter()
+			.filter(|(_, entry)| entry.deadline <= now)
+			.min_by_key(|(id, entry)| (entry.deadline, **id))
+			.map(|(id, _)| *id)
+	}
+	fn next_deadline(&self) -> Option<Instant> {
+		self.entries.values().map(|entry| entry.deadline).min()
+	}
+}
+
+/// Schedules actions against a clock. `Timers::shared` runs on one background
+/// thread; timers made with `Timers::new` fire only when `fire_due` is called.
+#[derive(Clone)]
+pub struct Timers {
+	clock: Arc<dyn Clock>,
+	schedule: Arc<(Mutex<Schedule>, Condvar)>,
+}
+
+impl Timers {
+	pub fn new(clock: Arc<dyn Clock>) -> Self {
+		Timers { clock, schedule: Arc::new((Mutex::new(Schedule::default()), Condvar::new())) }
+	}
+
+	/// The system-clock timers shared by every story.
+	pub fn shared() -> Self {
+		static SHARED: OnceLock<Timers> = OnceLock::new();
+		SHARED.get_or_init(|| {
+			let timers = Timers::new(Arc::new(SystemClock));
+			let runner = timers.clone();
+			thread::Builder::new().name("Timers".to_string()).spawn(move || loop {
+				runner.fire_due();
+				let schedule = runner.lock();
+				match schedule.next_deadline() {
+					None => { runner.schedule.1.wait(schedule).ok(); }
+					Some(deadline) => {
+						let wait = deadline.saturating_duration_since(runner.clock.now());
+						runner.schedule.1.wait_timeout(schedule, wait).ok();
+					}
+				}
+			}).expect("spawn");
+			timers
+		}).clone()
+	}
+
+	pub fn now(&self) -> Instant { self.clock.now() }
+
+	/// Sends the action once after the delay.
+	pub fn after<A: Send + 'static>(&self, delay: Duration, link: &SenderLink<A>, action: A) -> TimerId {
+		let link = link.clone();
+		let mut action = Some(action);
+		self.add(delay, None, Box::new(move || match action.take() {
+			Some(action) => link.try_send(action).is_ok(),
+			None => false,
+		}))
+	}
+
+	/// Sends the action each time the period passes. Panics if the period is zero.
+	pub fn every<A: Clone + Send + 'static>(&self, period: Duration, link: &SenderLink<A>, action: A) -> TimerId {
+		assert!(!period.is_zero(), "timer period must be non-zero");
+		let link = link.clone();
+		self.add(period, Some(period), Box::new(move || link.try_send(action.clone()).is_ok()))
+	}
+
+	pub fn cancel(&self, id: TimerId) {
+		self.lock().entries.remove(&id.0);
+	}
+
+	/// Fires every timer whose deadline has passed. Periodic timers that fell
+	/// several periods behind fire once per missed period.
+	pub fn fire_due(&self) {
+		let now = self.clock.now();
+		let mut schedule = self.lock();
+		while let Some(id) = schedule.next_due(now) {
+			let mut entry = schedule.entries.remove(&id).expect("due entry");
+			let delivered = (entry.fire)();
+			if let (true, Some(period)) = (delivered, entry.period) {
+				entry.deadline += period;
+				schedule.entries.insert(id, entry);
+			}
+		}
+	}
+
+	fn add(&self, delay: Duration, period: Option<Duration>, fire: Box<dyn FnMut() -> bool + Send>) -> TimerId {
+		let deadline = self.clock.now() + delay;
+		let mut schedule = self.lock();
+		let id = schedule.next_id;
+		schedule.next_id += 1;
+		schedule.entries.insert(id, Entry { deadline, period, fire });
+		self.schedule.1.notify_all();
+		TimerId(id)
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Schedule> {
+		self.schedule.0.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+impl fmt::Debug for Timers {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Timers")
+	}
+}
+
+/// The timers belonging to one story. Cancelling the group cancels them all.
+#[derive(Clone)]
+pub struct StoryTimers {
+	timers: Timers,
+	ids: Arc<Mutex<HashSet<TimerId>>>,
+}
+
+impl StoryTimers {
+	pub fn new(timers: Timers) -> Self {
+		StoryTimers { timers, ids: Arc::new(Mutex::new(HashSet::new())) }
+	}
+	pub fn timers(&self) -> &Timers { &self.timers }
+	pub fn after<A: Send + 'static>(&self, delay: Duration, link: &SenderLink<A>, action: A) -> TimerId {
+		self.keep(self.timers.after(delay, link, action))
+	}
+	pub fn every<A: Clone + Send + 'static>(&self, period: Duration, link: &SenderLink<A>, action: A) -> TimerId {
+		self.keep(self.timers.every(period, link, action))
+	}
+	pub fn cancel(&self, id: TimerId) {
+		self.timers.cancel(id);
+		self.ids().remove(&id);
+	}
+	pub fn cancel_all(&self) {
+		for id in self.ids().drain() {
+			self.timers.cancel(id);
+		}
+	}
+	fn keep(&self, id: TimerId) -> TimerId {
+		self.ids().insert(id);
+		id
+	}
+	fn ids(&self) -> MutexGuard<'_, HashSet<TimerId>> {
+		self.ids.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::mpsc::channel;
+	use std::time::Duration;
+
+	use crate::{ManualClock, SenderLink, StoryTimers, Timers};
+
+	#[test]
+	fn timers_follow_the_clock() {
+		let clock = Arc::new(ManualClock::new());
+		let timers = Timers::new(clock.clone());
+		let (tx, rx) = channel();
+		let link = SenderLink::new(tx);
+		timers.every(Duration::from_secs(2), &link, "tick");
+		timers.after(Duration::from_secs(3), &link, "once");
+
+		clock.advance(Duration::from_secs(1));
+		timers.fire_due();
+		assert_eq!(rx.try_iter().collect::<Vec<_>>(), Vec::<&str>::new());
+
+		clock.advance(Duration::from_secs(5));
+		timers.fire_due();
+		assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec!["tick", "once", "tick", "tick"]);
+	}
+
+	#[test]
+	fn cancelled_story_timers_stop_firing() {
+		let clock = Arc::new(ManualClock::new());
+		let timers = Timers::new(clock.clone());
+		let story_timers = StoryTimers::new(timers.clone());
+		let (tx, rx) = channel();
+		let link = SenderLink::new(tx);
+		story_timers.every(Duration::from_secs(1), &link, 1);
+		story_timers.after(Duration::from_secs(1), &link, 2);
+		story_timers.cancel_all();
+
+		clock.advance(Duration::from_secs(10));
+		timers.fire_due();
+		assert_eq!(rx.try_recv().ok(), None);
+	}
+}
@@ -1,12 +1,14 @@
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::ops::Index;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
 
 use rand::random;
 
-use crate::{ArcYard, Link, SenderLink, Spark, story_verse};
+use crate::{ArcYard, Link, LinkClosed, Persistence, Recorder, SenderLink, Spark, story_verse, Timers};
+use crate::keymap::Keymap;
 use crate::story_id::StoryId;
 use crate::story_stack::{StoryStack, StoryStackAction};
 use crate::story_verse::story_box::StoryBoxAction;
@@ -20,6 +22,21 @@ pub mod dialog_story;
 pub(crate) mod story_box;
 pub mod story_stack;
 
+/// A story whose thread panicked.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StoryFailure {
+	pub story_id: StoryId,
+	pub message: String,
+}
+
+impl fmt::Display for StoryFailure {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "story {:?} failed: {}", self.story_id, self.message)
+	}
+}
+
+impl Error for StoryFailure {}
+
 #[derive(Debug, Clone)]
 pub struct StoryVerseStats {
 	pub story_count: usize,
@@ -29,33 +46,58 @@ pub struct StoryVerseStats {
 pub struct StoryVerse {
 	story_verse_link: Sender<StoryVerseAction>,
 	root_story_id: StoryId,
+	keymap: Keymap,
+	timers: Timers,
 }
 
 impl StoryVerse {
 	pub fn build<S: Spark>(spark: S, story_id: StoryId) -> (StoryVerse, SenderLink<S::Action>) where S: Send + 'static {
-		let (story_verse_link, root_story_id) = story_verse::connect();
+		Self::build_with_persistence(spark, story_id, None)
+	}
+
+	/// Builds a story verse whose stories can restore state saved by an earlier run.
+	pub fn build_with_persistence<S: Spark>(spark: S, story_id: StoryId, persistence: Option<Persistence>) -> (StoryVerse, SenderLink<S::Action>) where S: Send + 'static {
+		Self::build_with(spark, story_id, persistence, None, Timers::shared())
+	}
+
+	/// Builds a story verse whose stories schedule `after` and `every` on the given timers.
+	pub fn build_with_timers<S: Spark>(spark: S, story_id: StoryId, timers: Timers) -> (StoryVerse, SenderLink<S::Action>) where S: Send + 'static {
+		Self::build_with(spark, story_id, None, None, timers)
+	}
+
+	pub(crate) fn build_with<S: Spark>(spark: S, story_id: StoryId, persistence: Option<Persistence>, recorder: Option<Recorder>, timers: Timers) -> (StoryVerse, SenderLink<S::Action>) where S: Send + 'static {
+		let keymap = Keymap::new();
+		let (story_verse_link, root_story_id) = story_verse::connect(keymap.clone(), timers.clone());
 		let main_story_id = story_id;
-		let (main_story_box, main_sender) = story_box::connect(spark, None, main_story_id, story_verse_link.clone());
-		story_verse_link.send(StoryVerseAction::AddStackStoryBox(main_story_box, main_story_id)).expect("Add main story box to stack");
-		(StoryVerse { story_verse_link, root_story_id }, main_sender)
+		let (main_story_box, main_sender) = story_box::connect(spark, None, main_story_id, main_story_id, story_verse_link.clone(), keymap.clone(), persistence, recorder, timers.clone());
+		story_verse_link.send(StoryVerseAction::AddStackStoryBox(main_story_box, main_story_id)).ok();
+		(StoryVerse { story_verse_link, root_story_id, keymap, timers }, main_sender)
 	}
 
-	pub fn read_stats(&self) -> StoryVerseStats {
+	pub fn read_stats(&self) -> Result<StoryVerseStats, LinkClosed> {
 		let (stats_link, stats_read) = channel::<StoryVerseStats>();
-		self.story_verse_link.send(GetStats(stats_link)).expect("send stats request");
-		stats_read.recv().expect("receive stats")
+		self.story_verse_link.send(GetStats(stats_link)).map_err(|_| LinkClosed)?;
+		stats_read.recv().map_err(|_| LinkClosed)
+	}
+	pub fn start_yards(&self) -> Result<Receiver<(StoryId, Option<ArcYard>)>, LinkClosed> {
+		let (feed_link, feed_source) = channel();
+		self.story_verse_link.send(StartYardsFeed(feed_link)).map_err(|_| LinkClosed)?;
+		Ok(feed_source)
 	}
-	pub fn start_yards(&self) -> Receiver<(StoryId, Option<ArcYard>)> {
+	/// Streams the failures of stories that panic from now on.
+	pub fn start_failures(&self) -> Receiver<StoryFailure> {
 		let (feed_link, feed_source) = channel();
-		self.story_verse_link.send(StartYardsFeed(feed_link)).expect("send feed request");
+		self.story_verse_link.send(StoryVerseAction::StartFailureFeed(feed_link)).ok();
 		feed_source
 	}
 	pub fn root_story_id(&self) -> StoryId { self.root_story_id }
+	pub fn keymap(&self) -> &Keymap { &self.keymap }
+	pub fn timers(&self) -> &Timers { &self.timers }
 }
 
 fn notify_stack_when_story_stops(story_verse_link: &Sender<StoryVerseAction>, story_stack_link: &SenderLink<StoryStackAction>) {
 	let (send_story_stopped, receive_story_stopped) = channel();
-	story_verse_link.send(StoryVerseAction::StartStoryStopFeed(send_story_stopped)).expect("Send StartStoryStopFeed");
+	story_verse_link.send(StoryVerseAction::StartStoryStopFeed(send_story_stopped)).ok();
 	let root_sender = story_stack_link.clone();
 	thread::spawn(move || {
 		for stopped_story_id in receive_story_stopped {
@@ -72,14 +114,16 @@ pub enum StoryVerseAction {
 	StoryBoxStopped(StoryId),
 	StartStoryStopFeed(Sender<StoryId>),
 	StoryBoxUpdate(StoryId, Option<ArcYard>),
+	StoryFailed(StoryFailure),
+	StartFailureFeed(Sender<StoryFailure>),
 }
 
-fn connect() -> (Sender<StoryVerseAction>, StoryId) {
+fn connect(keymap: Keymap, timers: Timers) -> (Sender<StoryVerseAction>, StoryId) {
 	let (story_verse_link, action_source) = channel();
 
 	let stack_story_id = StoryId::random();
-	let (stack_story_box, stack_link) = story_box::connect(StoryStack {}, None, stack_story_id, story_verse_link.clone());
-	story_verse_link.send(StoryVerseAction::AddStoryBox(stack_story_box, stack_story_id)).expect("Add root story box");
+	let (stack_story_box, stack_link) = story_box::connect(StoryStack {}, None, stack_story_id, stack_story_id, story_verse_link.clone(), keymap.clone(), None, None, timers);
+	story_verse_link.send(StoryVerseAction::AddStoryBox(stack_story_box, stack_story_id)).ok();
 	notify_stack_when_story_stops(&story_verse_link, &stack_link);
 
 	let own_link = story_verse_link.clone();
@@ -88,12 +132,13 @@ fn connect() -> (Sender<StoryVerseAction>, StoryId) {
 		let mut story_box_links: HashMap<StoryId, Sender<StoryBoxAction>> = HashMap::new();
 		let mut yard_feed_links: HashMap<u64, Sender<(StoryId, Option<ArcYard>)>> = HashMap::new();
 		let mut story_stop_feed_links: HashMap<u64, Sender<StoryId>> = HashMap::new();
+		let mut failure_feed_links: HashMap<u64, Sender<StoryFailure>> = HashMap::new();
 		for action in action_source {
 			match action {
 				StoryVerseAction::GetStats(stats_link) => {
 					let count_excluding_root = story_box_links.len() - 1;
 					let stats = StoryVerseStats { story_count: count_excluding_root };
-					stats_link.send(stats).expect("send stats response");
+					stats_link.send(stats).ok();
 				}
 				StoryVerseAction::StartYardsFeed(yards_link) => {
 					if push_yards_to_feed(&latest_yards, &yards_link).is_ok() {
@@ -105,12 +150,14 @@ fn connect() -> (Sender<StoryVerseAction>, StoryId) {
 					start_story_box_feed(&story_box, own_link.clone());
 				}
 				StoryVerseAction::AddStackStoryBox(story_box, story_id) => {
-					own_link.send(StoryVerseAction::AddStoryBox(story_box, story_id)).expect("Add story box");
+					own_link.send(StoryVerseAction::AddStoryBox(story_box, story_id)).ok();
+					keymap.push_dialog(story_id);
 					stack_link.send(StoryStackAction::PushStory(story_id));
 				}
 				StoryVerseAction::StoryBoxStopped(story_id) => {
 					info!("STORY VERSE STORY BOX STOPPED: {:?}", story_id);
 					story_box_links.remove(&story_id);
+					keymap.unbind_story(story_id);
 					latest_yards.remove(&story_id);
 					push_yard_to_feeds(story_id, None, &mut yard_feed_links);
 					push_stop_to_feeds(story_id, &mut story_stop_feed_links);
@@ -122,6 +169,13 @@ fn connect() -> (Sender<StoryVerseAction>, StoryId) {
 					latest_yards.insert(story_id, story_yard.clone());
 					push_yard_to_feeds(story_id, story_yard, &mut yard_feed_links)
 				}
+				StoryVerseAction::StoryFailed(failure) => {
+					error!("STORY VERSE {}", failure);
+					push_value_to_feeds(failure, &mut failure_feed_links);
+				}
+				StoryVerseAction::StartFailureFeed(failure_link) => {
+					failure_feed_links.insert(random(), failure_link);
+				}
 			}
 		}
 	});
@@ -160,10 +214,14 @@ fn push_value_to_feeds<T: Clone>(value: T, feed_links: &mut HashMap<u64, Sender<
 
 fn start_story_box_feed(story_box_link: &Sender<StoryBoxAction>, story_verse_link: Sender<StoryVerseAction>) {
 	let (feed_link, feed_source) = channel();
-	story_box_link.send(StoryBoxAction::StartFeed(feed_link)).expect("send start-feed to story-box");
+	if story_box_link.send(StoryBoxAction::StartFeed(feed_link)).is_err() {
+		return;
+	}
 	thread::spawn(move || {
 		for (story_id, story_yard) in feed_source {
-			story_verse_link.send(StoryVerseAction::StoryBoxUpdate(story_id, story_yard)).expect("send update-story-yard to story-verse");
+			if story_verse_link.send(StoryVerseAction::StoryBoxUpdate(story_id, story_yard)).is_err() {
+				break;
+			}
 		}
 	});
 }
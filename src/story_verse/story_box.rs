@@ -4,8 +4,9 @@ use std::thread;
 
 use rand::random;
 
-use crate::{ArcYard, Sendable, SenderLink, Spark, story, Story, StoryVerseAction, yard};
+use crate::{ArcYard, Persistence, Recorder, Sendable, SenderLink, Spark, story, Story, StoryTimers, StoryVerseAction, Timers, yard};
 use crate::app::Edge;
+use crate::keymap::Keymap;
 use crate::story_id::StoryId;
 use crate::yard::YardPublisher;
 
@@ -23,11 +24,20 @@ pub fn connect<S: Spark>(
 	spark: S,
 	reports_link: Option<SenderLink<S::Report>>,
 	story_id: StoryId,
+	dialog_id: StoryId,
 	story_verse_link: Sender<StoryVerseAction>,
+	keymap: Keymap,
+	persistence: Option<Persistence>,
+	recorder: Option<Recorder>,
+	timers: Timers,
 ) -> (Sender<StoryBoxAction>, SenderLink<S::Action>) where S: Send + 'static {
 	let (story_box_link, actions) = channel::<StoryBoxAction>();
 	let own_actions = story_box_link.clone();
 	let own_verse_actions = story_verse_link.clone();
+	let timers = StoryTimers::new(timers);
+	let own_timers = timers.clone();
+	let story = connect_story(spark, reports_link, story_id, dialog_id, story_box_link.clone(), story_verse_link, keymap, persistence, recorder, timers);
+	let own_story = story.clone();
 	thread::spawn(move || {
 		let mut latest_yard: Option<ArcYard> = Some(yard::empty());
 		let mut active_feed_links: HashMap<u64, Sender<(StoryId, Option<ArcYard>)>> = HashMap::new();
@@ -44,7 +54,9 @@ pub fn connect<S: Spark>(
 						latest_yard = None;
 						push_yard(story_id, &latest_yard, &mut active_feed_links);
 					}
-					own_verse_actions.send(StoryVerseAction::StoryBoxStopped(story_id)).expect("Send StoryBoxStopped");
+					own_timers.cancel_all();
+					own_story.flush();
+					own_verse_actions.send(StoryVerseAction::StoryBoxStopped(story_id)).ok();
 					break;
 				}
 				StoryBoxAction::StartFeed(feed_link) => {
@@ -54,13 +66,12 @@ pub fn connect<S: Spark>(
 				}
 				StoryBoxAction::EndDialog => {
 					info!("STORY BOX END DIALOG: {:?}", story_id);
-					own_actions.send(StoryBoxAction::SetStopped).expect("set stopped");
+					own_actions.send(StoryBoxAction::SetStopped).ok();
 				}
 			}
 		}
 		info!("STORY BOX THREAD ENDED: {:?}", story_id);
 	});
-	let story = connect_story(spark, reports_link, story_id, story_box_link.clone(), story_verse_link);
 	(story_box_link, story.link())
 }
 
@@ -81,12 +92,17 @@ fn connect_story<S: Spark>(
 	spark: S,
 	reports_link: Option<SenderLink<S::Report>>,
 	story_id: StoryId,
+	dialog_id: StoryId,
 	story_box_link: Sender<StoryBoxAction>,
 	story_verse_link: Sender<StoryVerseAction>,
+	keymap: Keymap,
+	persistence: Option<Persistence>,
+	recorder: Option<Recorder>,
+	timers: StoryTimers,
 ) -> Story<S> where S: Send + 'static {
 	let end_dialog_trigger = StoryBoxAction::EndDialog.into_trigger(&story_box_link);
-	let edge = Edge::new(story_id, end_dialog_trigger, story_verse_link);
-	let story = story::spark(spark, Some(edge), reports_link);
+	let edge = Edge::new(story_id, dialog_id, end_dialog_trigger, story_verse_link, keymap, persistence, recorder, timers.timers().clone());
+	let story = story::spark_with_timers(spark, Some(edge), reports_link, timers);
 	match story.subscribe() {
 		Ok(yard_source) => {
 			thread::spawn(move || {
@@ -44,7 +44,7 @@ impl Spark for StoryStack {
 		StoryStackModel { story_ids: Vec::new(), yard_ids: Vec::new() }
 	}
 
-	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> {
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
 		match action {
 			StoryStackAction::PushStory(story_id) => {
 				let mut model = ctx.state().clone();
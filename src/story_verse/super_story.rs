@@ -3,5 +3,6 @@ use crate::sub_story::SubStory;
 
 pub trait SuperStory {
 	// TODO Move registration of reports_link into SubStory.
-	fn sub_story<S: Spark + Send + 'static>(&self, spark: S, reports_link: Option<SenderLink<S::Report>>) -> SubStory;
+	/// Starts a sub-story whose state persists, and whose actions record, under `key` within this story's key.
+	fn sub_story<S: Spark + Send + 'static>(&self, key: &str, spark: S, reports_link: Option<SenderLink<S::Report>>) -> SubStory;
 }
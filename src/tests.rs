@@ -1,13 +1,16 @@
-use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
 use std::time::Duration;
 
 use rand::random;
 
-use crate::{AfterFlow, ArcYard, Cling, Create, FillColor, FillGrade, Flow, Pack, Sendable, SenderLink, Spark, StoryVerse, StrokeColor, yard};
+use crate::{AfterFlow, ArcYard, Cling, Create, FillColor, FillGrade, Flow, Link, ManualClock, Pack, Sendable, SenderLink, Spark, StoryVerse, StoryVerseAction, StrokeColor, Timers, yard};
 
 use crate::pod::Pod;
 use crate::pod_verse::PodVerse;
+use crate::keymap::Keymap;
+use crate::story_box::{self, StoryBoxAction};
 use crate::story_id::StoryId;
 use crate::super_story::SuperStory;
 
@@ -18,6 +21,27 @@ enum TestAction {
 
 impl Sendable for TestAction {}
 
+/// Adds each action to its count. A zero is ignored. Restores the saved count
+/// and records its actions when the story has persistence or a recorder.
+pub(crate) struct Tally;
+
+impl Spark for Tally {
+	type State = i32;
+	type Action = i32;
+	type Report = ();
+
+	fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State {
+		ctx.record();
+		ctx.restore().unwrap_or(0)
+	}
+
+	fn flow(&self, action: Self::Action, ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
+		if action == 0 { AfterFlow::Ignore } else { AfterFlow::Revise(ctx.state() + action) }
+	}
+
+	fn render(_state: &Self::State, _action_link: &SenderLink<Self::Action>) -> Option<ArcYard> { None }
+}
+
 #[test]
 fn sub_story_rendering() {
 	struct Main {}
@@ -27,12 +51,12 @@ fn sub_story_rendering() {
 		type Report = ();
 		fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State {
 			if let Some(edge) = ctx.edge() {
-				edge.sub_story(PlainPrimary {}, None).story_id
+				edge.sub_story("primary", PlainPrimary {}, None).story_id
 			} else {
 				StoryId::random()
 			}
 		}
-		fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> { AfterFlow::Ignore }
+		fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> { AfterFlow::Ignore }
 		fn render(state: &Self::State, _link: &SenderLink<Self::Action>) -> Option<ArcYard> {
 			let yard = yard::empty().pack_right(1, yard::story(random(), state.clone()));
 			Some(yard)
@@ -44,7 +68,7 @@ fn sub_story_rendering() {
 		type Action = ();
 		type Report = ();
 		fn create(&self, _ctx: &Create<Self::Action, Self::Report>) -> Self::State { () }
-		fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> { AfterFlow::Ignore }
+		fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> { AfterFlow::Ignore }
 		fn render(_state: &Self::State, _link: &SenderLink<Self::Action>) -> Option<ArcYard> {
 			Some(yard::fill(FillColor::Primary, FillGrade::Plain))
 		}
@@ -71,7 +95,7 @@ fn simple_sub_story() {
 	let main_id = StoryId::new(0);
 	let (story_verse, _main_link) = StoryVerse::build(TwoWords { left: "Hello".into(), right: "World".into() }, main_id);
 	thread::sleep(Duration::from_millis(1));
-	assert_eq!(story_verse.read_stats().story_count, 3);
+	assert_eq!(story_verse.read_stats().unwrap().story_count, 3);
 	let pod_verse = PodVerse::build(&story_verse);
 	thread::sleep(Duration::from_millis(1));
 	let mut main_pod = pod_verse.to_main_pod(SenderLink::ignore());
@@ -85,7 +109,7 @@ fn simple_sub_story() {
 		match action {
 			TestAction::Refresh => {
 				count += 1;
-				if pod_verse.read_pod_count() >= 3 {
+				if pod_verse.read_pod_count().unwrap() >= 3 {
 					success = true;
 					break;
 				}
@@ -106,7 +130,7 @@ impl Spark for Word {
 	type Action = ();
 	type Report = ();
 	fn create(&self, _ctx: &Create<Self::Action, Self::Report>) -> Self::State { self.chars.to_string() }
-	fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> { AfterFlow::Ignore }
+	fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> { AfterFlow::Ignore }
 	fn render(state: &Self::State, _link: &SenderLink<Self::Action>) -> Option<ArcYard> {
 		Some(yard::label(state.clone(), StrokeColor::BodyOnBackground, Cling::Left))
 	}
@@ -124,14 +148,14 @@ impl Spark for TwoWords {
 
 	fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State {
 		if let Some(edge) = ctx.edge() {
-			(edge.sub_story(Word { chars: self.left.clone() }, None).story_id,
-			 edge.sub_story(Word { chars: self.right.clone() }, None).story_id)
+			(edge.sub_story("left", Word { chars: self.left.clone() }, None).story_id,
+			 edge.sub_story("right", Word { chars: self.right.clone() }, None).story_id)
 		} else {
 			(StoryId::random(), StoryId::random())
 		}
 	}
 
-	fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report> { AfterFlow::Ignore }
+	fn flow(&self, _action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> { AfterFlow::Ignore }
 	fn render(state: &Self::State, _link: &SenderLink<Self::Action>) -> Option<ArcYard> {
 		let yard = yard::fill(FillColor::Background, FillGrade::Plain)
 			.pack_right(1, yard::story(random(), state.1))
@@ -139,3 +163,82 @@ impl Spark for TwoWords {
 		Some(yard)
 	}
 }
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum TickAction {
+	Tick,
+	Stop,
+	Probe,
+}
+
+/// Ticks every second and echoes what it receives.
+struct Ticker {
+	echo: Sender<TickAction>,
+}
+
+impl Spark for Ticker {
+	type State = ();
+	type Action = TickAction;
+	type Report = ();
+	fn create(&self, ctx: &Create<Self::Action, Self::Report>) -> Self::State {
+		ctx.every(Duration::from_secs(1), TickAction::Tick);
+	}
+	fn flow(&self, action: Self::Action, _ctx: &impl Flow<Self::State, Self::Action, Self::Report>) -> AfterFlow<Self::State, Self::Report, Self::Action> {
+		self.echo.send(action).ok();
+		if action == TickAction::Stop { AfterFlow::Close(None) } else { AfterFlow::Ignore }
+	}
+	fn render(_state: &Self::State, _link: &SenderLink<Self::Action>) -> Option<ArcYard> { None }
+}
+
+/// Advances the clock until the ticker's first tick arrives.
+fn await_tick(clock: &ManualClock, timers: &Timers, echoes: &Receiver<TickAction>) {
+	for _ in 0..100 {
+		clock.advance(Duration::from_secs(1));
+		timers.fire_due();
+		if echoes.recv_timeout(Duration::from_millis(20)) == Ok(TickAction::Tick) {
+			return;
+		}
+	}
+	panic!("ticker never ticked");
+}
+
+fn assert_no_ticks(clock: &ManualClock, timers: &Timers, echoes: &Receiver<TickAction>) {
+	clock.advance(Duration::from_secs(5));
+	timers.fire_due();
+	thread::sleep(Duration::from_millis(20));
+	assert_eq!(echoes.try_iter().filter(|it| *it == TickAction::Tick).count(), 0);
+}
+
+#[test]
+fn every_stops_after_close() {
+	let clock = Arc::new(ManualClock::new());
+	let timers = Timers::new(clock.clone());
+	let (echo, echoes) = channel();
+	let (_story_verse, link) = StoryVerse::build_with_timers(Ticker { echo }, StoryId::new(0), timers.clone());
+	await_tick(&clock, &timers, &echoes);
+	link.send(TickAction::Stop);
+	link.send(TickAction::Probe);
+	// The probe follows the close, so the story's timers are cancelled once it echoes.
+	while echoes.recv_timeout(Duration::from_secs(2)).expect("probe") != TickAction::Probe {}
+	assert_no_ticks(&clock, &timers, &echoes);
+}
+
+#[test]
+fn every_stops_after_set_stopped() {
+	let clock = Arc::new(ManualClock::new());
+	let timers = Timers::new(clock.clone());
+	let (echo, echoes) = channel();
+	let (verse_link, verse_actions) = channel();
+	let story_id = StoryId::new(0);
+	let (story_box_link, _link) = story_box::connect(Ticker { echo }, None, story_id, story_id, verse_link, Keymap::new(), None, None, timers.clone());
+	await_tick(&clock, &timers, &echoes);
+	story_box_link.send(StoryBoxAction::SetStopped).unwrap();
+	// The box reports the stop after cancelling the story's timers.
+	loop {
+		match verse_actions.recv_timeout(Duration::from_secs(2)).expect("stopped") {
+			StoryVerseAction::StoryBoxStopped(id) if id == story_id => break,
+			_ => {}
+		}
+	}
+	assert_no_ticks(&clock, &timers, &echoes);
+}
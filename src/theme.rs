@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{FillColor, FillGrade, StrokeColor};
+use crate::palette::{DARK_LEVELS, Status};
+
+pub type Rgb = [u8; 3];
+
+const FILLS: [(&str, FillColor); 7] = [
+	("background", FillColor::Background),
+	("primary", FillColor::Primary),
+	("side", FillColor::Side),
+	("error", FillColor::Error),
+	("warning", FillColor::Warning),
+	("success", FillColor::Success),
+	("info", FillColor::Info),
+];
+
+const GRADES: [(&str, FillGrade); 4] = [
+	("plain", FillGrade::Plain),
+	("select", FillGrade::Select),
+	("focus", FillGrade::Focus),
+	("press", FillGrade::Press),
+];
+
+const STROKES: [(&str, StrokeColor); 22] = [
+	("comment_on_background", StrokeColor::CommentOnBackground),
+	("comment_on_side", StrokeColor::CommentOnSide),
+	("body_on_background", StrokeColor::BodyOnBackground),
+	("body_on_side", StrokeColor::BodyOnSide),
+	("enabled_on_background", StrokeColor::EnabledOnBackground),
+	("enabled_on_primary", StrokeColor::EnabledOnPrimary),
+	("body_on_primary", StrokeColor::BodyOnPrimary),
+	("comment_on_primary", StrokeColor::CommentOnPrimary),
+	("error_on_background", StrokeColor::ErrorOnBackground),
+	("error_on_side", StrokeColor::ErrorOnSide),
+	("error_on_primary", StrokeColor::ErrorOnPrimary),
+	("warning_on_background", StrokeColor::WarningOnBackground),
+	("warning_on_side", StrokeColor::WarningOnSide),
+	("warning_on_primary", StrokeColor::WarningOnPrimary),
+	("success_on_background", StrokeColor::SuccessOnBackground),
+	("success_on_side", StrokeColor::SuccessOnSide),
+	("success_on_primary", StrokeColor::SuccessOnPrimary),
+	("info_on_background", StrokeColor::InfoOnBackground),
+	("info_on_side", StrokeColor::InfoOnSide),
+	("info_on_primary", StrokeColor::InfoOnPrimary),
+	("body_on_status", StrokeColor::BodyOnStatus),
+	("comment_on_status", StrokeColor::CommentOnStatus),
+];
+
+const STATUSES: [Status; 4] = [Status::Error, Status::Warning, Status::Success, Status::Info];
+
+const BASE03: Rgb = [0, 43, 54];
+const BASE02: Rgb = [7, 54, 66];
+const BASE01: Rgb = [88, 110, 117];
+const BASE00: Rgb = [101, 123, 131];
+const BASE0: Rgb = [131, 148, 150];
+const BASE1: Rgb = [147, 161, 161];
+const BASE2: Rgb = [238, 232, 213];
+const BASE3: Rgb = [253, 246, 227];
+const YELLOW: Rgb = [181, 137, 0];
+const MAGENTA: Rgb = [211, 54, 130];
+const VIOLET: Rgb = [108, 113, 196];
+const RED: Rgb = [220, 50, 47];
+const GREEN: Rgb = [133, 153, 0];
+
+/// Maps every fill, grade and stroke to an RGB color.
+///
+/// Dark cells move their colors toward a fixed dark color where the theme has
+/// one, and halfway toward the `shade` color otherwise.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Theme {
+	pub name: String,
+	pub shade: Rgb,
+	fills: HashMap<(FillColor, FillGrade), Rgb>,
+	strokes: HashMap<StrokeColor, Rgb>,
+	darks: HashMap<Rgb, Rgb>,
+}
+
+impl Default for Theme {
+	fn default() -> Self { Theme::light() }
+}
+
+impl Theme {
+	/// Solarized light, the original yui colors. Dark cells step three places down
+	/// the base ladder and accents stay as they are.
+	pub fn light() -> Self {
+		let mut theme = Theme::build("light", BASE03, [
+			(FillColor::Background, [BASE3, BASE2, BASE1, BASE0]),
+			(FillColor::Primary, [BASE02, BASE01, BASE00, BASE00]),
+			(FillColor::Side, [BASE2, BASE3, BASE1, BASE0]),
+		], [BASE1, BASE00, BASE00, BASE02, MAGENTA, MAGENTA, BASE1, BASE00, RED], [BASE3, BASE2]);
+		let ladder = [BASE03, BASE02, BASE01, BASE00, BASE0, BASE1, BASE2, BASE3];
+		for (i, rgb) in ladder.iter().enumerate() {
+			theme.set_dark(*rgb, ladder[i.saturating_sub(3)]);
+		}
+		for rgb in [YELLOW, MAGENTA, VIOLET, RED, GREEN].iter() {
+			theme.set_dark(*rgb, *rgb);
+		}
+		theme
+	}
+
+	/// Solarized dark.
+	pub fn dark() -> Self {
+		Theme::build("dark", [0, 0, 0], [
+			(FillColor::Background, [BASE03, BASE02, BASE01, BASE00]),
+			(FillColor::Primary, [BASE2, BASE1, BASE0, BASE0]),
+			(FillColor::Side, [BASE02, BASE03, BASE01, BASE00]),
+		], [BASE01, BASE0, BASE0, BASE1, MAGENTA, MAGENTA, BASE01, BASE00, RED], [BASE3, BASE2])
+	}
+
+	/// Status colors take the same hue on every fill and get shaded as status fills
+	/// when focused or pressed.
+	fn build(name: &str, shade: Rgb, fills: [(FillColor, [Rgb; 4]); 3], strokes: [Rgb; 9], on_status: [Rgb; 2]) -> Self {
+		let mut theme = Theme { name: name.to_string(), shade, fills: HashMap::new(), strokes: HashMap::new(), darks: HashMap::new() };
+		for (color, grades) in fills.iter() {
+			for ((_, grade), rgb) in GRADES.iter().zip(grades.iter()) {
+				theme.set_fill(*color, *grade, *rgb);
+			}
+		}
+		for ((_, color), rgb) in STROKES.iter().zip(strokes.iter()) {
+			theme.set_stroke(*color, *rgb);
+		}
+		for status in STATUSES.iter() {
+			let hue = match status {
+				Status::Error => RED,
+				Status::Warning => YELLOW,
+				Status::Success => GREEN,
+				Status::Info => VIOLET,
+			};
+			for fill in [FillColor::Background, FillColor::Side, FillColor::Primary].iter() {
+				let stroke = status.stroke_on(*fill);
+				if !theme.strokes.contains_key(&stroke) {
+					theme.set_stroke(stroke, hue);
+				}
+			}
+			let shaded = theme.shaded(hue, DARK_LEVELS);
+			for (grade, rgb) in [(FillGrade::Plain, hue), (FillGrade::Select, hue), (FillGrade::Focus, shaded), (FillGrade::Press, shaded)].iter() {
+				theme.set_fill(status.fill(), *grade, *rgb);
+			}
+		}
+		theme.set_stroke(StrokeColor::BodyOnStatus, on_status[0]);
+		theme.set_stroke(StrokeColor::CommentOnStatus, on_status[1]);
+		theme
+	}
+
+	/// Reads a theme file. See `FromStr` for the format.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeParseError> {
+		let text = std::fs::read_to_string(path.as_ref())
+			.map_err(|e| error(&format!("{}: {}", path.as_ref().display(), e)))?;
+		text.parse()
+	}
+
+	/// The fill's color, shaded by a dark level from 0 to `DARK_LEVELS`.
+	pub fn fill_rgb(&self, color: FillColor, grade: FillGrade, dark: u8) -> Rgb {
+		self.shaded(self.fills[&(color, grade)], dark)
+	}
+
+	/// The stroke's color, shaded by a dark level from 0 to `DARK_LEVELS`.
+	pub fn stroke_rgb(&self, color: StrokeColor, dark: u8) -> Rgb {
+		self.shaded(self.strokes[&color], dark)
+	}
+
+	pub fn set_fill(&mut self, color: FillColor, grade: FillGrade, rgb: Rgb) {
+		self.fills.insert((color, grade), rgb);
+	}
+
+	pub fn set_stroke(&mut self, color: StrokeColor, rgb: Rgb) {
+		self.strokes.insert(color, rgb);
+	}
+
+	/// Sets the color that `rgb` becomes at full dark.
+	pub fn set_dark(&mut self, rgb: Rgb, dark: Rgb) {
+		self.darks.insert(rgb, dark);
+	}
+
+	/// Mixes toward the color's dark, or toward the shade reaching halfway at full dark.
+	fn shaded(&self, rgb: Rgb, dark: u8) -> Rgb {
+		let dark = dark.min(DARK_LEVELS) as u16;
+		let (target, full) = match self.darks.get(&rgb) {
+			Some(target) => (*target, DARK_LEVELS as u16),
+			None => (self.shade, 2 * DARK_LEVELS as u16),
+		};
+		let mut mixed = [0; 3];
+		for i in 0..3 {
+			mixed[i] = ((rgb[i] as u16 * (full - dark) + target[i] as u16 * dark) / full) as u8;
+		}
+		mixed
+	}
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ThemeParseError(pub String);
+
+impl fmt::Display for ThemeParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "theme: {}", self.0) }
+}
+
+impl std::error::Error for ThemeParseError {}
+
+/// Parses a TOML-like theme. Colors missing from the file keep the values of the
+/// `extends` theme, "light" by default.
+///
+/// ```text
+/// name = "harbor"
+/// extends = "dark"
+/// shade = "#000000"
+///
+/// [fill]
+/// background = "#1c1c1c"
+/// background.focus = "#303030"
+///
+/// [stroke]
+/// body_on_background = "#d0d0d0"
+/// ```
+impl FromStr for Theme {
+	type Err = ThemeParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut entries = Vec::new();
+		let mut section = String::new();
+		for (number, line) in s.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			if let Some(name) = line.strip_prefix('[').and_then(|it| it.strip_suffix(']')) {
+				section = name.trim().to_string();
+				continue;
+			}
+			let mut parts = line.splitn(2, '=');
+			let key = parts.next().unwrap_or("").trim();
+			let value = parts.next().ok_or_else(|| error(&format!("line {} has no '='", number + 1)))?.trim();
+			let value = match value.strip_prefix('"') {
+				Some(quoted) => quoted.split('"').next().unwrap_or(""),
+				None => value.split_whitespace().next().unwrap_or(""),
+			};
+			entries.push((number + 1, section.clone(), key.to_string(), value.to_string()));
+		}
+		let extends = entries.iter().find(|(_, section, key, _)| section.is_empty() && key == "extends").map(|it| it.3.as_str());
+		let mut theme = match extends {
+			None | Some("light") => Theme::light(),
+			Some("dark") => Theme::dark(),
+			Some(other) => return Err(error(&format!("unknown base theme {}", other))),
+		};
+		for (number, section, key, value) in &entries {
+			match (section.as_str(), key.as_str()) {
+				("", "extends") => {}
+				("", "name") => theme.name = value.to_string(),
+				("", "shade") => theme.shade = parse_rgb(value, *number)?,
+				("fill", key) => {
+					let mut names = key.splitn(2, '.');
+					let color = lookup(&FILLS, names.next().unwrap_or(""), *number)?;
+					let grade = match names.next() {
+						None => FillGrade::Plain,
+						Some(name) => lookup(&GRADES, name, *number)?,
+					};
+					theme.set_fill(color, grade, parse_rgb(value, *number)?);
+				}
+				("stroke", key) => {
+					let color = lookup(&STROKES, key, *number)?;
+					theme.set_stroke(color, parse_rgb(value, *number)?);
+				}
+				(section, key) => return Err(error(&format!("line {} has unknown key {}.{}", number, section, key))),
+			}
+		}
+		Ok(theme)
+	}
+}
+
+fn lookup<T: Copy>(names: &[(&str, T)], name: &str, number: usize) -> Result<T, ThemeParseError> {
+	names.iter().find(|(it, _)| *it == name).map(|(_, value)| *value)
+		.ok_or_else(|| error(&format!("line {} has unknown name {}", number, name)))
+}
+
+fn parse_rgb(value: &str, number: usize) -> Result<Rgb, ThemeParseError> {
+	let hex = value.strip_prefix('#').filter(|it| it.len() == 6 && it.is_ascii());
+	let parse = |hex: &str| -> Option<Rgb> {
+		let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+		Some([channel(0)?, channel(2)?, channel(4)?])
+	};
+	hex.and_then(parse).ok_or_else(|| error(&format!("line {} has bad color {}", number, value)))
+}
+
+fn error(message: &str) -> ThemeParseError { ThemeParseError(message.to_string()) }
+
+#[cfg(test)]
+mod tests {
+	use crate::{FillColor, FillGrade, StrokeColor};
+	use crate::palette::DARK_LEVELS;
+	use crate::theme::{Theme, ThemeParseError};
+
+	#[test]
+	fn parse_extends_base() {
+		let theme = "\
+name = \"harbor\"
+extends = \"dark\"
+
+[fill]
+background = \"#102030\"
+side.focus = \"#ffffff\" # bright
+
+[stroke]
+body_on_background = \"#d0d0d0\"
+".parse::<Theme>().unwrap();
+		assert_eq!(theme.name, "harbor");
+		assert_eq!(theme.fill_rgb(FillColor::Background, FillGrade::Plain, 0), [16, 32, 48]);
+		assert_eq!(theme.fill_rgb(FillColor::Side, FillGrade::Focus, 0), [255, 255, 255]);
+		assert_eq!(theme.fill_rgb(FillColor::Side, FillGrade::Focus, DARK_LEVELS), [127, 127, 127]);
+		assert_eq!(theme.stroke_rgb(StrokeColor::BodyOnBackground, 0), [208, 208, 208]);
+		assert_eq!(theme.stroke_rgb(StrokeColor::ErrorOnBackground, 0), Theme::dark().stroke_rgb(StrokeColor::ErrorOnBackground, 0));
+
+		assert_eq!("[fill]\nback = \"#000000\"".parse::<Theme>(), Err(ThemeParseError("line 2 has unknown name back".into())));
+		assert_eq!("[stroke]\nbody_on_side = \"red\"".parse::<Theme>(), Err(ThemeParseError("line 2 has bad color red".into())));
+	}
+
+	#[test]
+	fn light_keeps_original_dark_mapping() {
+		let theme = Theme::light();
+		assert_eq!(theme.fill_rgb(FillColor::Background, FillGrade::Plain, DARK_LEVELS), [131, 148, 150]);
+		assert_eq!(theme.fill_rgb(FillColor::Primary, FillGrade::Plain, DARK_LEVELS), [0, 43, 54]);
+		assert_eq!(theme.stroke_rgb(StrokeColor::CommentOnBackground, DARK_LEVELS), [88, 110, 117]);
+		assert_eq!(theme.stroke_rgb(StrokeColor::EnabledOnBackground, DARK_LEVELS), [211, 54, 130]);
+		assert_eq!(theme.stroke_rgb(StrokeColor::EnabledOnBackground, 2), [211, 54, 130]);
+	}
+}
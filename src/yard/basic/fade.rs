@@ -1,7 +1,9 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use crate::{Bounds, DrawPad, Fade, MultiLayout};
+use crate::animation::{Transition, Tween};
 use crate::layout::LayoutContext;
+use crate::palette::DARK_LEVELS;
 use crate::yard::{ArcYard, Yard};
 
 pub fn fade(indents: (i32, i32), rear_yard: ArcYard, fore_yard: ArcYard) -> ArcYard {
@@ -10,6 +12,20 @@ pub fn fade(indents: (i32, i32), rear_yard: ArcYard, fore_yard: ArcYard) -> ArcY
 		indents,
 		rear_yard,
 		fore_yard,
+		transition: None,
+		frame: RwLock::new((DARK_LEVELS, 0)),
+	})
+}
+
+pub fn fade_in(indents: (i32, i32), rear_yard: ArcYard, fore_yard: ArcYard, transition: Transition) -> ArcYard {
+	//! Produce a fade whose rear darkens while the fore yard slides up from the bottom edge.
+	Arc::new(FadeYard {
+		id: rand::random(),
+		indents,
+		rear_yard,
+		fore_yard,
+		transition: Some(transition),
+		frame: RwLock::new((0, 0)),
 	})
 }
 
@@ -18,6 +34,9 @@ struct FadeYard {
 	indents: (i32, i32),
 	rear_yard: ArcYard,
 	fore_yard: ArcYard,
+	transition: Option<Transition>,
+	/// Dark level and fore offset rows from the latest layout.
+	frame: RwLock<(u8, i32)>,
 }
 
 impl Fade for ArcYard {
@@ -33,6 +52,7 @@ impl Yard for FadeYard {
 
 	fn layout(&self, ctx: &mut LayoutContext) -> usize {
 		let (_bounds_id, bounds) = ctx.edge_bounds();
+		let progress = self.transition.map(|transition| ctx.progress(&transition));
 		let mut multi_layout = MultiLayout::new(ctx);
 		multi_layout.layout(&self.rear_yard, &bounds);
 
@@ -40,7 +60,12 @@ impl Yard for FadeYard {
 		let indent_bounds = bounds.pad(cols, cols, rows, rows);
 		let rear_near_z = multi_layout.near_z();
 		let fore_z = rear_near_z - 1;
-		let fore_bounds = indent_bounds.with_z(fore_z);
+		let (level, rows) = match progress {
+			None => (DARK_LEVELS, 0),
+			Some(progress) => (0.tween(&DARK_LEVELS, progress), (bounds.bottom - indent_bounds.top).tween(&0, progress)),
+		};
+		*self.frame.write().expect("write frame") = (level, rows);
+		let fore_bounds = indent_bounds.with_z(fore_z).shift_seam(0, 0, rows);
 		multi_layout.layout(&self.fore_yard, &fore_bounds);
 
 		// TODO: Record rear_near_z so we can draw the darkened area a the proper z.
@@ -52,8 +77,9 @@ impl Yard for FadeYard {
 
 	fn render(&self, bounds: &Bounds, _focus_id: i32, pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
 		let (cols, rows) = self.indents;
-		let inside = bounds.pad(cols, cols, rows, rows);
-		pad.dark(bounds, &inside);
+		let (level, offset) = *self.frame.read().expect("read frame");
+		let inside = bounds.pad(cols, cols, rows, rows).shift_seam(0, 0, offset);
+		pad.shade(bounds, &inside, level);
 		Some(vec![(self.fore_yard.clone(), None), (self.rear_yard.clone(), None)])
 	}
 }
@@ -61,7 +87,7 @@ impl Yard for FadeYard {
 #[cfg(test)]
 mod tests {
 	use crate::{FillColor, FillGrade, layout, render, yard};
-	use crate::FillColor::{Background, Primary};
+	use crate::spot::spot_snapshot::assert_golden;
 	use crate::yui::layout::ActiveFocus;
 
 	#[test]
@@ -72,14 +98,6 @@ mod tests {
 		let (max_x, max_y) = (3, 3);
 		let layout = layout::run(max_y, max_x, &yard, &ActiveFocus::default());
 		let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), 0);
-		let fronts = spot_table.to_fronts();
-		let computed = fronts.iter().flatten()
-			.map(|front| (front.fill_color, front.dark))
-			.collect::<Vec<_>>();
-		assert_eq!(computed, vec![
-			(Background, true), (Background, true), (Background, true),
-			(Background, true), (Primary, false), (Background, true),
-			(Background, true), (Background, true), (Background, true),
-		])
+		assert_golden(&spot_table, "fade_layout_render");
 	}
-}
\ No newline at end of file
+}
@@ -1,26 +1,62 @@
 use std::sync::Arc;
 
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::{Bounds, Cling, DrawPad};
 use crate::layout::LayoutContext;
-use crate::palette::StrokeColor;
+use crate::palette::{StrokeColor, StrokeStyle};
 use crate::yard::{ArcYard, Yard};
 
 pub fn label<S: AsRef<str>>(string: S, color: StrokeColor, cling: Cling) -> ArcYard {
 	//! Generate a yard that displays a string of characters.
+	label_yard(string.as_ref(), color, StrokeStyle::PLAIN, cling, false)
+}
+
+pub fn styled_label<S: AsRef<str>>(string: S, color: StrokeColor, style: StrokeStyle, cling: Cling) -> ArcYard {
+	//! Generate a label drawn with text attributes like bold or underline.
+	label_yard(string.as_ref(), color, style, cling, false)
+}
+
+pub fn ellipsis_label<S: AsRef<str>>(string: S, color: StrokeColor, cling: Cling) -> ArcYard {
+	//! Generate a label that ends with an ellipsis when it is wider than its bounds.
+	label_yard(string.as_ref(), color, StrokeStyle::PLAIN, cling, true)
+}
+
+fn label_yard(string: &str, color: StrokeColor, style: StrokeStyle, cling: Cling, ellipsis: bool) -> ArcYard {
 	let id = rand::random();
-	let string = string.as_ref().chars().filter(|it| !it.is_control()).collect::<String>();
+	let string = string.chars().filter(|it| !it.is_control()).collect::<String>();
 	let string_width = UnicodeWidthStr::width(string.as_str());
-	Arc::new(LabelYard { id, color, string, string_width, cling })
+	Arc::new(LabelYard { id, color, style, string, string_width, cling, ellipsis })
 }
 
 struct LabelYard {
 	id: i32,
 	color: StrokeColor,
+	style: StrokeStyle,
 	string: String,
 	string_width: usize,
 	cling: Cling,
+	ellipsis: bool,
+}
+
+impl LabelYard {
+	fn fitted_string(&self, width: i32) -> (String, usize) {
+		if !self.ellipsis || width <= 0 || self.string_width <= width as usize {
+			return (self.string.clone(), self.string_width);
+		}
+		let mut fitted = String::new();
+		let mut fitted_width = 0;
+		for c in self.string.chars() {
+			let c_width = c.width().unwrap_or(0);
+			if fitted_width + c_width + 1 > width as usize {
+				break;
+			}
+			fitted.push(c);
+			fitted_width += c_width;
+		}
+		fitted.push('…');
+		(fitted, fitted_width + 1)
+	}
 }
 
 impl Yard for LabelYard {
@@ -34,19 +70,20 @@ impl Yard for LabelYard {
 	}
 
 	fn render(&self, bounds: &Bounds, _focus_id: i32, pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
-		let (extra_width, extra_height) = (bounds.width() - self.string_width as i32, bounds.height() - 1);
+		let (string, string_width) = self.fitted_string(bounds.width());
+		let (extra_width, extra_height) = (bounds.width() - string_width as i32, bounds.height() - 1);
 		let (cling_x, cling_y) = self.cling.into();
 		let (extra_left, extra_top) = ((extra_width as f32 * cling_x) as i32, (extra_height as f32 * cling_y) as i32);
 		let extra_bottom = bounds.height() - extra_top - 1;
 		let text_bounds = bounds.pad(extra_left, 0, extra_top, extra_bottom);
-		pad.glyph(&text_bounds, &self.string, self.color);
+		pad.styled_glyph(&text_bounds, &string, self.color, self.style);
 		None
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::{Cling, layout, render, StrokeColor, yard};
+	use crate::{ArcYard, Cling, Confine, layout, Pack, render, StrokeColor, StrokeStyle, yard};
 	use crate::StrokeColor::BodyOnBackground;
 	use crate::yui::layout::ActiveFocus;
 
@@ -62,4 +99,28 @@ mod tests {
 			.collect::<Vec<_>>();
 		assert_eq!(computed, vec![("H".to_string(), BodyOnBackground), ("i".to_string(), BodyOnBackground)])
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn clip_and_ellipsis() {
+		let glyphs = |yard: ArcYard| {
+			let yard = yard.confine_width(4, Cling::Left).pack_right(2, yard::label("|", StrokeColor::BodyOnBackground, Cling::Left));
+			let layout = layout::run(1, 6, &yard, &ActiveFocus::default());
+			let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), layout.active_focus.focus_id());
+			spot_table.to_fronts()[0].iter().map(|front| front.stroke.clone().map(|it| it.0).unwrap_or(" ".to_string())).collect::<String>()
+		};
+		assert_eq!(glyphs(yard::label("Centered", StrokeColor::BodyOnBackground, Cling::Center)), "nter| ");
+		assert_eq!(glyphs(yard::ellipsis_label("Centered", StrokeColor::BodyOnBackground, Cling::Center)), "Cen…| ");
+	}
+
+	#[test]
+	fn styled_title() {
+		let yard = yard::styled_title("Hi", StrokeColor::BodyOnBackground, StrokeStyle::BOLD, Cling::Left);
+		let layout = layout::run(2, 3, &yard, &ActiveFocus::default());
+		let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), layout.active_focus.focus_id());
+		let fronts = spot_table.to_fronts();
+		let bold_underline = StrokeStyle::BOLD | StrokeStyle::UNDERLINE;
+		assert_eq!(fronts[0].iter().map(|front| front.stroke_style).collect::<Vec<_>>(), vec![bold_underline, bold_underline, StrokeStyle::PLAIN]);
+		assert!(fronts[1].iter().all(|front| front.stroke.is_none()));
+		assert_eq!(format!("{:?}", bold_underline), "BOLD|UNDERLINE");
+	}
+}
@@ -1,6 +1,7 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use crate::{Bounds, DrawPad};
+use crate::animation::{Transition, Tween};
 use crate::layout::LayoutContext;
 use crate::palette::FillGrade;
 use crate::yard::{ArcYard, Yard};
@@ -13,6 +14,17 @@ pub fn grade(grade: FillGrade) -> ArcYard {
 	})
 }
 
+pub fn grade_transition(from: FillGrade, to: FillGrade, transition: Transition) -> ArcYard {
+	//! Produce a yard that steps the color grade from one grade to another over the transition.
+	Arc::new(GradeTransitionYard {
+		id: rand::random(),
+		from,
+		to,
+		transition,
+		grade: RwLock::new(from),
+	})
+}
+
 struct GradeYard {
 	id: i32,
 	grade: FillGrade,
@@ -32,3 +44,28 @@ impl Yard for GradeYard {
 		None
 	}
 }
+
+struct GradeTransitionYard {
+	id: i32,
+	from: FillGrade,
+	to: FillGrade,
+	transition: Transition,
+	grade: RwLock<FillGrade>,
+}
+
+impl Yard for GradeTransitionYard {
+	fn id(&self) -> i32 { self.id }
+
+	fn layout(&self, ctx: &mut LayoutContext) -> usize {
+		let progress = ctx.progress(&self.transition);
+		*self.grade.write().expect("write grade") = self.from.tween(&self.to, progress);
+		let (bounds_id, _bounds) = ctx.edge_bounds();
+		ctx.set_yard_bounds(self.id, bounds_id);
+		bounds_id
+	}
+
+	fn render(&self, bounds: &Bounds, _focus_id: i32, pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
+		pad.grade(bounds, *self.grade.read().expect("read grade"));
+		None
+	}
+}
@@ -2,6 +2,7 @@ use std::ops::Deref;
 use std::sync::{Arc, RwLock};
 
 use crate::{ArcYard, DrawPad, Focus, FocusMotion, FocusMotionFuture, FocusType, Link, MultiLayout, SyncLink};
+use crate::animation::Tween;
 use crate::core::bounds::Bounds;
 use crate::layout::LayoutContext;
 use crate::yard::list::nexus::Nexus;
@@ -11,6 +12,9 @@ use crate::yard::Yard;
 mod nexus;
 
 pub mod model {
+	use std::time::Duration;
+
+	use crate::animation::{Easing, Transition};
 	use crate::yard::list::nexus::Nexus;
 
 	#[derive(Debug, Copy, Clone)]
@@ -25,6 +29,10 @@ pub mod model {
 		pub sum_heights: i32,
 		pub nexus: Nexus,
 		pub selected_index: usize,
+		/// Time a scroll takes to glide to its new position, or None to jump.
+		pub smooth_scroll: Option<Duration>,
+		/// The position a running scroll started from.
+		pub scroll_from: Option<(Nexus, Transition)>,
 	}
 
 	impl ScrollModel {
@@ -44,15 +52,25 @@ pub mod model {
 				min_item_height = min_item_height.min(item_height);
 			}
 			let nexus = Nexus::new(selected_index, &item_heights);
-			ScrollModel { id, item_heights, item_tops, min_item_height, sum_heights, nexus, selected_index }
+			ScrollModel { id, item_heights, item_tops, min_item_height, sum_heights, nexus, selected_index, smooth_scroll: None, scroll_from: None }
 		}
 		pub fn item_count(&self) -> usize { self.item_heights.len() }
 		pub fn selected_index(&self) -> usize { self.selected_index }
 		pub fn update(&self, action: ScrollAction) -> Option<Self> {
-			match action {
-				ScrollAction::Up => self.nexus.up(&self.item_heights).map(|it| self.with_nexus(it)),
-				ScrollAction::Down => self.nexus.down(&self.item_heights).map(|it| self.with_nexus(it)),
+			let nexus = match action {
+				ScrollAction::Up => self.nexus.up(&self.item_heights),
+				ScrollAction::Down => self.nexus.down(&self.item_heights),
+			}?;
+			let mut next = self.with_nexus(nexus);
+			if let Some(duration) = self.smooth_scroll {
+				next.scroll_from = Some((self.nexus, Transition::starting_now(duration, Easing::EaseOut)));
 			}
+			Some(next)
+		}
+		pub fn with_smooth_scroll(&self, duration: Duration) -> Self {
+			let mut art = self.clone();
+			art.smooth_scroll = Some(duration);
+			art
 		}
 		pub fn with_nexus(&self, nexus: Nexus) -> Self {
 			let mut art = self.clone();
@@ -70,7 +88,7 @@ pub mod model {
 pub fn list(yards: Vec<ArcYard>, scroll: ScrollModel, scroll_link: SyncLink<ScrollAction>) -> ArcYard {
 	assert_eq!(scroll.item_count(), yards.len());
 	let sub_focus = Arc::new(RwLock::new(None));
-	Arc::new(ListYard { scroll, yards, sub_focus, scroll_link })
+	Arc::new(ListYard { scroll, yards, sub_focus, scroll_link, scroll_offset: RwLock::new(0) })
 }
 
 struct ListYard {
@@ -78,6 +96,8 @@ struct ListYard {
 	yards: Vec<ArcYard>,
 	scroll_link: SyncLink<ScrollAction>,
 	sub_focus: Arc<RwLock<Option<Arc<Focus>>>>,
+	/// Rows the items sit away from their resting place while a scroll glides.
+	scroll_offset: RwLock<i32>,
 }
 
 struct LayoutItem {
@@ -91,12 +111,20 @@ impl Yard for ListYard {
 
 	fn layout(&self, ctx: &mut LayoutContext) -> usize {
 		let (_bounds_id, bounds) = ctx.edge_bounds();
+		let scroll_offset = match &self.scroll.scroll_from {
+			Some((from, transition)) if !self.yards.is_empty() => {
+				let progress = ctx.progress(transition);
+				(self.first_top(from, &bounds) - self.first_top(&self.scroll.nexus, &bounds)).tween(&0, progress)
+			}
+			_ => 0,
+		};
+		*self.scroll_offset.write().expect("write scroll_offset") = scroll_offset;
 		let mut focus = None;
 		let final_bounds_id = {
 			let mut multi_layout = MultiLayout::new(ctx);
 			multi_layout.trap_foci(true);
 			let focus_index = self.scroll.nexus.item_index();
-			for layout_item in self.layout_items(&bounds) {
+			for layout_item in self.layout_items(&bounds, scroll_offset) {
 				multi_layout.layout(&layout_item.yard, &layout_item.bounds);
 				if layout_item.index == focus_index {
 					let sub_focus = multi_layout.trapped_focus().map(|it| Arc::new((*it).clone()));
@@ -124,7 +152,8 @@ impl Yard for ListYard {
 		} else {
 			None
 		};
-		let more = self.layout_items(&bounds).iter().map(|layout_item| {
+		let scroll_offset = *self.scroll_offset.read().expect("read scroll_offset");
+		let more = self.layout_items(&bounds, scroll_offset).iter().map(|layout_item| {
 			let yard = layout_item.yard.clone();
 			let focus_id = if Some(layout_item.index) == sub_focus_index && sub_focus_id.is_some() {
 				let focus_id = sub_focus_id.expect("sub_focus_id");
@@ -147,9 +176,10 @@ impl ListYard {
 	fn create_focus(&self, bounds: &Bounds, sub_focus: Option<Arc<Focus>>, nexus: &Nexus, list_link: SyncLink<ScrollAction>) -> Focus {
 		let can_up = nexus.can_up();
 		let can_down = nexus.can_down();
+		let scroll_link = list_link.clone();
 		let focus_motion = Arc::new(move |focus_motion| {
 			match focus_motion {
-				FocusMotion::Left | FocusMotion::Right => {
+				FocusMotion::Left | FocusMotion::Right | FocusMotion::Next | FocusMotion::Previous => {
 					FocusMotionFuture::Default
 				}
 				FocusMotion::Up => {
@@ -170,6 +200,7 @@ impl ListYard {
 				}
 			}
 		});
+		let takes_enter = sub_focus.as_ref().map(|it| it.takes_enter).unwrap_or(false);
 		let (focus_type, priority) = match &sub_focus {
 			None => {
 				let focus_type = FocusType::CompositeSubmit(focus_motion);
@@ -191,6 +222,10 @@ impl ListYard {
 			focus_type,
 			bounds: bounds.to_owned(),
 			priority,
+			tab_index: None,
+			order: Vec::new(),
+			takes_enter,
+			scroll_link: Some(scroll_link),
 			action_block: Arc::new(move |ctx| {
 				if let Some(sub_focus) = &sub_focus {
 					(*sub_focus.action_block)(ctx);
@@ -200,7 +235,13 @@ impl ListYard {
 		focus
 	}
 
-	fn layout_items(&self, bounds: &Bounds) -> Vec<LayoutItem> {
+	/// Top row of the first item when the list rests at the nexus.
+	fn first_top(&self, nexus: &Nexus, bounds: &Bounds) -> i32 {
+		let pivot_row = nexus.pivot_row(bounds.height(), bounds.top, self.scroll.sum_heights, self.scroll.min_item_height, &self.scroll.item_tops);
+		nexus.item_bounds(0, bounds, pivot_row, nexus.pivot_pos(), &self.scroll.item_tops, &self.scroll.item_heights).top
+	}
+
+	fn layout_items(&self, bounds: &Bounds, scroll_offset: i32) -> Vec<LayoutItem> {
 		let nexus = &self.scroll.nexus;
 		let pivot_row = nexus.pivot_row(
 			bounds.height(),
@@ -217,7 +258,8 @@ impl ListYard {
 			next_index = if index >= self.scroll.item_heights.len() {
 				None
 			} else {
-				let item_bounds = nexus.item_bounds(index, bounds, pivot_row, pivot_pos, &self.scroll.item_tops, &self.scroll.item_heights);
+				let item_bounds = nexus.item_bounds(index, bounds, pivot_row, pivot_pos, &self.scroll.item_tops, &self.scroll.item_heights)
+					.shift_seam(0, 0, scroll_offset);
 				let (next, keep) = if item_bounds.bottom < bounds.top {
 					// Full underflow
 					(Some(index + 1), false)
@@ -254,3 +296,30 @@ impl ListYard {
 		layout_items
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::mpsc::channel;
+
+	use crate::{Bounds, FocusMotion, FocusMotionFuture, FocusType, SenderLink};
+	use crate::yard::list::ListYard;
+	use crate::yard::model::ScrollModel;
+
+	#[test]
+	fn tab_leaves_a_scrollable_list() {
+		let (scroll_tx, scroll_rx) = channel();
+		let scroll_link = SenderLink::new(scroll_tx).to_sync();
+		let scroll = ScrollModel::new_count_height(1, 10, 1, 5);
+		let list = ListYard { scroll: scroll.clone(), yards: Vec::new(), scroll_link: scroll_link.clone(), sub_focus: Default::default(), scroll_offset: Default::default() };
+		let focus = list.create_focus(&Bounds::new(4, 3), None, &scroll.nexus, scroll_link);
+		let on_motion = match focus.focus_type {
+			FocusType::CompositeSubmit(on_motion) => on_motion,
+			_ => panic!("list focus is not composite"),
+		};
+		assert_eq!(on_motion(FocusMotion::Next), FocusMotionFuture::Default);
+		assert_eq!(on_motion(FocusMotion::Previous), FocusMotionFuture::Default);
+		assert!(scroll_rx.try_recv().is_err());
+		assert_eq!(on_motion(FocusMotion::Down), FocusMotionFuture::Skip);
+		assert!(scroll_rx.try_recv().is_ok());
+	}
+}
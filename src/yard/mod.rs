@@ -19,8 +19,11 @@ pub use self::mux::*;
 pub use self::observable::*;
 pub use scalar::pressable::*;
 pub use self::quad_label::*;
+pub use self::slide::*;
+pub use self::status_line::*;
 pub use self::tabbar::*;
 pub use self::table::*;
+pub use self::textarea::*;
 pub use self::textfield::*;
 pub use self::title::*;
 pub use self::trellis::*;
@@ -29,9 +32,12 @@ mod grade;
 mod mux;
 mod observable;
 mod quad_label;
+mod slide;
+mod status_line;
 mod list;
 mod tabbar;
 mod table;
+mod textarea;
 mod textfield;
 mod title;
 mod trellis;
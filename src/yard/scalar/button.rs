@@ -1,10 +1,9 @@
 use std::sync::Arc;
-use std::thread;
 use std::time::Duration;
 
 use rand::random;
 
-use crate::{DrawPad, Link, SyncLink, Trigger, yard};
+use crate::{Command, DrawPad, Link, SyncLink, yard};
 use crate::core::bounds::Bounds;
 use crate::layout::LayoutContext;
 use crate::palette::{FillGrade, StrokeColor};
@@ -36,26 +35,28 @@ pub enum ButtonAction {
 	Release,
 }
 
+/// How long a button shows as pressed before its flow acts on the press.
+pub const BUTTON_RELEASE_DELAY: Duration = Duration::from_millis(300);
+
 #[derive(Debug, Clone)]
 pub struct ButtonModel {
 	pub id: i32,
 	pub label: String,
-	pub release_trigger: Trigger,
 	pub affordance: SubmitAffordance,
 }
 
 impl ButtonModel {
-	pub fn disabled(label: &str, release_trigger: Trigger) -> Self {
+	pub fn disabled(label: &str) -> Self {
 		let id = random();
 		let label = label.into();
 		let affordance = SubmitAffordance::Disabled;
-		ButtonModel { id, label, release_trigger, affordance }
+		ButtonModel { id, label, affordance }
 	}
-	pub fn enabled(label: &str, release_trigger: Trigger, press_link: SyncLink<i32>, priority: Priority) -> Self {
+	pub fn enabled(label: &str, press_link: SyncLink<i32>, priority: Priority) -> Self {
 		let id = random();
 		let label = label.to_string();
 		let affordance = SubmitAffordance::Enabled { press_link, priority };
-		ButtonModel { id, label, release_trigger, affordance }
+		ButtonModel { id, label, affordance }
 	}
 	pub fn set_label(&self, label: &str) -> Self {
 		ButtonModel { label: label.to_string(), ..self.clone() }
@@ -72,27 +73,25 @@ impl ButtonModel {
 		button.affordance = SubmitAffordance::Disabled;
 		button
 	}
-	pub fn update(&self, action: ButtonAction) -> Self {
-		match action {
-			ButtonAction::Press => self.press(),
-			ButtonAction::Release => self.release(),
-		}
+	/// Presses an enabled button and returns the command that sends `release`
+	/// once the press has shown. Disabled and pressed buttons return no command.
+	pub fn press<A: Send + 'static>(&self, release: A) -> (Self, Option<Command<A>>) {
+		let command = match &self.affordance {
+			SubmitAffordance::Enabled { .. } => Some(Command::delay(BUTTON_RELEASE_DELAY, release)),
+			SubmitAffordance::Disabled | SubmitAffordance::Pressed { .. } => None,
+		};
+		(self.pressed(), command)
 	}
-	fn press(&self) -> Self {
-		let (new_affordance, submit_trigger) = match &self.affordance {
-			SubmitAffordance::Disabled => (SubmitAffordance::Disabled, None),
-			SubmitAffordance::Enabled { press_link: press, priority, .. } => (SubmitAffordance::Pressed { press_link: press.clone(), priority: priority.clone() }, Some(self.release_trigger.clone())),
-			SubmitAffordance::Pressed { press_link: press, priority } => (SubmitAffordance::Pressed { press_link: press.clone(), priority: priority.clone() }, None),
+	fn pressed(&self) -> Self {
+		let new_affordance = match &self.affordance {
+			SubmitAffordance::Disabled => SubmitAffordance::Disabled,
+			SubmitAffordance::Enabled { press_link: press, priority, .. } => SubmitAffordance::Pressed { press_link: press.clone(), priority: priority.clone() },
+			SubmitAffordance::Pressed { press_link: press, priority } => SubmitAffordance::Pressed { press_link: press.clone(), priority: priority.clone() },
 		};
-		if let Some(trigger) = submit_trigger {
-			thread::spawn(move || {
-				thread::sleep(Duration::from_millis(300));
-				trigger.send(());
-			});
-		}
 		ButtonModel { affordance: new_affordance, ..self.clone() }
 	}
-	fn release(&self) -> Self {
+	/// Shows the button as no longer pressed.
+	pub fn release(&self) -> Self {
 		let material = match &self.affordance {
 			SubmitAffordance::Disabled => SubmitAffordance::Disabled,
 			SubmitAffordance::Enabled { press_link: press, priority, .. } => SubmitAffordance::Enabled { press_link: press.clone(), priority: priority.clone() },
@@ -145,6 +144,10 @@ impl Yard for ButtonYard {
 					focus_type: FocusType::Submit,
 					bounds: edge_bounds,
 					priority: focus_priority(&priority),
+					tab_index: None,
+					order: Vec::new(),
+					takes_enter: false,
+					scroll_link: None,
 					action_block: Arc::new(move |_ctx| press.send(id)),
 				})
 			}
@@ -155,6 +158,10 @@ impl Yard for ButtonYard {
 					focus_type: FocusType::Submit,
 					bounds: edge_bounds,
 					priority: focus_priority(&priority),
+					tab_index: None,
+					order: Vec::new(),
+					takes_enter: false,
+					scroll_link: None,
 					action_block: Arc::new(move |_ctx| {}),
 				})
 			}
@@ -170,6 +177,8 @@ impl Yard for ButtonYard {
 				SubmitAffordance::Enabled { .. } => FillGrade::Focus,
 				SubmitAffordance::Pressed { .. } => FillGrade::Press,
 			}
+		} else if pad.hover_id() == self.id && !matches!(self.material, SubmitAffordance::Disabled) {
+			FillGrade::Select
 		} else {
 			FillGrade::Plain
 		};
@@ -177,3 +186,18 @@ impl Yard for ButtonYard {
 		Some(vec![(self.label_yard.clone(), None)])
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::{Command, SyncLink};
+	use crate::yard::{BUTTON_RELEASE_DELAY, ButtonModel, Priority};
+
+	#[test]
+	fn press_returns_delayed_release() {
+		let button = ButtonModel::enabled("Go", SyncLink::ignore(), Priority::None);
+		let (pressed, release) = button.press(7);
+		assert!(matches!(release, Some(Command::Delay(delay, 7)) if delay == BUTTON_RELEASE_DELAY));
+		assert!(pressed.press(8).1.is_none());
+		assert!(ButtonModel::disabled("Go").press(9).1.is_none());
+	}
+}
@@ -1,8 +1,7 @@
 use std::sync::Arc;
-use std::thread;
 use std::time::Duration;
 
-use crate::{Bounds, DrawPad, FocusAction, Link, SyncLink, Trigger};
+use crate::{Bounds, Command, DrawPad, FocusAction, Link, SyncLink};
 use crate::layout::LayoutContext;
 use crate::palette::FillGrade;
 use crate::yard::{ArcTouch, ArcYard, focus_priority, Priority, Yard};
@@ -14,43 +13,33 @@ pub enum PressAction {
 	Release,
 }
 
+/// How long a pressable shows as pressed before its flow acts on the press.
+pub const PRESS_RELEASE_DELAY: Duration = Duration::from_millis(100);
+
 #[derive(Debug, Clone)]
 pub struct PressModel {
 	id: i32,
 	priority: Priority,
 	is_pressed: bool,
-	release_trigger: Trigger,
 }
 
 impl PressModel {
 	pub fn id(&self) -> i32 { self.id }
 	pub fn is_pressed(&self) -> bool { self.is_pressed }
-	pub fn new(id: i32, release_trigger: Trigger) -> Self {
-		PressModel { id, priority: Priority::None, is_pressed: false, release_trigger }
+	pub fn new(id: i32) -> Self {
+		PressModel { id, priority: Priority::None, is_pressed: false }
 	}
 	pub fn with_priority(self, priority: Priority) -> Self { PressModel { priority, ..self } }
-	pub fn update(&self, action: PressAction) -> Self {
-		let mut model = self.clone();
-		match action {
-			PressAction::Press => {
-				if !model.is_pressed {
-					model.is_pressed = true;
-					let trigger = model.release_trigger.clone();
-					thread::spawn(move || {
-						thread::sleep(Duration::from_millis(100));
-						trigger.send(());
-					});
-				}
-			}
-			PressAction::Release => {
-				model.is_pressed = false;
-			}
-		}
-		model
+	/// Shows the model as no longer pressed.
+	pub fn release(&self) -> Self { PressModel { is_pressed: false, ..self.clone() } }
+	/// Presses the model and returns the command that sends `release` once the
+	/// press has shown. An already pressed model returns no command.
+	pub fn press<A: Send + 'static>(&self, release: A) -> (Self, Option<Command<A>>) {
+		let command = if self.is_pressed { None } else { Some(Command::delay(PRESS_RELEASE_DELAY, release)) };
+		(PressModel { is_pressed: true, ..self.clone() }, command)
 	}
 }
 
-
 pub fn pressable(yard: ArcYard, press: &PressModel, press_link: SyncLink<i32>) -> ArcYard {
 	let id = press.id();
 	let is_pressed = press.is_pressed();
@@ -82,9 +71,13 @@ impl Yard for PressYard {
 				focus_type: FocusType::Submit,
 				bounds: edge_bounds,
 				priority: self.priority,
+				tab_index: None,
+				order: Vec::new(),
+				takes_enter: false,
+				scroll_link: None,
 				action_block: Arc::new(move |ctx| match ctx.action {
 					FocusAction::Go => on_press(),
-					FocusAction::Change(__) => {}
+					FocusAction::Key(_) | FocusAction::Paste(_) => {}
 				}),
 			});
 		}
@@ -95,6 +88,8 @@ impl Yard for PressYard {
 		if focus_id == self.id {
 			let grade = if self.is_pressed { FillGrade::Press } else { FillGrade::Focus };
 			pad.grade(bounds, grade);
+		} else if pad.hover_id() == self.id {
+			pad.grade(bounds, FillGrade::Select);
 		}
 		Some(vec![(self.yard.clone(), None)])
 	}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use crate::{Bounds, DrawPad};
+use crate::animation::{Transition, Tween};
+use crate::layout::LayoutContext;
+use crate::yard::{ArcYard, Yard};
+
+pub fn slide(yard: ArcYard, from: (i32, i32), transition: Transition) -> ArcYard {
+	//! Produce a yard that moves its content from an offset of (cols, rows) to its
+	//! resting place over the transition.
+	Arc::new(SlideYard {
+		id: rand::random(),
+		from,
+		transition,
+		yard,
+	})
+}
+
+struct SlideYard {
+	id: i32,
+	from: (i32, i32),
+	transition: Transition,
+	yard: ArcYard,
+}
+
+impl Yard for SlideYard {
+	fn id(&self) -> i32 { self.id }
+	fn type_desc(&self) -> &'static str { "Slide" }
+
+	fn layout(&self, ctx: &mut LayoutContext) -> usize {
+		let (edge_index, edge_bounds) = ctx.edge_bounds();
+		let progress = ctx.progress(&self.transition);
+		let (cols, rows) = self.from.tween(&(0, 0), progress);
+		let alt_bounds = edge_bounds.shift_seam(0, cols, rows);
+		let alt_index = ctx.push_bounds(&alt_bounds);
+		let core_index = self.yard.layout(&mut ctx.with_index(alt_index));
+		if core_index == alt_index {
+			edge_index
+		} else {
+			let core_bounds = ctx.bounds(core_index);
+			ctx.push_bounds(&edge_bounds.with_z(core_bounds.z))
+		}
+	}
+
+	fn render(&self, _bounds: &Bounds, _focus_id: i32, _pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
+		Some(vec![(self.yard.clone(), None)])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use crate::{Cling, layout, render, StrokeColor, yard};
+	use crate::animation::{Easing, Transition};
+	use crate::yui::layout::ActiveFocus;
+
+	#[test]
+	fn slides_into_place() {
+		let start = Instant::now();
+		let transition = Transition::new(start, Duration::from_millis(100), Easing::Linear);
+		let yard = yard::slide(yard::label("ab", StrokeColor::BodyOnBackground, Cling::LeftTop), (0, 2), transition);
+		let rows_at = |millis: u64| {
+			let layout = layout::run_at(3, 2, &yard, &ActiveFocus::default(), start + Duration::from_millis(millis));
+			let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), 0);
+			(spot_table.to_fronts().iter().position(|row| row[0].stroke.is_some()), layout.animating)
+		};
+		assert_eq!(rows_at(0), (Some(2), true));
+		assert_eq!(rows_at(50), (Some(1), true));
+		assert_eq!(rows_at(100), (Some(0), false));
+	}
+}
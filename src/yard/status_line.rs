@@ -0,0 +1,30 @@
+use crate::{ArcYard, Before, Cling, Padding, yard};
+use crate::palette::{body_and_comment_for_fill, FillGrade, Status};
+
+pub fn status_line(status: Status, message: &str) -> ArcYard {
+	//! Generate a one-row bar that shows a message on its status fill.
+	let fill = status.fill();
+	let (color, _) = body_and_comment_for_fill(fill);
+	let label = yard::ellipsis_label(message, color, Cling::Left);
+	label.pad_cols(1).before(yard::fill(fill, FillGrade::Plain))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{FillColor, layout, render, StrokeColor, yard};
+	use crate::palette::Status;
+	use crate::yui::layout::ActiveFocus;
+
+	#[test]
+	fn layout_render() {
+		let yard = yard::status_line(Status::Success, "Saved");
+		let layout = layout::run(1, 8, &yard, &ActiveFocus::default());
+		let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), layout.active_focus.focus_id());
+		let fronts = &spot_table.to_fronts()[0];
+		assert!(fronts.iter().all(|front| front.fill_color == FillColor::Success));
+		let text = fronts.iter().map(|front| front.stroke.as_ref().map(|(glyph, _)| glyph.as_str()).unwrap_or(" ")).collect::<String>();
+		assert_eq!(text, " Saved  ");
+		assert_eq!(fronts[1].stroke.as_ref().map(|(_, color)| *color), Some(StrokeColor::BodyOnStatus));
+		assert_eq!(Status::Warning.stroke_on(FillColor::Side), StrokeColor::WarningOnSide);
+	}
+}
@@ -81,13 +81,23 @@ impl Yard for TabYard {
 			focus_type: FocusType::Submit,
 			bounds,
 			priority: if self.is_selected { 500 } else { 0 },
+			tab_index: None,
+			order: Vec::new(),
+			takes_enter: false,
+			scroll_link: None,
 			action_block: Arc::new(move |_| { on_select.send(()); }),
 		});
 		bounds_id
 	}
 
 	fn render(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
-		let fill_grade = if focus_id == self.id() { FillGrade::Focus } else { FillGrade::Plain };
+		let fill_grade = if focus_id == self.id() {
+			FillGrade::Focus
+		} else if pad.hover_id() == self.id() {
+			FillGrade::Select
+		} else {
+			FillGrade::Plain
+		};
 		pad.grade(bounds, fill_grade);
 		pad.fill(bounds, FillColor::Primary);
 		Some(vec![(self.content.clone(), None)])
@@ -96,8 +106,8 @@ impl Yard for TabYard {
 
 #[cfg(test)]
 mod tests {
-	use crate::{layout, render, SenderLink, StrokeColor};
-	use crate::StrokeColor::{BodyOnBackground, EnabledOnPrimary};
+	use crate::{layout, render, SenderLink};
+	use crate::spot::spot_snapshot::assert_golden;
 	use crate::yard::tabbar::tab_yard;
 	use crate::yui::layout::ActiveFocus;
 
@@ -107,13 +117,6 @@ mod tests {
 		let (max_x, max_y) = (3, 2);
 		let layout = layout::run(max_y, max_x, &yard, &ActiveFocus::default());
 		let draw_pad = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), 0);
-		let fronts = draw_pad.to_fronts();
-		let fills = fronts.iter().flatten()
-			.map(|front| front.stroke.clone().unwrap_or((" ".to_string(), StrokeColor::BodyOnBackground)))
-			.collect::<Vec<_>>();
-		assert_eq!(fills, vec![
-			(" ".to_string(), BodyOnBackground), ("a".to_string(), EnabledOnPrimary), (" ".to_string(), BodyOnBackground),
-			("_".to_string(), EnabledOnPrimary), ("_".to_string(), EnabledOnPrimary), ("_".to_string(), EnabledOnPrimary),
-		])
+		assert_golden(&draw_pad, "tabbar_layout_render");
 	}
 }
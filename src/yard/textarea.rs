@@ -0,0 +1,339 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use stringedit::StringEdit;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{Before, Bounds, DrawPad, EditKey, Focus, FocusAction, FocusMotion, FocusMotionFuture, FocusType, KeyCode, KeyEvent, Link, SenderLink, StrokeColor, SyncLink};
+use crate::layout::LayoutContext;
+use crate::palette::{FillColor, FillGrade};
+use crate::yard::{ArcYard, Yard};
+use crate::yard;
+use crate::yard::textfield;
+
+/// Multi-line text editor. Lines wrap at word boundaries and the body scrolls to keep the cursor in view.
+pub fn textarea(id: i32, label: &str, edit: StringEdit, update: SenderLink<stringedit::Action>) -> ArcYard {
+	let yard = TextareaYard {
+		id,
+		label_chars: label.chars().collect(),
+		edit: Arc::new(edit),
+		update: update.into(),
+	};
+	let arc_yard = Arc::new(yard) as ArcYard;
+	arc_yard.before(yard::fill(FillColor::Background, FillGrade::Select))
+}
+
+struct TextareaYard {
+	id: i32,
+	label_chars: Vec<char>,
+	edit: Arc<StringEdit>,
+	update: SyncLink<stringedit::Action>,
+}
+
+impl Yard for TextareaYard {
+	fn id(&self) -> i32 { self.id }
+
+	fn layout(&self, ctx: &mut LayoutContext) -> usize {
+		let (edge_index, edge_bounds) = ctx.edge_bounds();
+		let wrap_width = body_bounds(&edge_bounds).width().max(1) as usize;
+		let focus_update = self.update.clone();
+		let focus_edit = self.edit.clone();
+		let action_update = self.update.clone();
+		let action_edit = self.edit.clone();
+		ctx.add_focus(Focus {
+			yard_id: self.id,
+			focus_type: FocusType::Edit(Arc::new(move |motion| {
+				match motion {
+					FocusMotion::Left => {
+						if focus_edit.cursor_index == 0 {
+							FocusMotionFuture::Default
+						} else {
+							focus_update.send(stringedit::Action::MoveCursorLeft);
+							FocusMotionFuture::Skip
+						}
+					}
+					FocusMotion::Right => {
+						if focus_edit.cursor_index == focus_edit.chars.len() {
+							FocusMotionFuture::Default
+						} else {
+							focus_update.send(stringedit::Action::MoveCursorRight);
+							FocusMotionFuture::Skip
+						}
+					}
+					FocusMotion::Up | FocusMotion::Down => {
+						match vertical_actions(&focus_edit, wrap_width, motion == FocusMotion::Up) {
+							None => FocusMotionFuture::Default,
+							Some(actions) => {
+								for action in actions {
+									focus_update.send(action);
+								}
+								FocusMotionFuture::Skip
+							}
+						}
+					}
+					FocusMotion::Next | FocusMotion::Previous => FocusMotionFuture::Default,
+				}
+			})),
+			bounds: edge_bounds.clone(),
+			priority: 0,
+			tab_index: None,
+			order: Vec::new(),
+			takes_enter: true,
+			scroll_link: None,
+			action_block: Arc::new(move |ctx| {
+				let actions = match ctx.action {
+					FocusAction::Go => Vec::new(),
+					FocusAction::Key(key) => key_actions(&action_edit, &key),
+					FocusAction::Paste(ref text) => paste_actions(text),
+				};
+				if !actions.is_empty() {
+					for action in actions {
+						action_update.send(action);
+					}
+					ctx.refresh.deref()();
+				}
+			}),
+		});
+		ctx.set_yard_bounds(self.id, edge_index);
+		edge_index
+	}
+
+	fn render(&self, edge_bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
+		let (head_bounds, _) = edge_bounds.split_from_top(1);
+		self.render_head(&head_bounds, focus_id, pad);
+		let (_, foot_bounds) = edge_bounds.split_from_bottom(1);
+		self.render_foot(&foot_bounds, focus_id, pad);
+		self.render_body(&body_bounds(edge_bounds), focus_id, pad);
+		None
+	}
+}
+
+impl TextareaYard {
+	fn render_head(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) {
+		let bounds = bounds.pad(1, 1, 0, 0);
+		if !self.label_chars.is_empty() && (focus_id == self.id || !self.edit.chars.is_empty()) {
+			let glyph: String = self.label_chars.iter().collect();
+			let color = if focus_id == self.id { StrokeColor::EnabledOnBackground } else { StrokeColor::CommentOnBackground };
+			pad.glyph(&bounds, &glyph, color);
+		}
+	}
+	fn render_body(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) {
+		let edit = self.edit.clone();
+		if bounds.width() <= 0 || bounds.height() <= 0 {
+			return;
+		}
+		if focus_id != self.id && edit.chars.is_empty() {
+			if !self.label_chars.is_empty() {
+				let label: String = self.label_chars.iter().collect();
+				pad.glyph(bounds, &label, StrokeColor::CommentOnBackground);
+			}
+			return;
+		}
+		let lines = wrap_lines(&edit.chars, bounds.width() as usize);
+		let (cursor_row, cursor_col) = row_col(&edit.chars, &lines, edit.cursor_index);
+		let height = bounds.height() as usize;
+		let top_row = (cursor_row + 1).saturating_sub(height);
+		for (row, &(start, end)) in lines.iter().enumerate().skip(top_row).take(height) {
+			let text: String = edit.chars[start..end].iter().map(|c| if c.is_control() { ' ' } else { *c }).collect();
+			if !text.is_empty() {
+				let row_bounds = row_bounds(bounds, row - top_row);
+				pad.glyph(&row_bounds, &text, StrokeColor::BodyOnBackground);
+			}
+		}
+		let cursor_col = (cursor_col as i32).min(bounds.width() - 1);
+		let cursor_cols = edit.chars.get(edit.cursor_index).and_then(|c| c.width()).unwrap_or(1).max(1) as i32;
+		let cursor_cols = cursor_cols.min(bounds.width() - cursor_col);
+		let cursor_bounds = row_bounds(bounds, cursor_row - top_row).pad(cursor_col, bounds.width() - (cursor_col + cursor_cols), 0, 0);
+		if focus_id == self.id {
+			pad.fill(&cursor_bounds, FillColor::Background);
+			pad.grade(&cursor_bounds, FillGrade::Focus);
+		}
+		if let Some(c) = edit.chars.get(edit.cursor_index) {
+			if !c.is_control() {
+				pad.glyph(&cursor_bounds, &c.to_string(), StrokeColor::BodyOnBackground);
+			}
+		}
+	}
+	fn render_foot(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) {
+		let color = if focus_id == self.id {
+			StrokeColor::EnabledOnBackground
+		} else {
+			StrokeColor::CommentOnBackground
+		};
+		let underline: String = (0..bounds.width()).map(|_| '_').collect();
+		pad.glyph(bounds, &underline, color);
+	}
+}
+
+fn body_bounds(edge_bounds: &Bounds) -> Bounds {
+	edge_bounds.pad(1, 1, 1, 1)
+}
+
+fn row_bounds(bounds: &Bounds, row: usize) -> Bounds {
+	let row = row as i32;
+	bounds.pad(0, 0, row, bounds.height() - (row + 1))
+}
+
+fn key_actions(edit: &StringEdit, key: &KeyEvent) -> Vec<stringedit::Action> {
+	if key.code == KeyCode::Enter && key.modifiers.is_empty() {
+		return vec![stringedit::Action::InsertChar('\n')];
+	}
+	let cursor = edit.cursor_index;
+	let line_start = edit.chars[..cursor].iter().rposition(|c| *c == '\n').map(|it| it + 1).unwrap_or(0);
+	let line_end = edit.chars[cursor..].iter().position(|c| *c == '\n').map(|it| cursor + it).unwrap_or(edit.chars.len());
+	let (action, count) = match key.to_edit_key() {
+		Some(EditKey::Home) => (stringedit::Action::MoveCursorLeft, cursor - line_start),
+		Some(EditKey::End) => (stringedit::Action::MoveCursorRight, line_end - cursor),
+		Some(EditKey::KillToStart) => (stringedit::Action::DeleteCharBeforeCursor, cursor - line_start),
+		Some(EditKey::KillToEnd) => (stringedit::Action::DeleteCharAtCursor, line_end - cursor),
+		_ => return textfield::key_actions(edit, key),
+	};
+	(0..count).map(|_| action.clone()).collect()
+}
+
+fn paste_actions(text: &str) -> Vec<stringedit::Action> {
+	text.chars()
+		.map(|c| if c == '\t' { ' ' } else { c })
+		.filter(|c| *c == '\n' || !c.is_control())
+		.map(stringedit::Action::InsertChar)
+		.collect()
+}
+
+/// Cursor moves that reach the same column on the visual line above or below, or `None` at the first or last line.
+fn vertical_actions(edit: &StringEdit, width: usize, up: bool) -> Option<Vec<stringedit::Action>> {
+	let lines = wrap_lines(&edit.chars, width);
+	let (row, col) = row_col(&edit.chars, &lines, edit.cursor_index);
+	let target_row = if up { row.checked_sub(1)? } else { row + 1 };
+	if target_row >= lines.len() {
+		return None;
+	}
+	let target = index_at(&edit.chars, &lines, target_row, col);
+	let actions = if target < edit.cursor_index {
+		(target..edit.cursor_index).map(|_| stringedit::Action::MoveCursorLeft).collect()
+	} else {
+		(edit.cursor_index..target).map(|_| stringedit::Action::MoveCursorRight).collect()
+	};
+	Some(actions)
+}
+
+/// Splits text into visual lines of at most `width` columns, as `(start, end)` char ranges
+/// that exclude newlines. Lines break after the last space that fits, or mid-word when none does.
+pub(crate) fn wrap_lines(chars: &[char], width: usize) -> Vec<(usize, usize)> {
+	let width = width.max(1);
+	let mut lines = Vec::new();
+	let mut start = 0;
+	while start <= chars.len() {
+		let end = chars[start..].iter().position(|c| *c == '\n').map(|it| start + it).unwrap_or(chars.len());
+		let mut line_start = start;
+		loop {
+			let mut used = 0;
+			let mut index = line_start;
+			let mut after_space = None;
+			while index < end {
+				let char_width = chars[index].width().unwrap_or(0);
+				if used + char_width > width {
+					break;
+				}
+				used += char_width;
+				if chars[index] == ' ' {
+					after_space = Some(index + 1);
+				}
+				index += 1;
+			}
+			if index >= end {
+				lines.push((line_start, end));
+				break;
+			}
+			let line_end = match after_space {
+				Some(after_space) => after_space,
+				None => index.max(line_start + 1),
+			};
+			lines.push((line_start, line_end));
+			line_start = line_end;
+		}
+		start = end + 1;
+	}
+	lines
+}
+
+fn row_col(chars: &[char], lines: &[(usize, usize)], cursor: usize) -> (usize, usize) {
+	let row = lines.iter().rposition(|(start, _)| *start <= cursor).unwrap_or(0);
+	let start = lines[row].0;
+	let col = chars[start..cursor].iter().map(|c| c.width().unwrap_or(0)).sum();
+	(row, col)
+}
+
+fn index_at(chars: &[char], lines: &[(usize, usize)], row: usize, col: usize) -> usize {
+	let (start, end) = lines[row];
+	let soft_wrapped = row + 1 < lines.len() && lines[row + 1].0 == end;
+	let last = if soft_wrapped { end - 1 } else { end };
+	let mut used = 0;
+	let mut index = start;
+	while index < last {
+		let char_width = chars[index].width().unwrap_or(0);
+		if used + char_width > col {
+			break;
+		}
+		used += char_width;
+		index += 1;
+	}
+	index
+}
+
+#[cfg(test)]
+mod tests {
+	use stringedit::{StringEdit, Validity};
+
+	use crate::{KeyCode, KeyEvent, layout, render, SenderLink, yard};
+	use crate::spot::spot_snapshot::assert_golden;
+	use crate::yard::textarea::{paste_actions, vertical_actions, wrap_lines};
+	use crate::yui::layout::ActiveFocus;
+	use crate::yui_curses::keyboard::paste_text;
+
+	#[test]
+	fn wrap_at_words() {
+		let chars = "one two three\nfour".chars().collect::<Vec<_>>();
+		assert_eq!(wrap_lines(&chars, 8), vec![(0, 8), (8, 13), (14, 18)]);
+		let chars = "abcdefghij".chars().collect::<Vec<_>>();
+		assert_eq!(wrap_lines(&chars, 4), vec![(0, 4), (4, 8), (8, 10)]);
+		assert_eq!(wrap_lines(&[], 4), vec![(0, 0)]);
+	}
+
+	#[test]
+	fn up_down_keep_column() {
+		let edit = StringEdit::new("one two three\nfour", 10, Validity::NotEmpty);
+		let apply = |up| vertical_actions(&edit, 8, up).map(|actions| actions.into_iter().fold(edit.clone(), |edit, action| edit.edit(action)).cursor_index);
+		assert_eq!(apply(true), Some(2));
+		assert_eq!(apply(false), Some(16));
+		let last = StringEdit::new("one two three\nfour", 16, Validity::NotEmpty);
+		assert_eq!(vertical_actions(&last, 8, false), None);
+	}
+
+	#[test]
+	fn layout_render() {
+		let edit = StringEdit::new("one two three four five", 23, Validity::NotEmpty);
+		let yard = yard::textarea(300, "Notes", edit, SenderLink::ignore());
+		let (max_x, max_y) = (10, 5);
+		let layout = layout::run(max_y, max_x, &yard, &ActiveFocus::default());
+		let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), layout.active_focus.focus_id());
+		assert_golden(&spot_table, "textarea_layout_render");
+	}
+
+	#[test]
+	fn only_textarea_takes_enter() {
+		let enter = KeyEvent::plain(KeyCode::Enter);
+		let edit = StringEdit::new("one", 3, Validity::NotEmpty);
+		let textarea = yard::textarea(300, "Notes", edit.clone(), SenderLink::ignore());
+		let textfield = yard::textfield(301, "Name", edit, SenderLink::ignore());
+		let takes_enter = |yard| layout::run(5, 10, &yard, &ActiveFocus::default()).active_focus.accepts_key(&enter);
+		assert!(takes_enter(textarea));
+		assert!(!takes_enter(textfield));
+	}
+
+	#[test]
+	fn pasted_line_breaks_stay_lines() {
+		let edit = StringEdit::empty(Validity::NotEmpty);
+		let pasted = paste_actions(&paste_text(b"a\rb\r\nc")).into_iter().fold(edit, |edit, action| edit.edit(action));
+		assert_eq!(pasted.chars.iter().collect::<String>(), "a\nb\nc");
+	}
+}
@@ -1,19 +1,75 @@
 use std::ops::Deref;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use stringedit::StringEdit;
+use unicode_width::UnicodeWidthChar;
 
-use crate::{Before, Bounds, DrawPad, Focus, FocusAction, FocusMotion, FocusMotionFuture, FocusType, Link, SenderLink, StrokeColor, SyncLink};
+use crate::{Before, Bounds, DrawPad, EditKey, Focus, FocusAction, FocusMotion, FocusMotionFuture, FocusType, KeyCode, KeyEvent, Link, SenderLink, StrokeColor, SyncLink};
 use crate::layout::LayoutContext;
 use crate::palette::{FillColor, FillGrade};
 use crate::yard::{ArcYard, Yard};
 use crate::yard;
 
-pub fn textfield(id: i32, label: &str, edit: StringEdit, update: SenderLink<stringedit::Action>) -> ArcYard {
+/// Edit state for a textfield together with its display options.
+#[derive(Debug, Clone)]
+pub struct TextfieldModel {
+	pub edit: StringEdit,
+	pub mask: Option<char>,
+	pub max_length: Option<usize>,
+	pub help: Option<String>,
+	pub error: Option<String>,
+	/// Index of the first visible char. Shared by the model's revisions so the
+	/// window only moves when the cursor would leave it.
+	window_start: Arc<AtomicUsize>,
+}
+
+impl TextfieldModel {
+	pub fn new(edit: StringEdit) -> Self {
+		TextfieldModel { edit, mask: None, max_length: None, help: None, error: None, window_start: Arc::new(AtomicUsize::new(0)) }
+	}
+	/// Index of the first char the last layout showed.
+	pub fn window_start(&self) -> usize { self.window_start.load(Ordering::Relaxed) }
+	/// Shows every char as `mask`.
+	pub fn with_mask(&self, mask: char) -> Self { TextfieldModel { mask: Some(mask), ..self.clone() } }
+	/// Refuses inserts past `max_length` chars and shows the count in the foot row.
+	pub fn with_max_length(&self, max_length: usize) -> Self { TextfieldModel { max_length: Some(max_length), ..self.clone() } }
+	/// Text for the foot row while the value is invalid and no error is set.
+	pub fn with_help(&self, help: &str) -> Self { TextfieldModel { help: Some(help.to_string()), ..self.clone() } }
+	/// Text for the foot row while the value is invalid.
+	pub fn with_error(&self, error: &str) -> Self { TextfieldModel { error: Some(error.to_string()), ..self.clone() } }
+
+	pub fn edit(&self, action: stringedit::Action) -> Self {
+		if let stringedit::Action::InsertChar(_) = action {
+			if self.is_full() {
+				return self.clone();
+			}
+		}
+		TextfieldModel { edit: self.edit.edit(action), ..self.clone() }
+	}
+	pub fn is_full(&self) -> bool {
+		self.max_length.map(|max_length| self.edit.chars.len() >= max_length).unwrap_or(false)
+	}
+	pub fn is_valid(&self) -> bool { self.edit.is_valid() }
+}
+
+impl From<StringEdit> for TextfieldModel {
+	fn from(edit: StringEdit) -> Self { TextfieldModel::new(edit) }
+}
+
+pub fn textfield(id: i32, label: &str, model: impl Into<TextfieldModel>, update: SenderLink<stringedit::Action>) -> ArcYard {
+	let model = model.into();
+	let valid = model.is_valid();
+	let message = model.error.or(model.help).map(|it| it.chars().collect());
 	let yard = TextfieldYard {
 		id,
 		label_chars: label.chars().collect(),
-		edit: Arc::new(edit),
+		edit: Arc::new(model.edit),
+		mask: model.mask,
+		max_length: model.max_length,
+		valid,
+		message_chars: message,
+		window_start: model.window_start,
 		update: update.into(),
 	};
 	let arc_yard = Arc::new(yard) as ArcYard;
@@ -24,6 +80,11 @@ struct TextfieldYard {
 	id: i32,
 	label_chars: Vec<char>,
 	edit: Arc<StringEdit>,
+	mask: Option<char>,
+	max_length: Option<usize>,
+	valid: bool,
+	message_chars: Option<Vec<char>>,
+	window_start: Arc<AtomicUsize>,
 	update: SyncLink<stringedit::Action>,
 }
 
@@ -32,9 +93,13 @@ impl Yard for TextfieldYard {
 
 	fn layout(&self, ctx: &mut LayoutContext) -> usize {
 		let (edge_index, edge_bounds) = ctx.edge_bounds();
+		let body_width = body_bounds(&edge_bounds).width().max(0) as usize;
+		let window_start = scroll_window(&self.display_chars(), self.edit.cursor_index, self.window_start.load(Ordering::Relaxed), body_width).start;
+		self.window_start.store(window_start, Ordering::Relaxed);
 		let focus_update = self.update.clone();
 		let focus_edit = self.edit.clone();
 		let action_update = self.update.clone();
+		let action_edit = self.edit.clone();
 		ctx.add_focus(Focus {
 			yard_id: self.id,
 			focus_type: FocusType::Edit(Arc::new(move |motion| {
@@ -57,27 +122,34 @@ impl Yard for TextfieldYard {
 							FocusMotionFuture::Skip
 						}
 					}
-					FocusMotion::Up => FocusMotionFuture::Default,
-					FocusMotion::Down => FocusMotionFuture::Default,
+					FocusMotion::Up | FocusMotion::Down => FocusMotionFuture::Default,
+					FocusMotion::Next | FocusMotion::Previous => FocusMotionFuture::Default,
 				}
 			})),
 			bounds: edge_bounds.clone(),
 			priority: 0,
+			tab_index: None,
+			order: Vec::new(),
+			takes_enter: false,
+			scroll_link: None,
 			action_block: Arc::new(move |ctx| {
 				match ctx.action {
 					FocusAction::Go => {}
-					FocusAction::Change(c) => {
-						if !c.is_control() {
-							action_update.send(stringedit::Action::InsertChar(c));
-							ctx.refresh.deref()();
-						} else if c == '\x08' {
-							action_update.send(stringedit::Action::DeleteCharBeforeCursor);
-							ctx.refresh.deref()();
-						} else if c == '\x7f' {
-							action_update.send(stringedit::Action::DeleteCharAtCursor);
+					FocusAction::Key(key) => {
+						let actions = key_actions(&action_edit, &key);
+						if !actions.is_empty() {
+							for action in actions {
+								action_update.send(action);
+							}
 							ctx.refresh.deref()();
 						}
 					}
+					FocusAction::Paste(ref text) => {
+						for action in paste_actions(text) {
+							action_update.send(action);
+						}
+						ctx.refresh.deref()();
+					}
 				}
 			}),
 		});
@@ -90,14 +162,27 @@ impl Yard for TextfieldYard {
 		let (head_bounds, lower_bounds) = edge_bounds.split_from_top(1);
 		self.render_head(&head_bounds, focus_id, pad);
 
-		let (body_bounds, foot_bounds) = lower_bounds.split_from_bottom(1);
+		let (_, foot_bounds) = lower_bounds.split_from_bottom(1);
 		self.render_foot(&foot_bounds, focus_id, pad);
-		self.render_body(&body_bounds, focus_id, pad);
+		self.render_body(&body_bounds(edge_bounds), focus_id, pad);
 		None
 	}
 }
 
+/// The row holding the text, inside the side margins.
+fn body_bounds(edge_bounds: &Bounds) -> Bounds {
+	let (_, lower_bounds) = edge_bounds.split_from_top(1);
+	let (body_bounds, _) = lower_bounds.split_from_bottom(1);
+	body_bounds.pad(1, 1, 0, 0)
+}
+
 impl TextfieldYard {
+	fn display_chars(&self) -> Vec<char> {
+		match self.mask {
+			None => self.edit.chars.clone(),
+			Some(mask) => self.edit.chars.iter().map(|_| mask).collect(),
+		}
+	}
 	fn render_head(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) {
 		let bounds = bounds.pad(1, 1, 0, 0);
 		if !self.label_chars.is_empty() {
@@ -114,76 +199,204 @@ impl TextfieldYard {
 		}
 	}
 	fn render_body(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) {
-		let bounds = bounds.pad(1, 1, 0, 0);
 		let edit = self.edit.clone();
 		if focus_id != self.id && edit.chars.len() == 0 {
 			if !self.label_chars.is_empty() {
 				let label: String = self.label_chars.iter().collect();
 				pad.glyph(&bounds, &label, StrokeColor::CommentOnBackground);
 			}
-		} else {
-			let cursor_from_left = {
-				let full_length = edit.chars.len();
-				let visible_length = bounds.width() as usize;
-				if full_length < visible_length {
-					edit.cursor_index as i32
-				} else {
-					let cursor_fraction = edit.cursor_index as f32 / full_length as f32;
-					let range = (visible_length - 1) as f32;
-					(cursor_fraction * range).ceil() as i32
-				}
+		} else if bounds.width() > 0 {
+			let width = bounds.width() as usize;
+			let chars = self.display_chars();
+			let window = scroll_window(&chars, edit.cursor_index, self.window_start.load(Ordering::Relaxed), width);
+			let cell_bounds = |col: usize, cols: usize| {
+				let col = col as i32;
+				bounds.pad(col, bounds.width() - (col + cols as i32), 0, 0)
 			};
-			{
-				let pre_chars: Vec<char> = edit.chars[0..edit.cursor_index].iter().cloned().collect();
-				if !pre_chars.is_empty() {
-					let pre_start = (pre_chars.len() as i32 - cursor_from_left).max(0) as usize;
-					let pre_visible_chars: String = pre_chars[pre_start..].iter().cloned().map(|c| if !c.is_control() { c } else { ' ' }).collect();
-					pad.glyph(&bounds, &pre_visible_chars, StrokeColor::BodyOnBackground);
-				}
+			if focus_id == self.id {
+				let cursor_width = chars.get(edit.cursor_index).map(|c| char_width(*c)).unwrap_or(1);
+				let cursor_bounds = cell_bounds(window.cursor_col, cursor_width.min(width - window.cursor_col));
+				pad.fill(&cursor_bounds, FillColor::Background);
+				pad.grade(&cursor_bounds, FillGrade::Focus);
 			}
-			{
-				let cursor_bounds = bounds.pad(cursor_from_left, bounds.width() - (cursor_from_left + 1), 0, 0);
-				if focus_id == self.id {
-					pad.fill(&cursor_bounds, FillColor::Background);
-					pad.grade(&cursor_bounds, FillGrade::Focus);
+			let mut glyphs: Vec<(usize, String, usize)> = Vec::new();
+			for &(col, c) in &window.cells {
+				match glyphs.last_mut() {
+					// Combining marks join the glyph they follow.
+					Some((_, glyph, _)) if char_width(c) == 0 => glyph.push(c),
+					_ => glyphs.push((col, if c.is_control() { ' ' } else { c }.to_string(), char_width(c).max(1))),
 				}
-				if edit.cursor_index < edit.chars.len() {
-					let char = edit.chars[edit.cursor_index];
-					if !char.is_control() {
-						pad.glyph(&cursor_bounds, &char.to_string(), StrokeColor::BodyOnBackground);
-					}
-				};
 			}
-			{
-				let post_start = edit.cursor_index + 1;
-				if post_start < edit.chars.len() {
-					let post_chars: String = edit.chars[post_start..].iter().cloned().map(|c| if !c.is_control() { c } else { ' ' }).collect();
-					let post_bounds = bounds.pad(cursor_from_left + 1, 0, 0, 0);
-					pad.glyph(&post_bounds, &post_chars, StrokeColor::BodyOnBackground);
-				}
+			for (col, glyph, cols) in glyphs {
+				pad.glyph(&cell_bounds(col, cols), &glyph, StrokeColor::BodyOnBackground);
+			}
+			if window.start > 0 && window.cursor_col > 0 {
+				pad.glyph(&cell_bounds(0, 1), ELLIPSIS, StrokeColor::CommentOnBackground);
+			}
+			if window.clipped_right {
+				pad.glyph(&cell_bounds(width - 1, 1), ELLIPSIS, StrokeColor::CommentOnBackground);
 			}
 		}
 	}
 	fn render_foot(&self, bounds: &Bounds, focus_id: i32, pad: &mut dyn DrawPad) {
-		let color = if focus_id == self.id {
+		let focused = focus_id == self.id;
+		let show_invalid = !self.valid && (focused || !self.edit.chars.is_empty());
+		let color = if show_invalid {
+			StrokeColor::ErrorOnBackground
+		} else if focused {
 			StrokeColor::EnabledOnBackground
 		} else {
 			StrokeColor::CommentOnBackground
 		};
-		let underline: String = (0..bounds.width()).map(|_| '_').collect();
-		pad.glyph(bounds, &underline, color);
+		let width = bounds.width().max(0) as usize;
+		let mut foot = vec!['_'; width];
+		if let Some(max_length) = self.max_length {
+			let count = format!("{}/{}", self.edit.chars.len(), max_length).chars().collect::<Vec<_>>();
+			if count.len() < width {
+				foot[width - count.len()..].copy_from_slice(&count);
+			}
+		}
+		if show_invalid {
+			if let Some(message) = &self.message_chars {
+				let room = foot.iter().position(|c| *c != '_').unwrap_or(width).saturating_sub(1);
+				for (i, c) in message.iter().take(room).enumerate() {
+					foot[i] = *c;
+				}
+			}
+		}
+		let foot: String = foot.into_iter().collect();
+		pad.glyph(bounds, &foot, color);
 	}
 }
 
+const ELLIPSIS: &str = "…";
+
+struct ScrollWindow {
+	start: usize,
+	cells: Vec<(usize, char)>,
+	cursor_col: usize,
+	clipped_right: bool,
+}
+
+/// Finds the window nearest `scroll_index` that shows the cursor. The window only
+/// moves when the cursor would leave it, and a clipped side gives up a column to an ellipsis.
+fn scroll_window(chars: &[char], cursor: usize, scroll_index: usize, width: usize) -> ScrollWindow {
+	let mut start = scroll_index.min(cursor);
+	loop {
+		let window = layout_window(chars, start, cursor, width, true);
+		if let Some(cursor_col) = window.1 {
+			return ScrollWindow { start, cells: window.0, cursor_col, clipped_right: window.2 };
+		}
+		if start >= cursor {
+			let window = layout_window(chars, cursor, cursor, width, false);
+			return ScrollWindow { start: cursor, cells: window.0, cursor_col: window.1.unwrap_or(0), clipped_right: false };
+		}
+		start += 1;
+	}
+}
+
+fn layout_window(chars: &[char], start: usize, cursor: usize, width: usize, markers: bool) -> (Vec<(usize, char)>, Option<usize>, bool) {
+	let mut col = if markers && start > 0 { 1 } else { 0 };
+	let mut cells = Vec::new();
+	let mut clipped_right = false;
+	for (index, c) in chars.iter().enumerate().skip(start) {
+		let c_width = char_width(*c);
+		if col + c_width > width {
+			clipped_right = true;
+			break;
+		}
+		cells.push((index, col, *c));
+		col += c_width;
+	}
+	let end_col = col;
+	if clipped_right && markers {
+		cells.retain(|(_, col, c)| col + char_width(*c) < width);
+	}
+	let cursor_col = if cursor < chars.len() {
+		cells.iter().find(|(index, _, _)| *index == cursor).map(|(_, col, _)| *col)
+	} else if !clipped_right && end_col < width {
+		Some(end_col)
+	} else {
+		None
+	};
+	let cells = cells.into_iter().map(|(_, col, c)| (col, c)).collect();
+	(cells, cursor_col, clipped_right && markers)
+}
+
+fn char_width(c: char) -> usize {
+	if c.is_control() { 1 } else { c.width().unwrap_or(1) }
+}
+
+pub(crate) fn key_actions(edit: &StringEdit, key: &KeyEvent) -> Vec<stringedit::Action> {
+	if let Some(edit_key) = key.to_edit_key() {
+		edit_key_actions(edit, edit_key)
+	} else if let Some(c) = key.to_text_char() {
+		vec![stringedit::Action::InsertChar(c)]
+	} else {
+		match key.code {
+			KeyCode::Backspace => vec![stringedit::Action::DeleteCharBeforeCursor],
+			KeyCode::Delete => vec![stringedit::Action::DeleteCharAtCursor],
+			_ => Vec::new(),
+		}
+	}
+}
+
+/// Expands an editing key into the primitive actions that perform it on `edit`.
+pub(crate) fn edit_key_actions(edit: &StringEdit, key: EditKey) -> Vec<stringedit::Action> {
+	let cursor = edit.cursor_index;
+	let len = edit.chars.len();
+	let (action, count) = match key {
+		EditKey::Home => (stringedit::Action::MoveCursorLeft, cursor),
+		EditKey::End => (stringedit::Action::MoveCursorRight, len - cursor),
+		EditKey::WordLeft => (stringedit::Action::MoveCursorLeft, cursor - word_start_before(&edit.chars, cursor)),
+		EditKey::WordRight => (stringedit::Action::MoveCursorRight, word_end_after(&edit.chars, cursor) - cursor),
+		EditKey::KillWordBefore => (stringedit::Action::DeleteCharBeforeCursor, cursor - word_start_before(&edit.chars, cursor)),
+		EditKey::KillToStart => (stringedit::Action::DeleteCharBeforeCursor, cursor),
+		EditKey::KillToEnd => (stringedit::Action::DeleteCharAtCursor, len - cursor),
+	};
+	(0..count).map(|_| action.clone()).collect()
+}
+
+pub(crate) fn paste_actions(text: &str) -> Vec<stringedit::Action> {
+	text.chars()
+		.map(|c| if c == '\n' || c == '\t' { ' ' } else { c })
+		.filter(|c| !c.is_control())
+		.map(stringedit::Action::InsertChar)
+		.collect()
+}
+
+fn word_start_before(chars: &[char], cursor: usize) -> usize {
+	let mut index = cursor;
+	while index > 0 && chars[index - 1].is_whitespace() {
+		index -= 1;
+	}
+	while index > 0 && !chars[index - 1].is_whitespace() {
+		index -= 1;
+	}
+	index
+}
+
+fn word_end_after(chars: &[char], cursor: usize) -> usize {
+	let mut index = cursor;
+	while index < chars.len() && chars[index].is_whitespace() {
+		index += 1;
+	}
+	while index < chars.len() && !chars[index].is_whitespace() {
+		index += 1;
+	}
+	index
+}
+
 #[cfg(test)]
 mod tests {
 	use stringedit::{StringEdit, Validity};
 
-	use crate::{layout, render, SenderLink, yard};
-	use crate::FillColor::Background;
-	use crate::FillGrade::{Focus, Select};
-	use crate::StrokeColor::{BodyOnBackground, EnabledOnBackground};
+	use crate::{EditKey, layout, render, SenderLink, yard};
+	use crate::yard::TextfieldModel;
+	use crate::yard::textfield::{edit_key_actions, paste_actions, scroll_window};
+	use crate::spot::spot_snapshot::assert_golden;
 	use crate::yui::layout::ActiveFocus;
+	use crate::yui_curses::keyboard::paste_text;
 
 	#[test]
 	fn layout_render() {
@@ -192,29 +405,76 @@ mod tests {
 		let (max_x, max_y) = (4, 3);
 		let layout = layout::run(max_y, max_x, &yard, &ActiveFocus::default());
 		let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), layout.active_focus.focus_id());
-		let fronts = spot_table.to_fronts();
-
-
-		let computed = fronts.iter().flatten()
-			.map(|front| (front.fill_color.clone(), front.stroke.clone(), front.fill_grade))
-			.collect::<Vec<_>>();
-		assert_eq!(computed[0..4], vec![
-			(Background, None, Select),
-			(Background, Some(('L'.into(), EnabledOnBackground)), Select),
-			(Background, Some(('a'.into(), EnabledOnBackground)), Select),
-			(Background, None, Select),
-		]);
-		assert_eq!(computed[4..8], vec![
-			(Background, None, Select),
-			(Background, Some(('s'.into(), BodyOnBackground)), Select),
-			(Background, None, Focus),
-			(Background, None, Select),
-		]);
-		assert_eq!(computed[8..12], vec![
-			(Background, Some(('_'.into(), EnabledOnBackground)), Select),
-			(Background, Some(('_'.into(), EnabledOnBackground)), Select),
-			(Background, Some(('_'.into(), EnabledOnBackground)), Select),
-			(Background, Some(('_'.into(), EnabledOnBackground)), Select),
-		]);
-	}
-}
\ No newline at end of file
+		assert_golden(&spot_table, "textfield_layout_render");
+	}
+
+	#[test]
+	fn mask_and_invalid_foot() {
+		let model = TextfieldModel::new(StringEdit::new("12a", 3, Validity::UnsignedInt))
+			.with_mask('*')
+			.with_max_length(4)
+			.with_error("Digits");
+		let yard = yard::textfield(300, "Pin", model, SenderLink::ignore());
+		let layout = layout::run(3, 14, &yard, &ActiveFocus::default());
+		let spot_table = render::run(&yard, layout.max_x, layout.max_y, layout.bounds_hold.clone(), layout.active_focus.focus_id());
+		assert_golden(&spot_table, "textfield_mask_invalid");
+	}
+
+	#[test]
+	fn max_length_refuses_inserts() {
+		let model = TextfieldModel::new(StringEdit::new("ab", 2, Validity::NotEmpty)).with_max_length(3);
+		let model = model.edit(stringedit::Action::InsertChar('c')).edit(stringedit::Action::InsertChar('d'));
+		assert_eq!(model.edit.chars.iter().collect::<String>(), "abc");
+		assert!(model.is_full());
+	}
+
+	#[test]
+	fn scroll_moves_only_to_keep_cursor_visible() {
+		let chars = "abcdefghij".chars().collect::<Vec<_>>();
+		let window = scroll_window(&chars, 9, 0, 5);
+		assert_eq!((window.start, window.cursor_col, window.clipped_right), (6, 4, false));
+		let window = scroll_window(&chars, 8, window.start, 5);
+		assert_eq!((window.start, window.cursor_col), (6, 3));
+		let window = scroll_window(&chars, 5, window.start, 5);
+		assert_eq!((window.start, window.cursor_col, window.clipped_right), (5, 1, true));
+		let wide = "日本語です".chars().collect::<Vec<_>>();
+		let window = scroll_window(&wide, 0, 0, 5);
+		assert_eq!(window.cells, vec![(0, '日'), (2, '本')]);
+		assert!(window.clipped_right);
+	}
+
+	#[test]
+	fn window_start_persists_across_edits() {
+		let start = |model: &TextfieldModel| {
+			let yard = yard::textfield(300, "Label", model.clone(), SenderLink::ignore());
+			layout::run(3, 11, &yard, &ActiveFocus::default());
+			model.window_start()
+		};
+		let model = TextfieldModel::new(StringEdit::new("abcdefghijklmnopqrst", 20, Validity::NotEmpty));
+		assert_eq!(start(&model), 13);
+		let model = (0..5).fold(model, |model, _| model.edit(stringedit::Action::MoveCursorLeft));
+		assert_eq!(start(&model), 13);
+		let model = (0..4).fold(model, |model, _| model.edit(stringedit::Action::MoveCursorLeft));
+		assert_eq!(start(&model), 11);
+	}
+
+	#[test]
+	fn edit_keys() {
+		let edit = StringEdit::new("one two three", 9, Validity::NotEmpty);
+		let apply = |key| edit_key_actions(&edit, key).into_iter().fold(edit.clone(), |edit, action| edit.edit(action));
+		assert_eq!(apply(EditKey::Home).cursor_index, 0);
+		assert_eq!(apply(EditKey::End).cursor_index, 13);
+		assert_eq!(apply(EditKey::WordLeft).cursor_index, 8);
+		assert_eq!(apply(EditKey::WordRight).cursor_index, 13);
+		assert_eq!(apply(EditKey::KillWordBefore).chars.iter().collect::<String>(), "one two hree");
+		assert_eq!(apply(EditKey::KillToStart).chars.iter().collect::<String>(), "hree");
+		assert_eq!(apply(EditKey::KillToEnd).chars.iter().collect::<String>(), "one two t");
+	}
+
+	#[test]
+	fn pasted_line_breaks_become_spaces() {
+		let edit = StringEdit::empty(Validity::NotEmpty);
+		let pasted = paste_actions(&paste_text(b"a\rb\r\nc")).into_iter().fold(edit, |edit, action| edit.edit(action));
+		assert_eq!(pasted.chars.iter().collect::<String>(), "a b c");
+	}
+}
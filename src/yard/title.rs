@@ -1,10 +1,10 @@
-use crate::{ArcYard, Cling, Confine, Pack, yard};
-use crate::palette::StrokeColor;
+use crate::{ArcYard, Cling, Confine, yard};
+use crate::palette::{StrokeColor, StrokeStyle};
 
 pub fn title<T: AsRef<str>>(text: T, color: StrokeColor, cling: Cling) -> ArcYard {
-	let text = text.as_ref();
-	let length = text.chars().count();
-	let title = yard::label(text, color, cling);
-	let underline = yard::glyph(color, || '=').confine(length as i32, 1, cling);
-	title.pack_bottom(1, underline).confine_height(2, Cling::Top)
-}
\ No newline at end of file
+	styled_title(text, color, StrokeStyle::PLAIN, cling)
+}
+
+pub fn styled_title<T: AsRef<str>>(text: T, color: StrokeColor, style: StrokeStyle, cling: Cling) -> ArcYard {
+	yard::styled_label(text, color, style | StrokeStyle::UNDERLINE, cling).confine_height(1, Cling::Top)
+}
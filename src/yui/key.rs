@@ -0,0 +1,114 @@
+use std::fmt;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum KeyCode {
+	Char(char),
+	Enter,
+	Escape,
+	Backspace,
+	Delete,
+	Insert,
+	Tab,
+	BackTab,
+	Up,
+	Down,
+	Left,
+	Right,
+	Home,
+	End,
+	PageUp,
+	PageDown,
+	F(u8),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct KeyModifiers {
+	pub ctrl: bool,
+	pub alt: bool,
+	pub shift: bool,
+}
+
+impl KeyModifiers {
+	pub const NONE: KeyModifiers = KeyModifiers { ctrl: false, alt: false, shift: false };
+	pub const CTRL: KeyModifiers = KeyModifiers { ctrl: true, alt: false, shift: false };
+	pub const ALT: KeyModifiers = KeyModifiers { ctrl: false, alt: true, shift: false };
+	pub const SHIFT: KeyModifiers = KeyModifiers { ctrl: false, alt: false, shift: true };
+
+	pub fn is_empty(&self) -> bool { *self == KeyModifiers::NONE }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct KeyEvent {
+	pub code: KeyCode,
+	pub modifiers: KeyModifiers,
+}
+
+impl KeyEvent {
+	pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self { KeyEvent { code, modifiers } }
+	pub fn plain(code: KeyCode) -> Self { KeyEvent { code, modifiers: KeyModifiers::NONE } }
+	pub fn char(c: char) -> Self { Self::plain(KeyCode::Char(c)) }
+	pub fn ctrl(c: char) -> Self { KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::CTRL } }
+	pub fn alt(c: char) -> Self { KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::ALT } }
+
+	/// The printable character this key inserts, if any.
+	pub fn to_text_char(&self) -> Option<char> {
+		match self.code {
+			KeyCode::Char(c) if !self.modifiers.ctrl && !self.modifiers.alt && !c.is_control() => Some(c),
+			_ => None,
+		}
+	}
+
+	/// True for the keys that activate a submit focus.
+	pub fn is_submit(&self) -> bool {
+		self.modifiers.is_empty() && (self.code == KeyCode::Enter || self.code == KeyCode::Char(' '))
+	}
+
+	/// True for the keys an edit focus consumes.
+	pub fn is_edit(&self) -> bool {
+		self.to_text_char().is_some() || self.to_edit_key().is_some() || (self.modifiers.is_empty() && (self.code == KeyCode::Backspace || self.code == KeyCode::Delete))
+	}
+
+	pub fn to_edit_key(&self) -> Option<EditKey> {
+		let KeyModifiers { ctrl, alt, .. } = self.modifiers;
+		match self.code {
+			KeyCode::Home => Some(EditKey::Home),
+			KeyCode::End => Some(EditKey::End),
+			KeyCode::Left if ctrl => Some(EditKey::WordLeft),
+			KeyCode::Right if ctrl => Some(EditKey::WordRight),
+			KeyCode::Char('a') if ctrl => Some(EditKey::Home),
+			KeyCode::Char('e') if ctrl => Some(EditKey::End),
+			KeyCode::Char('w') if ctrl => Some(EditKey::KillWordBefore),
+			KeyCode::Char('u') if ctrl => Some(EditKey::KillToStart),
+			KeyCode::Char('k') if ctrl => Some(EditKey::KillToEnd),
+			KeyCode::Char('b') if alt => Some(EditKey::WordLeft),
+			KeyCode::Char('f') if alt => Some(EditKey::WordRight),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for KeyEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.modifiers.ctrl { f.write_str("Ctrl-")?; }
+		if self.modifiers.alt { f.write_str("Alt-")?; }
+		if self.modifiers.shift { f.write_str("Shift-")?; }
+		match self.code {
+			KeyCode::Char(' ') => f.write_str("Space"),
+			KeyCode::Char(c) if self.modifiers.ctrl || self.modifiers.alt => write!(f, "{}", c.to_ascii_uppercase()),
+			KeyCode::Char(c) => write!(f, "{}", c),
+			KeyCode::F(n) => write!(f, "F{}", n),
+			code => write!(f, "{:?}", code),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EditKey {
+	Home,
+	End,
+	WordLeft,
+	WordRight,
+	KillWordBefore,
+	KillToStart,
+	KillToEnd,
+}
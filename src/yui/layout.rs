@@ -2,19 +2,23 @@ use std::fmt::Debug;
 use std::ops::Deref;
 use std::rc::Rc;
 
+use crate::Link;
 use crate::core::bounds::Bounds;
-use crate::yui::{Focus, FocusMotion, FocusMotionFuture, FocusType};
+use crate::yard::model::ScrollAction;
+use crate::yui::{Focus, FocusMotion, FocusMotionFuture, FocusType, KeyEvent};
 
 #[derive(Debug, Clone)]
 pub struct ActiveFocus {
 	pub focus: Option<Rc<Focus>>,
 	pub peers: Vec<Rc<Focus>>,
 	pub rear_z: i32,
+	/// The focus under the pointer, or 0. Hovering does not move focus.
+	pub hover_id: i32,
 }
 
 impl Default for ActiveFocus {
 	fn default() -> Self {
-		ActiveFocus { focus: None, peers: Vec::new(), rear_z: 0 }
+		ActiveFocus { focus: None, peers: Vec::new(), rear_z: 0, hover_id: 0 }
 	}
 }
 
@@ -48,6 +52,14 @@ impl ActiveFocus {
 		};
 		self.focus = None;
 	}
+	pub fn with_order_prefix(&self, prefix: usize) -> Self {
+		ActiveFocus {
+			focus: self.focus.as_ref().map(|focus| Rc::new(focus.with_order_prefix(prefix))),
+			peers: self.peers.iter().map(|focus| Rc::new(focus.with_order_prefix(prefix))).collect(),
+			rear_z: self.rear_z,
+			hover_id: self.hover_id,
+		}
+	}
 	pub fn insert_seam(&mut self, from: &Self, z: i32, left: i32, top: i32) {
 		let mut insert_foci
 			= from.to_foci()
@@ -83,15 +95,68 @@ impl ActiveFocus {
 		})
 	}
 
-	pub fn insert_space(&self, refresh: impl Fn() + Send + 'static) {
+	pub fn insert_key(&self, key: KeyEvent, refresh: impl Fn() + Send + 'static) {
 		if let Some(ref focus) = self.focus {
-			focus.insert_space(refresh);
+			focus.insert_key(key, refresh);
 		}
 	}
 
-	pub fn insert_char(&self, char: char, refresh: impl Fn() + Send + 'static) {
+	pub fn accepts_key(&self, key: &KeyEvent) -> bool {
+		self.focus.as_ref().map(|focus| focus.accepts_key(key)).unwrap_or(false)
+	}
+
+	pub fn paste(&self, text: String, refresh: impl Fn() + Send + 'static) {
 		if let Some(ref focus) = self.focus {
-			focus.insert_char(char, refresh);
+			focus.paste(text, refresh);
+		}
+	}
+
+	pub fn focus_at(&self, row: i32, col: i32) -> Option<Rc<Focus>> {
+		self.to_foci()
+			.into_iter()
+			.filter(|focus| focus.is_in_range(self.rear_z) && focus.bounds.intersects(row, col))
+			.min_by_key(|focus| (focus.bounds.z, focus.bounds.width() * focus.bounds.height()))
+	}
+
+	pub fn move_to(&self, yard_id: i32) -> ActiveFocus {
+		let (mut targets, peers): (Vec<Rc<Focus>>, Vec<Rc<Focus>>)
+			= self.to_foci()
+			.into_iter()
+			.partition(|it| it.yard_id == yard_id);
+		if targets.is_empty() {
+			self.to_owned()
+		} else {
+			ActiveFocus {
+				focus: Some(targets.remove(0)),
+				peers,
+				rear_z: self.rear_z,
+				hover_id: self.hover_id,
+			}
+		}
+	}
+
+	pub fn click(&self, row: i32, col: i32, refresh: impl Fn() + Send + 'static) -> ActiveFocus {
+		if let Some(target) = self.focus_at(row, col) {
+			target.go(refresh);
+			self.move_to(target.yard_id)
+		} else {
+			self.to_owned()
+		}
+	}
+
+	pub fn hover(&self, row: i32, col: i32) -> ActiveFocus {
+		let hover_id = self.focus_at(row, col).map(|target| target.yard_id).unwrap_or(0);
+		ActiveFocus { hover_id, ..self.to_owned() }
+	}
+
+	/// Scrolls the innermost scrollable yard under the pointer.
+	pub fn wheel(&self, row: i32, col: i32, action: ScrollAction) {
+		let target = self.to_foci()
+			.into_iter()
+			.filter(|focus| focus.scroll_link.is_some() && focus.is_in_range(self.rear_z) && focus.bounds.intersects(row, col))
+			.min_by_key(|focus| (focus.bounds.z, focus.bounds.width() * focus.bounds.height()));
+		if let Some(scroll_link) = target.as_ref().and_then(|focus| focus.scroll_link.as_ref()) {
+			scroll_link.send(action);
 		}
 	}
 
@@ -150,6 +215,37 @@ impl ActiveFocus {
 		}
 	}
 
+	pub fn move_next(&self) -> ActiveFocus {
+		if self.send_motion(FocusMotion::Next) == FocusMotionFuture::Default {
+			self.step_tab_order(true)
+		} else {
+			self.to_owned()
+		}
+	}
+
+	pub fn move_previous(&self) -> ActiveFocus {
+		if self.send_motion(FocusMotion::Previous) == FocusMotionFuture::Default {
+			self.step_tab_order(false)
+		} else {
+			self.to_owned()
+		}
+	}
+
+	fn step_tab_order(&self, forward: bool) -> ActiveFocus {
+		let mut foci = self.to_foci().into_iter().filter(|it| it.is_in_range(self.rear_z)).collect::<Vec<_>>();
+		if foci.is_empty() {
+			return self.to_owned();
+		}
+		foci.sort_by_key(|it| it.tab_key());
+		let count = foci.len();
+		let position = self.focus.as_ref().and_then(|focus| foci.iter().position(|it| it.yard_id == focus.yard_id));
+		let next_index = match position {
+			None => if forward { 0 } else { count - 1 },
+			Some(index) => if forward { (index + 1) % count } else { (index + count - 1) % count },
+		};
+		self.move_to(foci[next_index].yard_id)
+	}
+
 	fn next_focus(
 		&self,
 		include_bounds: impl Fn(&Bounds, &Bounds) -> bool,
@@ -172,6 +268,7 @@ impl ActiveFocus {
 					focus: Some(next_focus),
 					peers: next_peers,
 					rear_z: self.rear_z,
+					hover_id: self.hover_id,
 				}
 			}
 		} else {
@@ -179,3 +276,73 @@ impl ActiveFocus {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+	use std::sync::Arc;
+	use std::sync::mpsc::channel;
+
+	use crate::{Bounds, Focus, FocusType, SenderLink};
+	use crate::yard::model::ScrollAction;
+	use crate::yui::layout::ActiveFocus;
+
+	fn focus(yard_id: i32, bounds: Bounds) -> Rc<Focus> {
+		ordered_focus(yard_id, bounds, vec![yard_id as usize], None)
+	}
+
+	fn ordered_focus(yard_id: i32, bounds: Bounds, order: Vec<usize>, tab_index: Option<i32>) -> Rc<Focus> {
+		Rc::new(Focus { yard_id, focus_type: FocusType::Submit, bounds, priority: 0, tab_index, order, takes_enter: false, scroll_link: None, action_block: Arc::new(|_| {}) })
+	}
+
+	#[test]
+	fn next_previous_follow_tab_order() {
+		let bounds = Bounds::new(1, 1);
+		let first = ordered_focus(1, bounds, vec![0], None);
+		let nested = ordered_focus(2, bounds, vec![1, 0], None);
+		let last = ordered_focus(3, bounds, vec![2], None);
+		let jump = ordered_focus(4, bounds, vec![3], Some(1));
+		let active_focus = ActiveFocus { focus: Some(first), peers: vec![last, jump, nested], rear_z: i32::MAX, hover_id: 0 };
+		let ids = (0..4).scan(active_focus.clone(), |active, _| {
+			*active = active.move_next();
+			Some(active.focus_id())
+		}).collect::<Vec<_>>();
+		assert_eq!(ids, vec![2, 3, 4, 1]);
+		assert_eq!(active_focus.move_previous().focus_id(), 4);
+	}
+
+	#[test]
+	fn focus_at_picks_nearest_in_range() {
+		let back = focus(1, Bounds::new(4, 4));
+		let front = focus(2, Bounds::new(2, 2).with_z(-1));
+		let dialog = focus(3, Bounds::new(1, 1).with_z(-5));
+		let active_focus = ActiveFocus { focus: Some(back), peers: vec![front, dialog], rear_z: -1, hover_id: 0 };
+		assert_eq!(active_focus.focus_at(0, 0).map(|it| it.yard_id), Some(3));
+		assert_eq!(active_focus.focus_at(1, 1).map(|it| it.yard_id), Some(2));
+		assert!(active_focus.focus_at(3, 3).is_none());
+		let moved = active_focus.click(1, 1, || {});
+		assert_eq!(moved.focus_id(), 2);
+	}
+
+	#[test]
+	fn hover_marks_focus_without_moving() {
+		let back = focus(1, Bounds::new(4, 4));
+		let front = focus(2, Bounds::new(2, 2).with_z(-1));
+		let active_focus = ActiveFocus { focus: Some(back), peers: vec![front], rear_z: i32::MAX, hover_id: 0 };
+		let hovered = active_focus.hover(1, 1);
+		assert_eq!((hovered.focus_id(), hovered.hover_id), (1, 2));
+		assert_eq!(hovered.move_to(2).hover_id, 2);
+		assert_eq!(hovered.hover(9, 9).hover_id, 0);
+	}
+
+	#[test]
+	fn wheel_scrolls_list_under_editor() {
+		let (scroll_tx, scroll_rx) = channel();
+		let list = Rc::new(Focus { scroll_link: Some(SenderLink::new(scroll_tx).to_sync()), ..(*focus(1, Bounds::new(4, 4))).clone() });
+		let editor = focus(2, Bounds::new(2, 2).with_z(-1));
+		let active_focus = ActiveFocus { focus: Some(editor), peers: vec![list], rear_z: i32::MAX, hover_id: 0 };
+		active_focus.wheel(1, 1, ScrollAction::Down);
+		assert!(matches!(scroll_rx.try_recv(), Ok(ScrollAction::Down)));
+		assert_eq!(active_focus.focus_id(), 2);
+	}
+}
@@ -4,21 +4,27 @@ use std::sync::Arc;
 
 pub use stringedit::{Action as StringEditAction, StringEdit, Validity as ValidIf};
 
+pub use key::*;
 pub use multi_layout::*;
 
 use crate::core::bounds::Bounds;
+use crate::SyncLink;
 use crate::yard::ArcYard;
+use crate::yard::model::ScrollAction;
 
 pub mod layout;
 pub mod pad;
 pub mod place;
 pub mod confine;
+pub mod tab_index;
+pub mod key;
 mod multi_layout;
 pub mod prelude;
 
 pub enum FocusAction {
 	Go,
-	Change(char),
+	Key(KeyEvent),
+	Paste(String),
 }
 
 #[derive(Clone)]
@@ -27,6 +33,12 @@ pub struct Focus {
 	pub focus_type: FocusType,
 	pub bounds: Bounds,
 	pub priority: u32,
+	pub tab_index: Option<i32>,
+	pub order: Vec<usize>,
+	/// Lets an edit focus take Enter, as multi-line edits do.
+	pub takes_enter: bool,
+	/// Receives wheel scrolls over the focus bounds.
+	pub scroll_link: Option<SyncLink<ScrollAction>>,
 	pub action_block: Arc<dyn Fn(&FocusActionContext) + Send + Sync>,
 }
 
@@ -35,6 +47,8 @@ impl Debug for Focus {
 		f.debug_struct("Focus")
 			.field("yard", &self.yard_id)
 			.field("priority", &self.priority)
+			.field("tab_index", &self.tab_index)
+			.field("order", &self.order)
 			.field("bounds", &self.bounds)
 			.finish()
 	}
@@ -52,43 +66,71 @@ impl Focus {
 		focus
 	}
 
+	pub fn with_order_prefix(&self, prefix: usize) -> Self {
+		let mut focus = self.clone();
+		focus.order.insert(0, prefix);
+		focus
+	}
+	pub fn tab_key(&self) -> (bool, i32, Vec<usize>) {
+		(self.tab_index.is_none(), self.tab_index.unwrap_or(0), self.order.clone())
+	}
+
 	pub fn is_in_range(&self, focus_max: i32) -> bool {
 		self.bounds.z <= focus_max
 	}
 
-	pub fn insert_char(&self, char: char, refresh: impl Fn() + Send + 'static) {
+	pub fn is_edit(&self) -> bool {
 		match self.focus_type {
-			FocusType::Submit | FocusType::CompositeSubmit(_) => {}
-			FocusType::Edit(_) => {
+			FocusType::Edit(_) => true,
+			FocusType::Submit | FocusType::CompositeSubmit(_) => false,
+		}
+	}
+
+	pub fn go(&self, refresh: impl Fn() + Send + 'static) {
+		match self.focus_type {
+			FocusType::Edit(_) => {}
+			FocusType::Submit | FocusType::CompositeSubmit(_) => {
 				let action_block = self.action_block.clone();
-				thread::Builder::new().name("Focus::insert_char".to_string()).spawn(move || {
+				thread::Builder::new().name("Focus::go".to_string()).spawn(move || {
 					let ctx = FocusActionContext {
-						action: FocusAction::Change(char),
+						action: FocusAction::Go,
 						refresh: Box::new(refresh),
 					};
 					action_block(&ctx);
 				}).expect("spawn");
 			}
-		};
+		}
+	}
+
+	pub fn insert_key(&self, key: KeyEvent, refresh: impl Fn() + Send + 'static) {
+		match self.focus_type {
+			FocusType::Edit(_) => self.send_edit(FocusAction::Key(key), refresh),
+			FocusType::Submit | FocusType::CompositeSubmit(_) => if key.is_submit() {
+				self.go(refresh)
+			},
+		}
 	}
 
-	pub fn insert_space(&self, refresh: impl Fn() + Send + 'static) {
+	/// True if the focus handles the key itself rather than leaving it to the keymap.
+	pub fn accepts_key(&self, key: &KeyEvent) -> bool {
 		match self.focus_type {
+			FocusType::Edit(_) => key.is_edit() || (self.takes_enter && *key == KeyEvent::plain(KeyCode::Enter)),
+			FocusType::Submit | FocusType::CompositeSubmit(_) => key.is_submit(),
+		}
+	}
+
+	pub fn paste(&self, text: String, refresh: impl Fn() + Send + 'static) {
+		self.send_edit(FocusAction::Paste(text), refresh);
+	}
+
+	fn send_edit(&self, action: FocusAction, refresh: impl Fn() + Send + 'static) {
+		match self.focus_type {
+			FocusType::Submit | FocusType::CompositeSubmit(_) => {}
 			FocusType::Edit(_) => {
 				let action_block = self.action_block.clone();
-				thread::Builder::new().name("insert_space Edit".to_string()).spawn(move || {
-					let ctx = FocusActionContext {
-						action: FocusAction::Change(' '),
-						refresh: Box::new(refresh),
-					};
-					action_block(&ctx);
-				}).expect("spawn");
-			}
-			FocusType::Submit | FocusType::CompositeSubmit(_) => {
-				let action_block = self.action_block.clone();
-				thread::Builder::new().name("insert_space Submit".to_string()).spawn(move || {
+				thread::Builder::new().name("Focus::send_edit".to_string()).spawn(move || {
 					let ctx = FocusActionContext {
-						action: FocusAction::Go,
+						action,
 						refresh: Box::new(refresh),
 					};
 					action_block(&ctx);
@@ -126,6 +168,8 @@ pub enum FocusMotion {
 	Right,
 	Up,
 	Down,
+	Next,
+	Previous,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -160,6 +204,10 @@ pub trait Pack {
 	fn pack_right(self, cols: i32, right_yard: ArcYard) -> ArcYard;
 }
 
+pub trait TabIndex {
+	fn tab_index(self, index: i32) -> ArcYard;
+}
+
 pub trait Place {
 	fn place_center(self, width: i32) -> ArcYard;
 }
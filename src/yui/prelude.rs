@@ -1,3 +1,4 @@
-pub use crate::{AfterFlow, ArcYard, Before, Cling, Confine, Create, Flow, SyncLink, Pack, Padding, Spark, story, StringEdit, StringEditAction, ValidIf, yard};
-pub use crate::palette::{FillColor, FillGrade, StrokeColor};
+pub use crate::{AfterFlow, ArcYard, Before, Cling, Command, Confine, Create, Flow, SyncLink, Pack, Padding, Spark, story, StringEdit, StringEditAction, ValidIf, yard};
+pub use crate::animation::{Easing, Transition, Tween};
+pub use crate::palette::{FillColor, FillGrade, Status, StrokeColor, StrokeStyle};
 
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use crate::{Bounds, DrawPad};
+use crate::layout::LayoutContext;
+use crate::yard::{ArcYard, Yard};
+use crate::yui::TabIndex;
+
+impl TabIndex for ArcYard {
+	fn tab_index(self, index: i32) -> ArcYard {
+		Arc::new(TabIndexYard { id: rand::random(), index, yard: self })
+	}
+}
+
+struct TabIndexYard {
+	id: i32,
+	index: i32,
+	yard: ArcYard,
+}
+
+impl Yard for TabIndexYard {
+	fn id(&self) -> i32 { self.id }
+	fn type_desc(&self) -> &'static str { "TabIndex" }
+
+	fn layout(&self, ctx: &mut LayoutContext) -> usize {
+		let mut tab_ctx = ctx.with_tab_index(self.index);
+		self.yard.layout(&mut tab_ctx)
+	}
+
+	fn render(&self, _bounds: &Bounds, _focus_id: i32, _pad: &mut dyn DrawPad) -> Option<Vec<(ArcYard, Option<i32>)>> {
+		Some(vec![(self.yard.clone(), None)])
+	}
+}
@@ -1,29 +1,67 @@
 use std::error::Error;
+use std::panic;
+use std::sync::{Mutex, Once};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
 
-use ncurses::{endwin, has_colors, initscr, LcCategory, setlocale};
+use ncurses::{initscr, LcCategory, setlocale};
 
-use crate::{ArcYard, Link, ScreenAction, Sendable, Spark, Story, StoryVerse, Trigger};
+use crate::{ArcYard, Link, Persistence, Recorder, ScreenAction, Sendable, Spark, Story, StoryFailure, StoryVerse, Timers, Trigger};
 use crate::pod::Pod;
 use crate::pod_verse::PodVerse;
+use crate::keymap::Keymap;
 use crate::story_id::StoryId;
+use crate::theme::Theme;
 use crate::yard::YardPublisher;
 use crate::yui_curses::{screen, spawn_screen_feeder};
 use crate::yui_curses::keyboard::Keyboard;
 
+/// Startup settings for `run_spark_with`.
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+	pub theme: Theme,
+	pub persistence: Option<Persistence>,
+	pub recorder: Option<Recorder>,
+}
+
+impl RunOptions {
+	pub fn with_theme(self, theme: Theme) -> Self { RunOptions { theme, ..self } }
+	/// Lets stories that call `Create::restore` keep their state across runs.
+	pub fn with_persistence(self, persistence: Persistence) -> Self { RunOptions { persistence: Some(persistence), ..self } }
+	/// Logs the actions of stories that call `Create::record`.
+	pub fn with_recorder(self, recorder: Recorder) -> Self { RunOptions { recorder: Some(recorder), ..self } }
+}
+
+/// Runs the spark until it finishes. If a story fails, restores the terminal, prints
+/// the failure and exits with status 1.
 pub fn run_spark<S: Spark>(spark: S) where S: Send + 'static {
+	if let Err(failure) = run_spark_with(spark, RunOptions::default()) {
+		eprintln!("{}", failure);
+		std::process::exit(1);
+	}
+}
+
+/// Runs the spark until it finishes or one of its stories fails.
+pub fn run_spark_with<S: Spark>(spark: S, options: RunOptions) -> Result<(), StoryFailure> where S: Send + 'static {
 	let main_story_id = StoryId::new(0);
-	let (story_verse, _) = StoryVerse::build(spark, main_story_id);
+	let (story_verse, _) = StoryVerse::build_with(spark, main_story_id, options.persistence.clone(), options.recorder.clone(), Timers::shared());
 	let pod_verse = PodVerse::build(&story_verse);
-	run_pod_verse(&pod_verse);
+	run_pod_verse_with(&pod_verse, options)
+}
+
+/// Runs the pod verse until it finishes or one of its stories fails.
+pub fn run_pod_verse(pod_verse: &PodVerse) -> Result<(), StoryFailure> {
+	run_pod_verse_with(pod_verse, RunOptions::default())
 }
 
-pub fn run_pod_verse(pod_verse: &PodVerse) {
+fn run_pod_verse_with(pod_verse: &PodVerse, options: RunOptions) -> Result<(), StoryFailure> {
+	let failures = pod_verse.start_failures();
 	let console = Console::connect_pod_verse(pod_verse);
+	console.set_theme(options.theme);
 	let (done_tx, done_rx) = channel();
-	pod_verse.set_done_trigger(done_tx);
-	Keyboard::read_blocking(console.screen_link.clone(), done_rx);
+	pod_verse.set_done_trigger(done_tx.clone());
+	console.read_keys(done_tx, done_rx, Some(pod_verse.keymap().clone()), Some(failures))
 }
 
 pub struct Console {
@@ -39,12 +77,9 @@ impl Console {
 	}
 	fn init_connect() {
 		setlocale(LcCategory::all, "en_US.UTF-8");
+		hold_panic_messages();
+		CURSES_ACTIVE.store(true, Ordering::SeqCst);
 		initscr();
-		if !has_colors() {
-			endwin();
-			println!("Your terminal does not support color");
-			std::process::exit(1);
-		}
 	}
 	fn finish_connect(screen_link: Sender<ScreenAction>) -> Console {
 		let refresh_trigger = ScreenAction::ResizeRefresh.into_trigger(&screen_link);
@@ -52,17 +87,45 @@ impl Console {
 		Console { screen_link, refresh_trigger }
 	}
 	pub fn refresh_trigger(&self) -> &Trigger { &self.refresh_trigger }
+	/// Redraws the screen with the theme's colors.
+	pub fn set_theme(&self, theme: Theme) {
+		self.screen_link.send(ScreenAction::SetTheme(theme)).ok();
+	}
 	pub fn run(&self, yard_source: Receiver<Option<ArcYard>>) {
 		let (done_tx, done_rx) = channel();
-		spawn_screen_feeder(yard_source, done_tx, &self.screen_link);
-		Keyboard::read_blocking(self.screen_link.clone(), done_rx);
+		spawn_screen_feeder(yard_source, done_tx.clone(), &self.screen_link);
+		self.read_keys(done_tx, done_rx, None, None).ok();
+	}
+	/// Reads keys until done or until a story fails, then restores the terminal,
+	/// prints held panic messages and returns the failure.
+	fn read_keys(&self, done_tx: Sender<()>, done_rx: Receiver<()>, keymap: Option<Keymap>, failures: Option<Receiver<StoryFailure>>) -> Result<(), StoryFailure> {
+		let (failed_tx, failed_rx) = channel();
+		if let Some(failures) = failures {
+			thread::spawn(move || {
+				if let Ok(failure) = failures.recv() {
+					failed_tx.send(failure).ok();
+					done_tx.send(()).ok();
+				}
+			});
+		}
+		Keyboard::read_blocking(self.screen_link.clone(), done_rx, keymap);
+		CURSES_ACTIVE.store(false, Ordering::SeqCst);
+		for message in HELD_PANICS.lock().unwrap_or_else(|e| e.into_inner()).drain(..) {
+			eprintln!("{}", message);
+		}
+		match failed_rx.try_recv() {
+			Ok(failure) => Err(failure),
+			Err(_) => Ok(()),
+		}
 	}
 	pub fn run_story<Sp: Spark + 'static>(&self, story: Story<Sp>) -> Result<(), Box<dyn Error>> {
 		let yard_source = {
 			let (opt_yard_link, opt_yard_source) = channel();
 			let yard_source = story.subscribe()?;
 			thread::spawn(move || for yard in yard_source {
-				opt_yard_link.send(Some(yard)).expect("Send opt-yard");
+				if opt_yard_link.send(Some(yard)).is_err() {
+					break;
+				}
 			});
 			opt_yard_source
 		};
@@ -76,3 +139,21 @@ impl Console {
 	}
 }
 
+static CURSES_ACTIVE: AtomicBool = AtomicBool::new(false);
+static HELD_PANICS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Keeps panic messages off the curses screen. They print once the keyboard
+/// has restored the terminal.
+fn hold_panic_messages() {
+	static INSTALL: Once = Once::new();
+	INSTALL.call_once(|| {
+		let default_hook = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			if CURSES_ACTIVE.load(Ordering::SeqCst) {
+				HELD_PANICS.lock().unwrap_or_else(|e| e.into_inner()).push(info.to_string());
+			} else {
+				default_hook(info);
+			}
+		}));
+	});
+}
@@ -1,18 +1,25 @@
+use std::io::Write;
 use std::sync::mpsc::{Receiver, Sender};
 
 use ncurses::*;
 
+use crate::{KeyCode, KeyEvent, KeyModifiers};
+use crate::keymap::Keymap;
+use crate::yard::model::ScrollAction;
 use crate::yui_curses::screen::ScreenAction;
 
 pub(crate) struct Keyboard;
 
 impl Keyboard {
-	pub(crate) fn read_blocking(screen_tx: Sender<ScreenAction>, stop_rx: Receiver<()>) {
+	/// Reads keys until stopped. Ctrl-D closes the screen unless the keymap binds it.
+	pub(crate) fn read_blocking(screen_tx: Sender<ScreenAction>, stop_rx: Receiver<()>, keymap: Option<Keymap>) {
 		raw();
 		keypad(stdscr(), true);
 		cbreak();
 		noecho();
-		timeout(300);
+		timeout(READ_WAIT);
+		set_escdelay(ESCAPE_WAIT);
+		set_terminal_modes(true);
 		let mut done = false;
 		while !done {
 			let ch = getch();
@@ -22,30 +29,29 @@ impl Keyboard {
 				KEY_DOWN => Some(ScreenAction::FocusDown),
 				KEY_LEFT => Some(ScreenAction::FocusLeft),
 				KEY_RIGHT => Some(ScreenAction::FocusRight),
+				KEY_TAB => Some(ScreenAction::FocusNext),
+				KEY_BTAB => Some(ScreenAction::FocusPrevious),
 				KEY_RESIZE => Some(ScreenAction::ResizeRefresh),
-				KEY_EOT => Some(ScreenAction::Close),
-				KEY_SPACE => { Some(ScreenAction::Space) }
-				KEY_BACKSPACE => { Some(ScreenAction::AsciiChar('\x08')) }
-				KEY_DC => { Some(ScreenAction::AsciiChar('\x7f')) }
+				KEY_EOT => Some(ctrl_d_action(keymap.as_ref())),
+				KEY_SPACE => key(KeyEvent::char(' ')),
+				KEY_ENTER | KEY_LF | KEY_CR => key(KeyEvent::plain(KeyCode::Enter)),
+				KEY_BACKSPACE | KEY_BS => key(KeyEvent::plain(KeyCode::Backspace)),
+				KEY_DC => key(KeyEvent::plain(KeyCode::Delete)),
+				KEY_IC => key(KeyEvent::plain(KeyCode::Insert)),
+				KEY_HOME => key(KeyEvent::plain(KeyCode::Home)),
+				KEY_END => key(KeyEvent::plain(KeyCode::End)),
+				KEY_PPAGE => key(KeyEvent::plain(KeyCode::PageUp)),
+				KEY_NPAGE => key(KeyEvent::plain(KeyCode::PageDown)),
+				KEY_MOUSE => read_mouse(),
+				KEY_ESC => read_escape(),
+				_ if ch > KEY_F0 && ch <= KEY_F0 + 12 => key(KeyEvent::plain(KeyCode::F((ch - KEY_F0) as u8))),
 				_ => {
 					let name = keyname(ch).unwrap_or("".to_string());
-					let chars: Vec<char> = name.chars().collect();
-					match chars.len() {
-						1 => Some(ScreenAction::AsciiChar(chars[0])),
-						2 if chars[0] == '^' => {
-							match chars[1] {
-								'?' => Some(ScreenAction::AsciiChar('\x08')),
-								_ => {
-									info!("UNHANDLED CTRL-KEY: {}, {}", ch, name);
-									None
-								}
-							}
-						}
-						_ => {
-							info!("UNHANDLED KEY: {}, {}", ch, name);
-							None
-						}
+					let event = named_key(&name);
+					if event.is_none() {
+						info!("UNHANDLED KEY: {}, {}", ch, name);
 					}
+					event.map(ScreenAction::Key)
 				}
 			};
 			if let Some(action) = action {
@@ -53,13 +59,190 @@ impl Keyboard {
 					ScreenAction::Close => true,
 					_ => false
 				};
-				screen_tx.send(action).expect("send screen action")
+				if screen_tx.send(action).is_err() {
+					done = true;
+				}
 			}
 		}
+		set_terminal_modes(false);
 		use_default_colors();
 		endwin();
 	}
 }
 
+fn set_terminal_modes(enable: bool) {
+	if enable {
+		mousemask((ALL_MOUSE_EVENTS | REPORT_MOUSE_POSITION) as mmask_t, None);
+		mouseinterval(0);
+		// Ask xterm-compatible terminals to bracket pastes and report motion without a pressed button.
+		print!("\x1b[?2004h\x1b[?1003h");
+	} else {
+		mousemask(0, None);
+		print!("\x1b[?1003l\x1b[?2004l");
+	}
+	std::io::stdout().flush().ok();
+}
+
+fn key(event: KeyEvent) -> Option<ScreenAction> { Some(ScreenAction::Key(event)) }
+
+fn ctrl_d_action(keymap: Option<&Keymap>) -> ScreenAction {
+	let event = KeyEvent::ctrl('d');
+	match keymap {
+		Some(keymap) if keymap.active_bindings().iter().any(|it| it.key == event) => ScreenAction::Key(event),
+		_ => ScreenAction::Close,
+	}
+}
+
+fn named_key(name: &str) -> Option<KeyEvent> {
+	let chars: Vec<char> = name.chars().collect();
+	match chars.len() {
+		1 => Some(KeyEvent::char(chars[0])),
+		2 if chars[0] == '^' => match chars[1] {
+			'?' | 'H' => Some(KeyEvent::plain(KeyCode::Backspace)),
+			c if c.is_ascii_alphabetic() => Some(KeyEvent::ctrl(c.to_ascii_lowercase())),
+			_ => None,
+		},
+		_ => modified_key(name),
+	}
+}
+
+// Terminfo names modified cursor keys like "kLFT5", where the digit is
+// one more than a shift/alt/ctrl bit set.
+fn modified_key(name: &str) -> Option<KeyEvent> {
+	let digit_index = name.find(|c: char| c.is_ascii_digit())?;
+	let (base, digit) = name.split_at(digit_index);
+	let bits = digit.parse::<u8>().ok()?.checked_sub(1)?;
+	let code = match base {
+		"kUP" => KeyCode::Up,
+		"kDN" => KeyCode::Down,
+		"kLFT" => KeyCode::Left,
+		"kRIT" => KeyCode::Right,
+		"kHOM" => KeyCode::Home,
+		"kEND" => KeyCode::End,
+		_ => return None,
+	};
+	let modifiers = KeyModifiers { shift: bits & 1 != 0, alt: bits & 2 != 0, ctrl: bits & 4 != 0 };
+	Some(KeyEvent::new(code, modifiers))
+}
+
+const PASTE_START: &[u8] = b"200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+fn read_escape() -> Option<ScreenAction> {
+	timeout(ESCAPE_WAIT);
+	let first = getch();
+	let action = if first == ERR {
+		key(KeyEvent::plain(KeyCode::Escape))
+	} else if first == '[' as i32 {
+		read_bracket_sequence()
+	} else {
+		let name = keyname(first).unwrap_or("".to_string());
+		match named_key(&name) {
+			Some(KeyEvent { code: KeyCode::Char(c), modifiers }) if !modifiers.ctrl => key(KeyEvent::alt(c)),
+			_ => {
+				info!("UNHANDLED ALT-KEY: {}, {}", first, name);
+				None
+			}
+		}
+	};
+	timeout(READ_WAIT);
+	action
+}
+
+fn read_bracket_sequence() -> Option<ScreenAction> {
+	let mut sequence = Vec::new();
+	while sequence.len() < PASTE_START.len() {
+		let ch = getch();
+		if ch == ERR || ch > 0xff {
+			break;
+		}
+		sequence.push(ch as u8);
+		if !PASTE_START.starts_with(&sequence) {
+			break;
+		}
+	}
+	if sequence.as_slice() == PASTE_START {
+		timeout(READ_WAIT);
+		let mut bytes = Vec::new();
+		while !bytes.ends_with(PASTE_END) {
+			let ch = getch();
+			if ch == ERR {
+				break;
+			}
+			if ch <= 0xff {
+				bytes.push(ch as u8);
+			}
+		}
+		if bytes.ends_with(PASTE_END) {
+			bytes.truncate(bytes.len() - PASTE_END.len());
+		}
+		Some(ScreenAction::Paste(paste_text(&bytes)))
+	} else {
+		info!("UNHANDLED ESCAPE SEQUENCE: {:?}", sequence);
+		None
+	}
+}
+
+/// Decodes pasted bytes. Terminals send line breaks as carriage returns, so
+/// every `\r\n` and lone `\r` becomes `\n`.
+pub(crate) fn paste_text(bytes: &[u8]) -> String {
+	String::from_utf8_lossy(bytes).replace("\r\n", "\n").replace('\r', "\n")
+}
+
+fn read_mouse() -> Option<ScreenAction> {
+	let mut event = MEVENT { id: 0, x: 0, y: 0, z: 0, bstate: 0 };
+	if getmouse(&mut event) != OK {
+		return None;
+	}
+	let (x, y) = (event.x, event.y);
+	let is = |mask: i32| event.bstate & (mask as mmask_t) != 0;
+	if is(BUTTON1_PRESSED) || is(BUTTON1_CLICKED) {
+		Some(ScreenAction::Click { x, y })
+	} else if is(BUTTON4_PRESSED) {
+		Some(ScreenAction::Wheel { x, y, action: ScrollAction::Up })
+	} else if is(BUTTON5_PRESSED) {
+		Some(ScreenAction::Wheel { x, y, action: ScrollAction::Down })
+	} else if is(REPORT_MOUSE_POSITION) {
+		Some(ScreenAction::Hover { x, y })
+	} else {
+		None
+	}
+}
+
 pub(crate) const KEY_EOT: i32 = 4;
+pub(crate) const KEY_TAB: i32 = 9;
+pub(crate) const KEY_ESC: i32 = 27;
+pub(crate) const KEY_BS: i32 = 8;
+pub(crate) const KEY_LF: i32 = 10;
+pub(crate) const KEY_CR: i32 = 13;
+
+const READ_WAIT: i32 = 300;
+const ESCAPE_WAIT: i32 = 25;
 pub(crate) const KEY_SPACE: i32 = 32;
+
+#[cfg(test)]
+mod tests {
+	use crate::{KeyCode, KeyEvent, KeyModifiers};
+	use crate::keymap::Keymap;
+	use crate::yui_curses::keyboard::{ctrl_d_action, named_key};
+	use crate::yui_curses::screen::ScreenAction;
+
+	#[test]
+	fn key_names() {
+		assert_eq!(named_key("x"), Some(KeyEvent::char('x')));
+		assert_eq!(named_key("^W"), Some(KeyEvent::ctrl('w')));
+		assert_eq!(named_key("^?"), Some(KeyEvent::plain(KeyCode::Backspace)));
+		assert_eq!(named_key("kLFT5"), Some(KeyEvent::new(KeyCode::Left, KeyModifiers::CTRL)));
+		assert_eq!(named_key("kRIT3"), Some(KeyEvent::new(KeyCode::Right, KeyModifiers::ALT)));
+		assert_eq!(named_key("kUNKNOWN"), None);
+	}
+
+	#[test]
+	fn ctrl_d_closes_without_a_binding() {
+		assert!(matches!(ctrl_d_action(None), ScreenAction::Close));
+		let keymap = Keymap::new();
+		assert!(matches!(ctrl_d_action(Some(&keymap)), ScreenAction::Close));
+		keymap.bind_default(KeyEvent::ctrl('d'), "Quit", || {});
+		assert!(matches!(ctrl_d_action(Some(&keymap)), ScreenAction::Key(key) if key == KeyEvent::ctrl('d')));
+	}
+}
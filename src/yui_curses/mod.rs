@@ -2,13 +2,13 @@ use std::error::Error;
 use std::sync::mpsc::{Receiver, Sender};
 use std::thread;
 
-pub(crate) use screen::ScreenAction;
+pub use screen::ScreenAction;
 
 use crate::{Sendable, SenderLink, Trigger};
 use crate::yard::ArcYard;
 
 mod screen;
-mod keyboard;
+pub(crate) mod keyboard;
 pub mod spot;
 
 #[derive(Debug, Clone)]
@@ -37,7 +37,7 @@ fn spawn_screen_feeder(yard_source: Receiver<Option<ArcYard>>, done_trigger: Sen
 				break;
 			}
 		}
-		done_trigger.send(()).expect("send () to stop_tx");
+		done_trigger.send(()).ok();
 	}).expect("spawn");
 }
 
@@ -0,0 +1,97 @@
+use crate::palette::{FillColor, FillGrade, StrokeColor, StrokeStyle};
+use crate::spot::SpotFront;
+
+/// Adjacent changed cells in one row that share a drawing style.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SpotRun {
+	pub row: usize,
+	pub col: usize,
+	pub fronts: Vec<SpotFront>,
+}
+
+impl SpotRun {
+	pub fn text(&self) -> String {
+		self.fronts.iter().map(|front| match &front.stroke {
+			Some((glyph, _)) if front.stroke_style.contains(StrokeStyle::STRIKETHROUGH) => format!("{}\u{336}", glyph),
+			Some((glyph, _)) => glyph.to_string(),
+			None => " ".to_string(),
+		}).collect()
+	}
+}
+
+type SpotStyle = (Option<StrokeColor>, StrokeStyle, FillColor, FillGrade, u8);
+
+fn style(front: &SpotFront) -> SpotStyle {
+	(front.stroke.as_ref().map(|(_, color)| *color), front.stroke_style, front.fill_color, front.fill_grade, front.dark)
+}
+
+fn is_drawable(front: &SpotFront) -> bool {
+	match &front.stroke {
+		Some((glyph, _)) => !glyph.is_empty(),
+		None => true,
+	}
+}
+
+/// Finds the runs to draw to turn `before` into `after`. Every cell counts as
+/// changed when there is no `before` or its size differs.
+pub(crate) fn diff_runs(before: Option<&Vec<Vec<SpotFront>>>, after: &Vec<Vec<SpotFront>>) -> Vec<SpotRun> {
+	let before = before.filter(|before| {
+		before.len() == after.len() && before.iter().zip(after.iter()).all(|(a, b)| a.len() == b.len())
+	});
+	let mut runs = Vec::new();
+	for (row, fronts) in after.iter().enumerate() {
+		let mut run: Option<SpotRun> = None;
+		for (col, front) in fronts.iter().enumerate() {
+			let changed = match before {
+				None => true,
+				Some(before) => &before[row][col] != front,
+			};
+			if !changed || !is_drawable(front) {
+				if let Some(done) = run.take() {
+					runs.push(done);
+				}
+				continue;
+			}
+			match run.as_mut() {
+				Some(open) if style(&open.fronts[0]) == style(front) => open.fronts.push(front.clone()),
+				_ => {
+					if let Some(done) = run.take() {
+						runs.push(done);
+					}
+					run = Some(SpotRun { row, col, fronts: vec![front.clone()] });
+				}
+			}
+		}
+		if let Some(done) = run {
+			runs.push(done);
+		}
+	}
+	runs
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Bounds, DrawPad, FillColor, StrokeColor};
+	use crate::spot::spot_table::SpotTable;
+	use crate::yui_curses::screen::diff::diff_runs;
+
+	#[test]
+	fn only_changed_runs() {
+		let before = SpotTable::new(2, 6).to_fronts();
+		let mut spot_table = SpotTable::new(2, 6);
+		spot_table.glyph(&Bounds::new(6, 1).pad(1, 0, 0, 0), "abc", StrokeColor::BodyOnBackground);
+		spot_table.glyph(&Bounds::new(6, 1).pad(4, 0, 0, 0), "d", StrokeColor::CommentOnBackground);
+		let after = spot_table.to_fronts();
+		let runs = diff_runs(Some(&before), &after);
+		let runs = runs.iter().map(|run| (run.row, run.col, run.text())).collect::<Vec<_>>();
+		assert_eq!(runs, vec![(0, 1, "abc".to_string()), (0, 4, "d".to_string())]);
+
+		assert!(diff_runs(Some(&after), &after).is_empty());
+
+		let mut filled = SpotTable::new(2, 6);
+		filled.fill(&Bounds::new(6, 2), FillColor::Primary);
+		let full = diff_runs(Some(&before), &filled.to_fronts());
+		assert_eq!(full.iter().map(|run| run.fronts.len()).collect::<Vec<_>>(), vec![6, 6]);
+		assert_eq!(diff_runs(None, &after).len(), 5);
+	}
+}
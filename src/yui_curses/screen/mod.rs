@@ -8,9 +8,14 @@ use ncurses::*;
 use crate::palette::Palette;
 use crate::pod::Pod;
 use crate::pod_verse::PodVerse;
-use crate::Sendable;
-use crate::spot::spot_table::SpotTable;
+use crate::{KeyEvent, Sendable};
+use crate::spot::SpotFront;
+use crate::theme::Theme;
 use crate::yard::ArcYard;
+use crate::yard::model::ScrollAction;
+use crate::yui_curses::screen::diff::diff_runs;
+
+mod diff;
 
 #[derive(Clone)]
 pub enum ScreenAction {
@@ -20,9 +25,15 @@ pub enum ScreenAction {
 	FocusDown,
 	FocusLeft,
 	FocusRight,
-	Space,
+	FocusNext,
+	FocusPrevious,
 	SetYard(ArcYard),
-	AsciiChar(char),
+	Key(KeyEvent),
+	Paste(String),
+	Click { x: i32, y: i32 },
+	Hover { x: i32, y: i32 },
+	Wheel { x: i32, y: i32, action: ScrollAction },
+	SetTheme(Theme),
 }
 
 impl Sendable for ScreenAction {}
@@ -35,7 +46,7 @@ pub fn connect_pod_verse(pod_verse: PodVerse) -> Sender<ScreenAction> {
 		let screen_link = screen_link.clone();
 		thread::spawn(move || {
 			let pod = pod_verse.to_main_pod(ScreenAction::ResizeRefresh.into_trigger(&screen_link));
-			let mut state = ScreenState::init(Box::new(pod)).expect("ScreenState");
+			let mut state = ScreenState::init(Box::new(pod));
 			let mut delay_refresh = false;
 			loop {
 				let mut repeat_try = false;
@@ -100,7 +111,7 @@ pub fn connect(pod: impl Pod + Send + 'static) -> Sender<ScreenAction> {
 		let screen_link = screen_link.clone();
 		thread::Builder::new().name("CursesScreen::start".into()).spawn(move || {
 			pod.set_refresh_trigger(ScreenAction::ResizeRefresh.into_trigger(&screen_link));
-			let mut next = ScreenState::init(pod);
+			let mut next = Some(ScreenState::init(pod));
 			while let Some(state) = next {
 				let action = next_screen_action(&actions_source, &screen_link).ok();
 				next = action.map(|action| state.update(action)).flatten();
@@ -114,11 +125,13 @@ pub fn connect(pod: impl Pod + Send + 'static) -> Sender<ScreenAction> {
 struct ScreenState {
 	pod: Box<dyn Pod>,
 	width_height: (i32, i32),
+	palette: Palette,
+	drawn: Option<Vec<Vec<SpotFront>>>,
 }
 
 impl ScreenState {
-	fn init(pod: Box<dyn Pod>) -> Option<Self> {
-		Some(ScreenState { pod, width_height: (0, 0) })
+	fn init(pod: Box<dyn Pod>) -> Self {
+		ScreenState { pod, width_height: (0, 0), palette: Palette::new(Theme::default()), drawn: None }
 	}
 	fn update(mut self, action: ScreenAction) -> Option<Self> {
 		let mut stop = false;
@@ -128,21 +141,34 @@ impl ScreenState {
 				let width_height = width_height();
 				if self.width_height != width_height {
 					self.width_height = width_height;
+					self.drawn = None;
 					self.pod.set_width_height(width_height);
 				}
 				if let Some(rendering) = self.pod.spot_table() {
-					update_screen(&rendering);
+					let fronts = rendering.to_fronts();
+					update_screen(self.drawn.as_ref(), &fronts, &self.palette);
+					self.drawn = Some(fronts);
 				}
 			}
+			ScreenAction::SetTheme(theme) => {
+				self.palette.set_theme(theme);
+				self.drawn = None;
+				return self.update(ScreenAction::ResizeRefresh);
+			}
 			ScreenAction::SetYard(yard) => self.pod.set_yard(yard),
-			ScreenAction::Space => self.pod.insert_space(),
-			ScreenAction::AsciiChar(char) => self.pod.insert_char(char),
+			ScreenAction::Key(key) => self.pod.insert_key(key),
+			ScreenAction::Paste(text) => self.pod.paste(text),
 			ScreenAction::FocusUp => self.pod.focus_up(),
 			ScreenAction::FocusDown => self.pod.focus_down(),
 			ScreenAction::FocusLeft => self.pod.focus_left(),
 			ScreenAction::FocusRight => self.pod.focus_right(),
+			ScreenAction::FocusNext => self.pod.focus_next(),
+			ScreenAction::FocusPrevious => self.pod.focus_previous(),
+			ScreenAction::Click { x, y } => self.pod.click(x, y),
+			ScreenAction::Hover { x, y } => self.pod.hover(x, y),
+			ScreenAction::Wheel { x, y, action } => self.pod.wheel(x, y, action),
 		}
-		if stop { None } else { Some(ScreenState { pod: self.pod, width_height: self.width_height }) }
+		if stop { None } else { Some(self) }
 	}
 }
 
@@ -153,16 +179,19 @@ fn width_height() -> (i32, i32) {
 	(max_x, max_y)
 }
 
-fn update_screen(spot_table: &SpotTable) {
-	erase();
-	info!("Update Screen");
-	let palette = Palette::new();
-	spot_table.each(|y, x, front| {
-		if let Some((glyph, attr)) = palette.to_glyph_attr(front) {
+/// Draws the cells that differ from the previous frame, or everything after a resize.
+fn update_screen(drawn: Option<&Vec<Vec<SpotFront>>>, fronts: &Vec<Vec<SpotFront>>, palette: &Palette) {
+	if drawn.is_none() {
+		erase();
+	}
+	let runs = diff_runs(drawn, fronts);
+	info!("Update Screen: {} runs", runs.len());
+	for run in runs {
+		if let Some((_, attr)) = palette.to_glyph_attr(&run.fronts[0]) {
 			attrset(attr);
-			mvaddstr(y as i32, x as i32, glyph);
+			mvaddstr(run.row as i32, run.col as i32, &run.text());
 		}
-	});
+	}
 	refresh();
 }
 
@@ -180,7 +209,7 @@ fn next_screen_action(rx: &Receiver<ScreenAction>, tx: &Sender<ScreenAction>) ->
 					_ => {
 						let last = first;
 						first = second;
-						tx.send(last).expect("send last screen action");
+						tx.send(last).ok();
 						done_trying_second = true
 					}
 				},
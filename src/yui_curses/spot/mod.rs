@@ -1,13 +1,47 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
 use crate::{FillColor, FillGrade, StrokeColor};
+use crate::palette::StrokeStyle;
 
 pub mod spot_stack;
 pub mod spot_table;
 pub mod spot_field;
+pub mod spot_snapshot;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SpotFront {
 	pub fill_color: FillColor,
 	pub fill_grade: FillGrade,
 	pub stroke: Option<(String, StrokeColor)>,
-	pub dark: bool,
+	pub stroke_style: StrokeStyle,
+	/// Dark level from 0 to `DARK_LEVELS`.
+	pub dark: u8,
+}
+
+impl SpotFront {
+	/// True for the right half of a double-width glyph drawn in the cell to the left.
+	pub fn is_continuation(&self) -> bool {
+		match &self.stroke {
+			Some((glyph, _)) => glyph.is_empty(),
+			None => false,
+		}
+	}
+}
+
+/// Splits text into one entry per terminal cell. Each grapheme cluster starts a cell
+/// and a double-width cluster is followed by an empty continuation cell.
+pub(crate) fn glyph_cells(glyph: &str) -> Vec<String> {
+	let mut cells = Vec::new();
+	for grapheme in glyph.graphemes(true) {
+		cells.push(grapheme.to_string());
+		for _ in 1..grapheme_width(grapheme) {
+			cells.push(String::new());
+		}
+	}
+	cells
+}
+
+pub(crate) fn grapheme_width(grapheme: &str) -> usize {
+	UnicodeWidthStr::width(grapheme).max(1)
 }
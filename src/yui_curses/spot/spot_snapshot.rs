@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{FillColor, FillGrade, StrokeColor};
+use crate::palette::{DARK_LEVELS, StrokeStyle};
+use crate::spot::{glyph_cells, SpotFront};
+use crate::spot::spot_table::SpotTable;
+
+const FILLS: [(char, FillColor); 7] = [
+	('B', FillColor::Background),
+	('P', FillColor::Primary),
+	('S', FillColor::Side),
+	('E', FillColor::Error),
+	('W', FillColor::Warning),
+	('O', FillColor::Success),
+	('I', FillColor::Info),
+];
+
+const GRADES: [(char, FillGrade); 4] = [
+	('.', FillGrade::Plain),
+	('s', FillGrade::Select),
+	('f', FillGrade::Focus),
+	('p', FillGrade::Press),
+];
+
+const STROKES: [(char, StrokeColor); 22] = [
+	('a', StrokeColor::CommentOnBackground),
+	('b', StrokeColor::CommentOnSide),
+	('c', StrokeColor::BodyOnBackground),
+	('d', StrokeColor::BodyOnSide),
+	('e', StrokeColor::EnabledOnBackground),
+	('f', StrokeColor::EnabledOnPrimary),
+	('g', StrokeColor::BodyOnPrimary),
+	('h', StrokeColor::CommentOnPrimary),
+	('i', StrokeColor::ErrorOnBackground),
+	('j', StrokeColor::ErrorOnSide),
+	('k', StrokeColor::ErrorOnPrimary),
+	('l', StrokeColor::WarningOnBackground),
+	('m', StrokeColor::WarningOnSide),
+	('n', StrokeColor::WarningOnPrimary),
+	('o', StrokeColor::SuccessOnBackground),
+	('p', StrokeColor::SuccessOnSide),
+	('q', StrokeColor::SuccessOnPrimary),
+	('r', StrokeColor::InfoOnBackground),
+	('s', StrokeColor::InfoOnSide),
+	('t', StrokeColor::InfoOnPrimary),
+	('u', StrokeColor::BodyOnStatus),
+	('v', StrokeColor::CommentOnStatus),
+];
+
+const STYLE_CODES: &str = ".123456789abcdefghijklmnopqrstuv";
+
+fn styles() -> Vec<(char, StrokeStyle)> {
+	STYLE_CODES.chars().enumerate().map(|(bits, code)| (code, StrokeStyle::from_bits(bits as u8))).collect()
+}
+
+const NO_STROKE: char = '.';
+const DARKS: [(char, u8); 5] = [('.', 0), ('1', 1), ('2', 2), ('3', 3), ('#', DARK_LEVELS)];
+
+/// Text form of a spot table: a glyph grid followed by legend grids for
+/// fill color, fill grade, stroke color and dark, plus stroke style when any cell has one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotSnapshot {
+	pub fronts: Vec<Vec<SpotFront>>,
+}
+
+impl SpotSnapshot {
+	pub fn width_height(&self) -> (usize, usize) {
+		let height = self.fronts.len();
+		let width = self.fronts.first().map(|row| row.len()).unwrap_or(0);
+		(width, height)
+	}
+}
+
+impl From<&SpotTable> for SpotSnapshot {
+	fn from(spot_table: &SpotTable) -> Self {
+		SpotSnapshot { fronts: spot_table.to_fronts() }
+	}
+}
+
+impl fmt::Display for SpotSnapshot {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (width, height) = self.width_height();
+		writeln!(f, "{}x{}", width, height)?;
+		writeln!(f, "glyph")?;
+		for row in &self.fronts {
+			let glyphs = row.iter().map(|front| match &front.stroke {
+				Some((glyph, _)) => glyph.to_string(),
+				None => " ".to_string(),
+			}).collect::<String>();
+			writeln!(f, "|{}|", glyphs)?;
+		}
+		write_layer(f, &self.fronts, "fill", &FILLS, |front| Some(front.fill_color))?;
+		write_layer(f, &self.fronts, "grade", &GRADES, |front| Some(front.fill_grade))?;
+		write_layer(f, &self.fronts, "stroke", &STROKES, |front| front.stroke.as_ref().map(|(_, color)| *color))?;
+		write_layer(f, &self.fronts, "dark", &DARKS, |front| Some(front.dark))?;
+		if self.fronts.iter().flatten().any(|front| !front.stroke_style.is_plain()) {
+			write_layer(f, &self.fronts, "style", &styles(), |front| Some(front.stroke_style))?;
+		}
+		Ok(())
+	}
+}
+
+fn write_layer<T: Copy + PartialEq + fmt::Debug>(f: &mut fmt::Formatter<'_>, fronts: &Vec<Vec<SpotFront>>, name: &str, codes: &[(char, T)], value: impl Fn(&SpotFront) -> Option<T>) -> fmt::Result {
+	let code = |front: &SpotFront| match value(front) {
+		None => NO_STROKE,
+		Some(value) => codes.iter().find(|(_, it)| *it == value).map(|(code, _)| *code).expect("snapshot code"),
+	};
+	let mut used = fronts.iter().flatten().map(|front| code(front)).collect::<Vec<_>>();
+	used.sort();
+	used.dedup();
+	let legend = used.iter().map(|used| {
+		let name = codes.iter().find(|(code, _)| code == used).map(|(_, value)| format!("{:?}", value)).unwrap_or_else(|| "None".to_string());
+		format!(" {}={}", used, name)
+	}).collect::<String>();
+	writeln!(f, "{}{}", name, legend)?;
+	for row in fronts {
+		writeln!(f, "|{}|", row.iter().map(|front| code(front)).collect::<String>())?;
+	}
+	Ok(())
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SnapshotParseError(pub String);
+
+impl fmt::Display for SnapshotParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "snapshot: {}", self.0) }
+}
+
+impl std::error::Error for SnapshotParseError {}
+
+impl FromStr for SpotSnapshot {
+	type Err = SnapshotParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut lines = s.lines();
+		let size = lines.next().ok_or_else(|| error("missing size"))?;
+		let (width, height) = {
+			let mut parts = size.trim().split('x');
+			let width = parts.next().and_then(|it| it.parse::<usize>().ok()).ok_or_else(|| error("bad width"))?;
+			let height = parts.next().and_then(|it| it.parse::<usize>().ok()).ok_or_else(|| error("bad height"))?;
+			(width, height)
+		};
+		let mut layers: HashMap<String, (HashMap<char, String>, Vec<Vec<String>>)> = HashMap::new();
+		while let Some(header) = lines.next() {
+			if header.trim().is_empty() {
+				continue;
+			}
+			let mut words = header.split(' ');
+			let name = words.next().unwrap_or("").to_string();
+			let legend = words.filter_map(|word| {
+				let mut chars = word.chars();
+				let code = chars.next()?;
+				let rest = chars.as_str().strip_prefix('=')?;
+				Some((code, rest.to_string()))
+			}).collect::<HashMap<_, _>>();
+			let mut rows = Vec::new();
+			for _ in 0..height {
+				let line = lines.next().ok_or_else(|| error(&format!("{} is missing rows", name)))?;
+				let row = line.strip_prefix('|').and_then(|it| it.strip_suffix('|')).ok_or_else(|| error(&format!("{} row is not fenced", name)))?;
+				let row = glyph_cells(row);
+				if row.len() != width {
+					return Err(error(&format!("{} row has width {}", name, row.len())));
+				}
+				rows.push(row);
+			}
+			layers.insert(name, (legend, rows));
+		}
+		let glyphs = &layers.get("glyph").ok_or_else(|| error("missing glyph"))?.1;
+		let fills = read_layer(&layers, "fill", &FILLS)?;
+		let grades = read_layer(&layers, "grade", &GRADES)?;
+		let strokes = read_layer(&layers, "stroke", &STROKES)?;
+		let darks = read_layer(&layers, "dark", &DARKS)?;
+		let stroke_styles = match layers.contains_key("style") {
+			true => Some(read_layer(&layers, "style", &styles())?),
+			false => None,
+		};
+		let fronts = (0..height).map(|y| {
+			(0..width).map(|x| {
+				SpotFront {
+					fill_color: fills[y][x].unwrap_or(FillColor::Background),
+					fill_grade: grades[y][x].unwrap_or(FillGrade::Plain),
+					stroke: strokes[y][x].map(|color| (glyphs[y][x].to_string(), color)),
+					stroke_style: stroke_styles.as_ref().and_then(|it| it[y][x]).unwrap_or(StrokeStyle::PLAIN),
+					dark: darks[y][x].unwrap_or(0),
+				}
+			}).collect::<Vec<_>>()
+		}).collect::<Vec<_>>();
+		Ok(SpotSnapshot { fronts })
+	}
+}
+
+fn read_layer<T: Copy + fmt::Debug>(layers: &HashMap<String, (HashMap<char, String>, Vec<Vec<String>>)>, name: &str, codes: &[(char, T)]) -> Result<Vec<Vec<Option<T>>>, SnapshotParseError> {
+	let (legend, rows) = layers.get(name).ok_or_else(|| error(&format!("missing {}", name)))?;
+	let by_name = codes.iter().map(|(_, value)| (format!("{:?}", value), *value)).collect::<HashMap<_, _>>();
+	rows.iter().map(|row| {
+		row.iter().map(|code| {
+			let code = code.chars().next().unwrap_or(' ');
+			let value_name = legend.get(&code).ok_or_else(|| error(&format!("{} has no legend for '{}'", name, code)))?;
+			if value_name == "None" {
+				Ok(None)
+			} else {
+				by_name.get(value_name).map(|value| Some(*value)).ok_or_else(|| error(&format!("{} has unknown value {}", name, value_name)))
+			}
+		}).collect::<Result<Vec<_>, _>>()
+	}).collect()
+}
+
+fn error(message: &str) -> SnapshotParseError { SnapshotParseError(message.to_string()) }
+
+/// Compares a spot table against `snapshots/<name>.snap` in the crate root.
+/// Set `YUI_BLESS=1` to write the current table as the new golden file.
+pub fn assert_golden(spot_table: &SpotTable, name: &str) {
+	let snapshot = SpotSnapshot::from(spot_table);
+	let text = snapshot.to_string();
+	let path = golden_path(name);
+	if std::env::var("YUI_BLESS").map(|it| !it.is_empty() && it != "0").unwrap_or(false) {
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent).expect("create snapshots dir");
+		}
+		std::fs::write(&path, &text).expect("write golden snapshot");
+		return;
+	}
+	let golden_text = match std::fs::read_to_string(&path) {
+		Ok(text) => text,
+		Err(_) => panic!("missing golden snapshot {}, run with YUI_BLESS=1 to create it\n{}", path.display(), text),
+	};
+	let golden = golden_text.parse::<SpotSnapshot>().unwrap_or_else(|e| panic!("{} in {}", e, path.display()));
+	if golden != snapshot {
+		panic!("snapshot {} does not match, run with YUI_BLESS=1 to update it\n{}", name, diff_lines(&golden_text, &text));
+	}
+}
+
+fn golden_path(name: &str) -> PathBuf {
+	let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+	PathBuf::from(root).join("snapshots").join(format!("{}.snap", name))
+}
+
+fn diff_lines(expected: &str, actual: &str) -> String {
+	let expected = expected.lines().collect::<Vec<_>>();
+	let actual = actual.lines().collect::<Vec<_>>();
+	let count = expected.len().max(actual.len());
+	(0..count).map(|i| {
+		let left = expected.get(i).cloned().unwrap_or("");
+		let right = actual.get(i).cloned().unwrap_or("");
+		if left == right {
+			format!("  {}\n", left)
+		} else {
+			format!("- {}\n+ {}\n", left, right)
+		}
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Bounds, DrawPad, FillColor, FillGrade, StrokeColor, StrokeStyle};
+	use crate::spot::spot_snapshot::SpotSnapshot;
+	use crate::spot::spot_table::SpotTable;
+
+	#[test]
+	fn print_parse() {
+		let mut spot_table = SpotTable::new(2, 3);
+		spot_table.fill(&Bounds::new(3, 2), FillColor::Primary);
+		spot_table.grade(&Bounds::new(1, 1), FillGrade::Focus);
+		spot_table.glyph(&Bounds::new(3, 1), "ab", StrokeColor::BodyOnPrimary);
+		spot_table.dark(&Bounds::new(3, 2), &Bounds::new(3, 1));
+		let snapshot = SpotSnapshot::from(&spot_table);
+		let text = snapshot.to_string();
+		assert_eq!(text, "\
+3x2
+glyph
+|ab |
+|   |
+fill P=Primary
+|PPP|
+|PPP|
+grade .=Plain f=Focus
+|f..|
+|...|
+stroke .=None g=BodyOnPrimary
+|gg.|
+|...|
+dark #=4 .=0
+|...|
+|###|
+");
+		assert_eq!(text.parse::<SpotSnapshot>(), Ok(snapshot));
+	}
+
+	#[test]
+	fn wide_and_combined_glyphs() {
+		let mut spot_table = SpotTable::new(3, 6);
+		spot_table.glyph(&Bounds::new(6, 1), "日本e\u{301}", StrokeColor::BodyOnBackground);
+		spot_table.glyph(&Bounds::new(6, 2).pad(0, 0, 1, 0), "a👍b", StrokeColor::BodyOnBackground);
+		spot_table.glyph(&Bounds::new(5, 3).pad(1, 0, 2, 0), "x日本", StrokeColor::BodyOnBackground);
+		let snapshot = SpotSnapshot::from(&spot_table);
+		let glyphs = snapshot.fronts.iter().map(|row| {
+			row.iter().map(|front| front.stroke.as_ref().map(|(glyph, _)| glyph.as_str()).unwrap_or("_")).collect::<Vec<_>>()
+		}).collect::<Vec<_>>();
+		assert_eq!(glyphs[0], vec!["日", "", "本", "", "e\u{301}", "_"]);
+		assert_eq!(glyphs[1], vec!["a", "👍", "", "b", "_", "_"]);
+		assert_eq!(glyphs[2], vec!["_", "x", "日", "", " ", "_"]);
+		assert!(snapshot.fronts[0][1].is_continuation());
+		spot_table.styled_glyph(&Bounds::new(6, 3).pad(5, 0, 2, 0), "s", StrokeColor::BodyOnBackground, StrokeStyle::ITALIC | StrokeStyle::REVERSE);
+		let snapshot = SpotSnapshot::from(&spot_table);
+		assert!(snapshot.to_string().ends_with("style .=PLAIN a=ITALIC|REVERSE\n|......|\n|......|\n|.....a|\n"));
+		assert_eq!(snapshot.to_string().parse::<SpotSnapshot>(), Ok(snapshot));
+	}
+}
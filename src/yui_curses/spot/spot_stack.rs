@@ -1,4 +1,4 @@
-use crate::palette::{FillColor, FillGrade, StrokeColor};
+use crate::palette::{FillColor, FillGrade, StrokeColor, StrokeStyle};
 use crate::yui_curses::spot::spot_field::SpotField;
 use crate::yui_curses::spot::SpotFront;
 
@@ -6,8 +6,8 @@ use crate::yui_curses::spot::SpotFront;
 pub struct SpotStack {
 	fill_color: SpotField<FillColor>,
 	fill_grade: SpotField<FillGrade>,
-	stroke: SpotField<Option<(String, StrokeColor)>>,
-	dark: SpotField<bool>,
+	stroke: SpotField<Option<(String, StrokeColor, StrokeStyle)>>,
+	dark: SpotField<u8>,
 }
 
 impl SpotStack {
@@ -16,7 +16,7 @@ impl SpotStack {
 			fill_color: SpotField::new(FillColor::Background),
 			fill_grade: SpotField::new(FillGrade::Plain),
 			stroke: SpotField::new(None),
-			dark: SpotField::new(false),
+			dark: SpotField::new(0),
 		}
 	}
 	pub fn expand_seam(&self, z: i32, depth: i32) -> Self {
@@ -57,24 +57,29 @@ impl SpotStack {
 		self.fill_grade.set_near_equal(grade, z);
 	}
 
-	pub fn set_stroke(&mut self, glyph: String, color: StrokeColor, z: i32) {
-		self.stroke.set_near_equal(Some((glyph, color)), z);
+	pub fn set_stroke(&mut self, glyph: String, color: StrokeColor, style: StrokeStyle, z: i32) {
+		self.stroke.set_near_equal(Some((glyph, color, style)), z);
 	}
 
-	pub fn set_dark(&mut self, z: i32) {
-		self.dark.set_near_equal(true, z);
+	pub fn set_dark(&mut self, level: u8, z: i32) {
+		self.dark.set_near_equal(level, z);
 	}
 
 	pub fn to_front(&self) -> SpotFront {
+		let (stroke, stroke_style) = match self.real_stroke() {
+			Some((glyph, color, style)) => (Some((glyph, color)), style),
+			None => (None, StrokeStyle::PLAIN),
+		};
 		SpotFront {
 			fill_color: self.fill_color.value,
 			fill_grade: self.real_grade(),
-			stroke: self.real_stroke(),
+			stroke,
+			stroke_style,
 			dark: self.real_dark(),
 		}
 	}
 
-	fn real_stroke(&self) -> Option<(String, StrokeColor)> {
+	fn real_stroke(&self) -> Option<(String, StrokeColor, StrokeStyle)> {
 		if self.stroke.z <= self.fill_color.z {
 			self.stroke.value.clone()
 		} else {
@@ -82,8 +87,8 @@ impl SpotStack {
 		}
 	}
 
-	fn real_dark(&self) -> bool {
-		self.dark.value && (self.dark.z <= self.fill_color.z)
+	fn real_dark(&self) -> u8 {
+		if self.dark.z <= self.fill_color.z { self.dark.value } else { 0 }
 	}
 
 	fn real_grade(&self) -> FillGrade {
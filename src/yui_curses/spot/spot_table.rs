@@ -2,8 +2,8 @@ use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 
-use crate::{Bounds, DrawPad, FillColor, FillGrade, StoryId, StrokeColor};
-use crate::spot::SpotFront;
+use crate::{Bounds, DrawPad, FillColor, FillGrade, StoryId, StrokeColor, StrokeStyle};
+use crate::spot::{glyph_cells, SpotFront};
 use crate::yui_curses::spot::spot_stack::SpotStack;
 
 #[derive(Debug, Clone)]
@@ -12,6 +12,8 @@ pub struct SpotTable {
 	cols: i32,
 	spots: Vec<RefCell<SpotStack>>,
 	seams: HashMap<StoryId, HashSet<Bounds>>,
+	clips: Vec<Bounds>,
+	hover_id: i32,
 }
 
 impl SpotTable {
@@ -22,8 +24,11 @@ impl SpotTable {
 			cols: width,
 			spots: vec![origin_stack; (width * height) as usize].into_iter().map(|it| RefCell::new(it)).collect(),
 			seams: HashMap::new(),
+			clips: Vec::new(),
+			hover_id: 0,
 		}
 	}
+	pub fn with_hover_id(self, hover_id: i32) -> Self { SpotTable { hover_id, ..self } }
 	pub fn expand_seam(&self, z: i32, depth: i32, exclude: (&StoryId, &Bounds)) -> Self {
 		let spots = self.spots.iter().map(|spot| {
 			let spot = spot.borrow();
@@ -48,7 +53,7 @@ impl SpotTable {
 				}
 			}
 		}
-		SpotTable { rows: self.rows, cols: self.cols, spots, seams }
+		SpotTable { rows: self.rows, cols: self.cols, spots, seams, clips: Vec::new(), hover_id: self.hover_id }
 	}
 	pub fn insert_seam(&mut self, from: &SpotTable, z: i32, left: i32, top: i32) {
 		for from_x in 0..from.cols {
@@ -110,6 +115,11 @@ impl SpotTable {
 		}
 	}
 
+	fn is_drawable(&self, y: i32, x: i32) -> bool {
+		let in_table = y >= 0 && y < self.rows && x >= 0 && x < self.cols;
+		in_table && self.clips.last().map(|clip| clip.intersects(y, x)).unwrap_or(true)
+	}
+
 	pub fn to_fronts(&self) -> Vec<Vec<SpotFront>> {
 		let mut table = Vec::new();
 		for i in 0..self.spots.len() {
@@ -128,7 +138,7 @@ impl SpotTable {
 impl DrawPad for SpotTable {
 	fn fill(&mut self, bounds: &Bounds, color: FillColor) {
 		for (x, y, z) in bounds.iter() {
-			if y >= 0 && y < self.rows && x >= 0 && x < self.cols {
+			if self.is_drawable(y, x) {
 				let stack = self.spot_stack(y, x);
 				stack.borrow_mut().set_fill(color, z);
 			}
@@ -137,41 +147,68 @@ impl DrawPad for SpotTable {
 
 	fn grade(&mut self, bounds: &Bounds, grade: FillGrade) {
 		for (x, y, z) in bounds.iter() {
-			if y >= 0 && y < self.rows && x >= 0 && x < self.cols {
+			if self.is_drawable(y, x) {
 				let stack = self.spot_stack(y, x);
 				stack.borrow_mut().set_fill_grade(grade, z);
 			}
 		}
 	}
 
-	fn glyph(&mut self, bounds: &Bounds, glyph: &str, color: StrokeColor) {
+	fn styled_glyph(&mut self, bounds: &Bounds, glyph: &str, color: StrokeColor, style: StrokeStyle) {
+		let cells = glyph_cells(glyph);
 		for (x, y, z) in bounds.iter() {
-			if y >= 0 && y < self.rows && x >= 0 && x < self.cols {
-				let chars = glyph.chars().collect::<Vec<_>>();
-				let string_index = x - bounds.left;
-				if string_index >= 0 && string_index < (chars.len() as i32) {
-					let start = string_index as usize;
-					let sub_glyph: String = chars[start..(start + 1)].iter().collect();
-					self.spot_stack(y, x).borrow_mut().set_stroke(sub_glyph, color, z)
+			if self.is_drawable(y, x) {
+				let index = (x - bounds.left) as usize;
+				if let Some(cell) = cells.get(index) {
+					// Blank the visible half of a wide glyph that is cut by the bounds or the clip.
+					let cell = if cell.is_empty() {
+						let head_drawn = index > 0 && self.is_drawable(y, x - 1);
+						if head_drawn { String::new() } else { " ".to_string() }
+					} else {
+						let has_tail = cells.get(index + 1).map(|it| it.is_empty()).unwrap_or(false);
+						let tail_fits = x + 1 < bounds.right && self.is_drawable(y, x + 1);
+						if has_tail && !tail_fits { " ".to_string() } else { cell.clone() }
+					};
+					self.spot_stack(y, x).borrow_mut().set_stroke(cell, color, style, z)
 				}
 			}
 		}
 	}
 
-	fn dark(&mut self, bounds: &Bounds, exclude: &Bounds) {
+	fn shade(&mut self, bounds: &Bounds, exclude: &Bounds, level: u8) {
 		for (x, y, z) in bounds.iter() {
-			if y >= 0 && y < self.rows && x >= 0 && x < self.cols {
+			if self.is_drawable(y, x) {
 				if !exclude.intersects(y, x) {
 					let stack = self.spot_stack(y, x);
-					stack.borrow_mut().set_dark(z)
+					stack.borrow_mut().set_dark(level, z)
 				}
 			}
 		}
 	}
 
+	fn push_clip(&mut self, bounds: &Bounds) {
+		let clip = match self.clips.last() {
+			None => bounds.clone(),
+			Some(outer) => {
+				let mut clip = bounds.clone();
+				clip.left = clip.left.max(outer.left);
+				clip.top = clip.top.max(outer.top);
+				clip.right = clip.right.min(outer.right).max(clip.left);
+				clip.bottom = clip.bottom.min(outer.bottom).max(clip.top);
+				clip
+			}
+		};
+		self.clips.push(clip);
+	}
+
+	fn pop_clip(&mut self) {
+		self.clips.pop();
+	}
+
 	fn story(&mut self, bounds: &Bounds, story_id: StoryId) {
 		let mut story_bounds = self.seams.remove(&story_id).unwrap_or_else(|| HashSet::new());
 		story_bounds.insert(bounds.clone());
 		self.seams.insert(story_id, story_bounds);
 	}
+	fn hover_id(&self) -> i32 { self.hover_id }
 }
\ No newline at end of file
@@ -0,0 +1,36 @@
+//! Runs alone in its own test binary so the process thread count belongs to this test.
+#![cfg(target_os = "linux")]
+
+use std::sync::mpsc::channel;
+
+use rand::random;
+
+use yui::{SenderLink, Spark};
+use yui::sparks::selection_editor::{SelectionAction, SelectionEditorSpark};
+use yui::yard::{ButtonModel, PressModel, Priority};
+use yui::yard::model::ScrollModel;
+
+fn thread_count() -> usize {
+	let status = std::fs::read_to_string("/proc/self/status").expect("read /proc/self/status");
+	status.lines()
+		.find_map(|line| line.strip_prefix("Threads:"))
+		.and_then(|count| count.trim().parse().ok())
+		.expect("thread count")
+}
+
+#[test]
+fn render_does_not_spawn_threads() {
+	let (tx, _rx) = channel();
+	let link = SenderLink::new(tx);
+	let choices = (0..100).collect::<Vec<_>>();
+	let presses = choices.iter().map(|_| PressModel::new(random())).collect::<Vec<_>>();
+	let scroll = ScrollModel::new_count_height(random(), choices.len(), 3, 0);
+	let button = ButtonModel::enabled("Close", link.to_sync().map(|_| SelectionAction::Close), Priority::None);
+	let state = (choices, presses, scroll, button);
+	// The first render starts the shared link dispatcher.
+	SelectionEditorSpark::<usize>::render(&state, &link);
+	let before = thread_count();
+	let yards = (0..20).map(|_| SelectionEditorSpark::<usize>::render(&state, &link)).collect::<Vec<_>>();
+	assert_eq!(yards.len(), 20);
+	assert_eq!(thread_count(), before);
+}